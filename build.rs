@@ -0,0 +1,39 @@
+// Picks MyUnique's pointer representation from the repr-* features (see
+// Cargo.toml and src/my_unique.rs), as `cfg(repr = "...")`.
+//
+// With the c-abi feature, compiles the C side of its demo (c/, and see
+// src/c_abi.rs) into a static library for the library crate to link, with
+// the system's C compiler: `$CC`, or else `cc`.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=c");
+    println!("cargo:rerun-if-env-changed=CC");
+    // Features are additive, so more than one may be on (as with
+    // --all-features): the first of these wins.
+    let repr = ["const-ptr", "nonnull", "unique-emulation"]
+        .into_iter()
+        .find(|r| env::var_os(format!("CARGO_FEATURE_REPR_{}", r.to_uppercase().replace('-', "_"))).is_some())
+        .unwrap_or("unique-emulation");
+    println!("cargo:rustc-check-cfg=cfg(repr, values(\"const-ptr\", \"nonnull\", \"unique-emulation\"))");
+    println!("cargo:rustc-cfg=repr=\"{}\"", repr);
+    if env::var_os("CARGO_FEATURE_C_ABI").is_none() {
+        return;
+    }
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    let obj = out.join("mybox3_test.o");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    run(Command::new(cc).args(["-c", "-fPIC", "-Wall", "-Wextra", "-Werror", "-o"]).arg(&obj).arg("c/mybox3_test.c"));
+    run(Command::new("ar").arg("crs").arg(out.join("libmybox3_test.a")).arg(&obj));
+    println!("cargo:rustc-link-search=native={}", out.display());
+    println!("cargo:rustc-link-lib=static=mybox3_test");
+}
+
+fn run(cmd: &mut Command) {
+    let status = cmd.status().unwrap_or_else(|e| panic!("couldn't run {:?}: {}", cmd, e));
+    assert!(status.success(), "{:?} failed: {}", cmd, status);
+}
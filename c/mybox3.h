@@ -0,0 +1,38 @@
+/* MyBox3, from C: what the c-abi feature exports (see src/c_abi.rs).
+ *
+ * Each box is owned by whoever holds the pointer, and must be given to
+ * the matching drop function exactly once; NULL may be, and does nothing.
+ * Nothing checks any of this: the compiler that would have is on the
+ * other side of the boundary.
+ */
+
+#ifndef MYBOX3_H
+#define MYBOX3_H
+
+#include <stdint.h>
+
+/* MyBox3<i32>. */
+typedef struct mybox3_i32 mybox3_i32;
+
+mybox3_i32 *mybox3_new_i32(int32_t v);
+int32_t mybox3_get_i32(const mybox3_i32 *b);
+void mybox3_set_i32(mybox3_i32 *b, int32_t v);
+void mybox3_drop_i32(mybox3_i32 *b);
+
+/* MyBox3<&i32>: borrows `*v`, and so must be dropped while `*v` is still
+ * there. In Rust, that is the lifetime's job (and, for dropck, the
+ * PhantomData's); here, it is the caller's. */
+typedef struct mybox3_ref mybox3_ref;
+
+mybox3_ref *mybox3_new_ref(const int32_t *v);
+int32_t mybox3_get_ref(const mybox3_ref *b);
+void mybox3_drop_ref(mybox3_ref *b);
+
+/* MyBox3<PrintOnDrop<i32>>, named `name` (copied): dropping it from C runs
+ * the PrintOnDrop's destructor, in Rust. */
+typedef struct mybox3_print_on_drop mybox3_print_on_drop;
+
+mybox3_print_on_drop *mybox3_new_print_on_drop(const char *name, int32_t v);
+void mybox3_drop_print_on_drop(mybox3_print_on_drop *b);
+
+#endif
@@ -0,0 +1,34 @@
+/* Uses MyBox3 from C, as a C program would, and is called back from the
+ * c_abi demo to check that it went as it should. */
+
+#include <stddef.h>
+
+#include "mybox3.h"
+
+#define CHECK(cond) do { if (!(cond)) return __LINE__; } while (0)
+
+/* 0 if all went well, or the line of the first check that failed. */
+int mybox3_c_test(void)
+{
+    mybox3_i32 *b = mybox3_new_i32(13);
+    CHECK(b != NULL);
+    CHECK(mybox3_get_i32(b) == 13);
+    mybox3_set_i32(b, 42);
+    CHECK(mybox3_get_i32(b) == 42);
+    mybox3_drop_i32(b);
+    mybox3_drop_i32(NULL);
+
+    {
+        int32_t v = 7;
+        mybox3_ref *r = mybox3_new_ref(&v);
+        CHECK(mybox3_get_ref(r) == 7);
+        /* Before `v` goes: nothing stops us from doing it after. */
+        mybox3_drop_ref(r);
+    }
+
+    mybox3_print_on_drop *p = mybox3_new_print_on_drop("c_abi_first", 1);
+    mybox3_print_on_drop *q = mybox3_new_print_on_drop("c_abi_second", 2);
+    mybox3_drop_print_on_drop(q);
+    mybox3_drop_print_on_drop(p);
+    return 0;
+}
@@ -0,0 +1,140 @@
+// `#[derive(DropTrace)]` gives a struct or enum a Drop impl that records
+// an `Event::Traced` with the type's name in whyPhantomData's recorder,
+// so that a demo can check when values of its own types are dropped
+// without wrapping every one of them in a PrintOnDrop.
+//
+// The generated code names `crate::recorder`, so it is only for use
+// inside whyPhantomData. It parses the item by hand (just far enough to
+// find its name, generics and where clause) rather than pull in syn.
+//
+// Since the derive *is* the Drop impl, the type can't have another, and,
+// like any Drop impl without #[may_dangle], it makes dropck insist that
+// whatever the type borrows outlives it.
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+#[proc_macro_derive(DropTrace)]
+pub fn derive_drop_trace(input: TokenStream) -> TokenStream {
+    let mut tokens = input.into_iter().peekable();
+
+    // Skip attributes and visibility, up to `struct`/`enum`/`union`.
+    for t in tokens.by_ref() {
+        if let TokenTree::Ident(i) = &t {
+            if matches!(i.to_string().as_str(), "struct" | "enum" | "union") {
+                break;
+            }
+        }
+    }
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        _ => return error("expected a type name"),
+    };
+
+    // `<...>`, as written (with bounds and defaults) and as just the
+    // parameter names.
+    let mut generics = Vec::new();
+    if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '<') {
+        tokens.next();
+        let mut depth = 1;
+        let mut after_dash = false;
+        for t in tokens.by_ref() {
+            if let TokenTree::Punct(p) = &t {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    // Not the `>` of `->`.
+                    '>' if !after_dash => depth -= 1,
+                    _ => {}
+                }
+                after_dash = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+            } else {
+                after_dash = false;
+            }
+            if depth == 0 {
+                break;
+            }
+            generics.push(t);
+        }
+    }
+    let params = split_params(&generics);
+
+    // A where clause comes before a brace-delimited body, but after a
+    // tuple struct's fields.
+    let mut where_clause = String::new();
+    let mut in_where = false;
+    for t in tokens {
+        match &t {
+            TokenTree::Ident(i) if i.to_string() == "where" => in_where = true,
+            TokenTree::Group(g) if in_where && g.delimiter() == Delimiter::Brace => break,
+            TokenTree::Punct(p) if in_where && p.as_char() == ';' => break,
+            _ => {}
+        }
+        if in_where {
+            where_clause.push_str(&t.to_string());
+            where_clause.push(' ');
+        }
+    }
+
+    let decls: Vec<String> = params.iter().map(|p| p.decl.to_string()).collect();
+    let names: Vec<&str> = params.iter().map(|p| p.name.as_str()).collect();
+    let (generics, params) = (decls.join(", "), names.join(", "));
+    format!(
+        "impl<{generics}> Drop for {name}<{params}> {where_clause} {{
+            fn drop(&mut self) {{
+                crate::recorder::record(crate::recorder::Event::Traced(::std::borrow::Cow::Borrowed(\"{name}\")));
+            }}
+        }}"
+    )
+    .parse()
+    .unwrap()
+}
+
+struct Param {
+    // As declared, minus any default (which an impl may not repeat).
+    decl: TokenStream,
+    // As used: `'a`, `T` or `N`.
+    name: String,
+}
+
+// Splits `'a, T: Clone = u8, const N: usize` at the top-level commas.
+fn split_params(generics: &[TokenTree]) -> Vec<Param> {
+    let mut params = Vec::new();
+    // Commas inside a bound's own `<...>` don't count: glue those pieces
+    // back on to the parameter they belong to.
+    let mut current: Vec<TokenTree> = Vec::new();
+    let mut depth = 0i32;
+    for piece in generics.split(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ',')) {
+        if !current.is_empty() {
+            current.push(TokenTree::Punct(proc_macro::Punct::new(',', Spacing::Alone)));
+        }
+        for t in piece {
+            if let TokenTree::Punct(p) = t {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    '>' => depth -= 1,
+                    _ => {}
+                }
+            }
+            current.push(t.clone());
+        }
+        if depth == 0 && !current.is_empty() {
+            params.push(param(std::mem::take(&mut current)));
+        }
+    }
+    params
+}
+
+fn param(tokens: Vec<TokenTree>) -> Param {
+    let name = match &tokens[..] {
+        [TokenTree::Punct(p), lt, ..] if p.as_char() == '\'' => format!("'{}", lt),
+        [TokenTree::Ident(c), n, ..] if c.to_string() == "const" => n.to_string(),
+        [t, ..] => t.to_string(),
+        [] => unreachable!(),
+    };
+    let end = tokens.iter().position(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == '='));
+    let decl = tokens[..end.unwrap_or(tokens.len())].iter().cloned().collect();
+    Param { decl, name }
+}
+
+fn error(msg: &str) -> TokenStream {
+    format!("compile_error!({:?});", msg).parse().unwrap()
+}
@@ -0,0 +1,152 @@
+// `#[lesson(...)]` goes on a module's `pub fn demo()`, in place of the
+// `demo!` line, and registers the demo along with the teaching text that
+// goes with it:
+//
+//     /// What the demo shows, in as many paragraphs as it takes.
+//     #[lesson(
+//         title = "phantom_owner!: PhantomData, eyepatch and size checks in one",
+//         tags = ["dropck", "macros"],
+//         notes = ["`let (_s, v2)` with a borrowing PrintOnDrop won't compile"],
+//         drops = ["a", "b"],
+//     )]
+//     pub fn demo() { ... }
+//
+// The doc comment is the explanation; `notes` say what is expected to
+// compile and what isn't; `tags`, `drops` and `drops_any_order` are as
+// for `demo!`. Only `title` is required. The function is left as it is,
+// and next to it goes the module's DEMO, with a `crate::registry::Lesson`
+// attached.
+//
+// Like drop_trace, the generated code names `crate::registry`, so it is
+// only for use inside whyPhantomData, and the input is parsed by hand.
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+#[proc_macro_attribute]
+pub fn lesson(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = match parse_args(attr) {
+        Ok(args) => args,
+        Err(msg) => return error(msg),
+    };
+    let Some(title) = args.title else {
+        return error("#[lesson] needs a `title = \"...\"`");
+    };
+
+    // Doc comments arrive as `#[doc = "..."]`; the function's name is the
+    // identifier after `fn`.
+    let mut docs = Vec::new();
+    let mut name = None;
+    let mut tokens = item.clone().into_iter().peekable();
+    while let Some(t) = tokens.next() {
+        match &t {
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(g)) = tokens.peek() {
+                    let attr: Vec<TokenTree> = g.stream().into_iter().collect();
+                    if let [TokenTree::Ident(i), TokenTree::Punct(_), TokenTree::Literal(doc)] = &attr[..] {
+                        if i.to_string() == "doc" {
+                            docs.push(doc.to_string());
+                        }
+                    }
+                }
+            }
+            TokenTree::Ident(i) if i.to_string() == "fn" => {
+                if let Some(TokenTree::Ident(n)) = tokens.next() {
+                    name = Some(n.to_string());
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+    let Some(name) = name else {
+        return error("#[lesson] goes on a function");
+    };
+
+    let drops = match args.drops {
+        Some((variant, drops)) => format!("Some(crate::registry::Expected::{}(&[{}]))", variant, drops.join(", ")),
+        None => "None".to_string(),
+    };
+    let demo: TokenStream = format!(
+        "pub const DEMO: crate::registry::Demo = crate::registry::Demo {{
+            path: module_path!(),
+            description: {title},
+            tags: &[{tags}],
+            expected_drops: {drops},
+            run: {name},
+            lesson: Some(&crate::registry::Lesson {{
+                explanation: &[{docs}],
+                notes: &[{notes}],
+            }}),
+            unsound: false,
+        }};",
+        tags = args.tags.join(", "),
+        docs = docs.join(", "),
+        notes = args.notes.join(", "),
+    )
+    .parse()
+    .unwrap();
+    item.into_iter().chain(demo).collect()
+}
+
+#[derive(Default)]
+struct Args {
+    // All as the literals' source text, to be pasted back as is.
+    title: Option<String>,
+    tags: Vec<String>,
+    notes: Vec<String>,
+    // The crate::registry::Expected variant, and its drops.
+    drops: Option<(&'static str, Vec<String>)>,
+}
+
+// `key = "literal"` or `key = ["literal", ...]`, separated by commas.
+fn parse_args(attr: TokenStream) -> Result<Args, &'static str> {
+    let mut args = Args::default();
+    let mut tokens = attr.into_iter();
+    while let Some(key) = tokens.next() {
+        let TokenTree::Ident(key) = key else {
+            return Err("expected `key = value`");
+        };
+        if !matches!(tokens.next(), Some(TokenTree::Punct(p)) if p.as_char() == '=') {
+            return Err("expected `=` after a key");
+        }
+        let value = tokens.next();
+        match (key.to_string().as_str(), value) {
+            ("title", Some(TokenTree::Literal(l))) => args.title = Some(l.to_string()),
+            ("tags", Some(TokenTree::Group(g))) => args.tags = literals(g.delimiter(), g.stream())?,
+            ("notes", Some(TokenTree::Group(g))) => args.notes = literals(g.delimiter(), g.stream())?,
+            ("drops", Some(TokenTree::Group(g))) => args.drops = Some(("InOrder", literals(g.delimiter(), g.stream())?)),
+            ("drops_any_order", Some(TokenTree::Group(g))) => {
+                args.drops = Some(("AnyOrder", literals(g.delimiter(), g.stream())?))
+            }
+            ("title" | "tags" | "notes" | "drops" | "drops_any_order", _) => {
+                return Err("expected a string, or a list of them")
+            }
+            _ => return Err("unknown key; expected title, tags, notes, drops or drops_any_order"),
+        }
+        match tokens.next() {
+            None => break,
+            Some(TokenTree::Punct(p)) if p.as_char() == ',' => {}
+            Some(_) => return Err("expected `,` between keys"),
+        }
+    }
+    Ok(args)
+}
+
+fn literals(delimiter: Delimiter, stream: TokenStream) -> Result<Vec<String>, &'static str> {
+    if delimiter != Delimiter::Bracket {
+        return Err("expected a list in `[...]`");
+    }
+    let mut lits = Vec::new();
+    for t in stream {
+        match t {
+            TokenTree::Literal(l) => lits.push(l.to_string()),
+            TokenTree::Punct(p) if p.as_char() == ',' => {}
+            _ => return Err("expected a list of strings"),
+        }
+    }
+    Ok(lits)
+}
+
+fn error(msg: &str) -> TokenStream {
+    format!("compile_error!({:?});", msg).parse().unwrap()
+}
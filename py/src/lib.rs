@@ -0,0 +1,100 @@
+// The demos from Python (see why_phantom_data.py, next to this crate's
+// Cargo.toml), for driving them from a notebook.
+//
+// pyo3 would make this a Python module of its own; without it, it's a
+// shared library with a small C ABI, which the Python side loads with
+// ctypes. Everything crosses the boundary as JSON, in the library's own
+// format (see trace.rs), and Python turns it into objects:
+//
+//     char *wpd_list_demos(void);             /* [DemoInfo, ...] */
+//     char *wpd_run_demo(const char *name);   /* a run, or {"error": "..."} */
+//     void wpd_free(char *json);
+//     void wpd_mark_main_thread(void);        /* see main_thread.rs */
+//
+// A run is the demo's DemoInfo, what it left in the recorder (`trace`),
+// every event observed on the running thread (`events`) and its failure
+// message, or null.
+
+use std::ffi::{c_char, CStr, CString};
+use std::fmt::Debug;
+use std::sync::Mutex;
+
+use why_phantom_data::{demos, mark_main_thread, run_demo, DropObserver, State, ToJson, Value};
+
+// Every event, in order, as JSON: what a Python caller can't observe any
+// other way.
+#[derive(Default)]
+struct Events(Mutex<Vec<Value>>);
+
+impl Events {
+    fn push(&self, kind: &str, fields: Vec<Value>) {
+        self.0.lock().unwrap().push(Value::Object(vec![(kind.to_string(), Value::Array(fields))]));
+    }
+}
+
+impl DropObserver for Events {
+    fn on_construct(&self, name: &str, id: u64) {
+        self.push("Construct", vec![Value::from(name), Value::from(id)]);
+    }
+
+    fn on_drop(&self, name: &str, id: u64, state: State, _: &dyn Debug) {
+        self.push("Drop", vec![Value::from(name), Value::from(id), state.to_json()]);
+    }
+
+    fn on_invalid_access(&self, name: &str, id: u64) {
+        self.push("InvalidAccess", vec![Value::from(name), Value::from(id)]);
+    }
+}
+
+fn into_c(v: Value) -> *mut c_char {
+    // JSON escapes control characters, NUL among them.
+    CString::new(v.to_string()).unwrap().into_raw()
+}
+
+fn error(msg: String) -> *mut c_char {
+    into_c(Value::Object(vec![("error".to_string(), Value::String(msg))]))
+}
+
+#[no_mangle]
+pub extern "C" fn wpd_list_demos() -> *mut c_char {
+    into_c(Value::Array(demos().iter().map(ToJson::to_json).collect()))
+}
+
+/// # Safety
+///
+/// `name` must be a NUL-terminated string, valid for reads during the call.
+#[no_mangle]
+pub unsafe extern "C" fn wpd_run_demo(name: *const c_char) -> *mut c_char {
+    let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else {
+        return error("demo names are UTF-8".to_string());
+    };
+    let mut events = Events::default();
+    match run_demo(name, &mut events) {
+        Ok(r) => into_c(Value::Object(vec![
+            ("demo".to_string(), r.demo.to_json()),
+            ("trace".to_string(), r.trace.to_json()),
+            ("events".to_string(), Value::Array(events.0.into_inner().unwrap())),
+            ("failure".to_string(), r.failure.map_or(Value::Null, Value::String)),
+        ])),
+        Err(e) => error(e.to_string()),
+    }
+}
+
+/// # Safety
+///
+/// `json` must be null, or have come from `wpd_list_demos` or
+/// `wpd_run_demo` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn wpd_free(json: *mut c_char) {
+    if !json.is_null() {
+        drop(unsafe { CString::from_raw(json) });
+    }
+}
+
+/// # Safety
+///
+/// It must be called from the process's main thread.
+#[no_mangle]
+pub unsafe extern "C" fn wpd_mark_main_thread() {
+    unsafe { mark_main_thread() };
+}
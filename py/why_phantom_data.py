@@ -0,0 +1,132 @@
+"""The whyPhantomData demos, from Python.
+
+Build the shared library first (`cargo +nightly build -p why_phantom_data_py`),
+then, from a notebook:
+
+    import why_phantom_data as wpd
+
+    for d in wpd.list_demos():
+        print(d.name, d.tags, d.description)
+
+    run = wpd.run_demo("scope_guard")
+    for e in run.events:
+        print(e.kind, e.name, e.id, e.state)
+
+The library is looked for in $WHY_PHANTOM_DATA_LIB, then in the
+workspace's target/debug and target/release. What the demos print goes to
+the process's own stdout, which a notebook may not show; the events are
+what's meant to be looked at.
+"""
+
+import ctypes
+import json
+import os
+import sys
+import threading
+from dataclasses import dataclass, field
+from typing import List, Optional
+
+_HERE = os.path.dirname(os.path.abspath(__file__))
+_NAME = {"darwin": "libwhy_phantom_data_py.dylib", "win32": "why_phantom_data_py.dll"}.get(
+    sys.platform, "libwhy_phantom_data_py.so"
+)
+
+
+def _load():
+    path = os.environ.get("WHY_PHANTOM_DATA_LIB")
+    candidates = [path] if path else [
+        os.path.join(_HERE, "..", "target", profile, _NAME) for profile in ("debug", "release")
+    ]
+    for path in candidates:
+        if os.path.exists(path):
+            lib = ctypes.CDLL(path)
+            # Returned as void pointers, not c_char_p, so that they can be
+            # handed back to be freed.
+            lib.wpd_list_demos.restype = ctypes.c_void_p
+            lib.wpd_run_demo.restype = ctypes.c_void_p
+            lib.wpd_run_demo.argtypes = [ctypes.c_char_p]
+            lib.wpd_free.argtypes = [ctypes.c_void_p]
+            # For MainThreadToken (see src/main_thread.rs): Python's main
+            # thread is the process's.
+            if threading.current_thread() is threading.main_thread():
+                lib.wpd_mark_main_thread()
+            return lib
+    raise ImportError(f"can't find {_NAME} (tried {candidates}); build it with cargo first")
+
+
+_lib = _load()
+
+
+def _call(f, *args):
+    p = f(*args)
+    try:
+        return json.loads(ctypes.string_at(p).decode("utf-8"))
+    finally:
+        _lib.wpd_free(p)
+
+
+@dataclass
+class DemoInfo:
+    name: str
+    description: str
+    tags: List[str]
+    expected_drops: Optional[List[str]]
+    # If not, expected_drops may come in any order.
+    drops_in_order: bool
+    unsound: bool
+    explanation: List[str]
+    notes: List[str]
+
+
+@dataclass
+class Event:
+    """One thing that happened: `kind` is Construct, Drop, InvalidAccess,
+    Guard, Traced, Enter or Exit. Guards, DropTrace types and scopes have
+    only a name; `state` is only for drops."""
+
+    kind: str
+    name: str
+    id: Optional[int] = None
+    state: Optional[str] = None
+
+
+@dataclass
+class Run:
+    demo: DemoInfo
+    # Every event observed on the thread the demo ran on, in order.
+    events: List[Event]
+    # What the demo left in the recorder, unchecked by the demo itself.
+    trace: List[Event]
+    lost: int = 0
+    failure: Optional[str] = None
+    drops: List[str] = field(init=False)
+
+    def __post_init__(self):
+        self.drops = [e.name for e in self.events if e.kind == "Drop"]
+
+
+class DemoError(Exception):
+    pass
+
+
+def _event(e):
+    # Externally tagged, as in trace.rs: {"Drop": ["name", 3, "Valid"]}.
+    [(kind, v)] = e.items()
+    return Event(kind, *v) if isinstance(v, list) else Event(kind, v)
+
+
+def list_demos() -> List[DemoInfo]:
+    return [DemoInfo(**d) for d in _call(_lib.wpd_list_demos)]
+
+
+def run_demo(name: str) -> Run:
+    r = _call(_lib.wpd_run_demo, name.encode("utf-8"))
+    if "error" in r:
+        raise DemoError(r["error"])
+    return Run(
+        demo=DemoInfo(**r["demo"]),
+        events=[_event(e) for e in r["events"]],
+        trace=[_event(e) for e in r["trace"]["events"]],
+        lost=r["trace"]["lost"],
+        failure=r["failure"],
+    )
@@ -0,0 +1,121 @@
+// Dropck is one half of what a raw-pointer owner like MyBox3 has to get
+// right; the other is aliasing. A `&mut T` promises that nothing else
+// touches the `T` while it lives, and a `&T` that nothing writes to it.
+// The compiler checks that for references, but the moment one is turned
+// into a raw pointer (or made from one) the promise is ours to keep, and
+// breaking it is undefined behaviour even if the program "works".
+//
+// Miri checks these rules at run time (under Stacked Borrows by default,
+// or Tree Borrows with -Zmiri-tree-borrows). Each `ub_*` function below
+// breaks one, next to an `ok_*` counterpart that does the same job
+// correctly. The `ok_*` ones always run; the `ub_*` ones only under Miri,
+// one per run (Miri stops at the first UB it finds), picked by name:
+//
+//     for ub in mut_while_shared write_through_shared stale_after_retag; do
+//         MIRIFLAGS=-Zmiri-env-forward=ALIASING_UB ALIASING_UB=$ub \
+//             cargo +nightly miri run && echo "$ub: NOT caught"
+//     done
+//
+// Every one of them must make Miri report an error.
+
+use crate::MyBox3;
+
+impl<T> MyBox3<T> {
+    fn get(&self) -> &T {
+        unsafe { &*self.v.as_ptr() }
+    }
+
+    // Fine: the `&mut` comes straight from the allocation's own pointer,
+    // and `&mut self` guarantees it's the only one.
+    fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.v.as_ptr() }
+    }
+
+    // Raw pointer from raw pointer: no reference in between to restrict
+    // what it may do.
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.v.as_ptr()
+    }
+
+    // The pitfall: going through `get` gives a pointer derived from a
+    // `&T`, which only ever had permission to read.
+    #[cfg_attr(not(miri), allow(dead_code))]
+    fn as_mut_ptr_via_ref(&self) -> *mut T {
+        self.get() as *const T as *mut T
+    }
+}
+
+// Creating a `&mut` while a `&` to the same place is still in use.
+#[cfg_attr(not(miri), allow(dead_code))]
+fn ub_mut_while_shared() {
+    let mut b = MyBox3::new(1);
+    let p = b.as_mut_ptr();
+    let shared = unsafe { &*p };
+    let unique = unsafe { &mut *p }; // kills `shared`...
+    *unique = 2;
+    println!("{}", shared); // ...so this read is UB
+}
+
+fn ok_mut_while_shared() {
+    let mut b = MyBox3::new(1);
+    let p = b.as_mut_ptr();
+    let shared = unsafe { &*p };
+    println!("aliasing: read {} before writing", shared); // last use of `shared`
+    let unique = unsafe { &mut *p };
+    *unique = 2;
+    assert_eq!(*b.get(), 2);
+}
+
+// Writing through a pointer that was derived from a `&T`.
+#[cfg_attr(not(miri), allow(dead_code))]
+fn ub_write_through_shared() {
+    let b = MyBox3::new(1);
+    unsafe { *b.as_mut_ptr_via_ref() = 2 };
+}
+
+fn ok_write_through_shared() {
+    let mut b = MyBox3::new(1);
+    unsafe { *b.as_mut_ptr() = 2 };
+    assert_eq!(*b.get(), 2);
+}
+
+// Keeping a raw pointer from one `get_mut` across another: the second
+// reborrow from `self.v` invalidates everything derived from the first.
+#[cfg_attr(not(miri), allow(dead_code))]
+fn ub_stale_after_retag() {
+    let mut b = MyBox3::new(1);
+    let p: *mut i32 = b.get_mut();
+    *b.get_mut() = 2;
+    unsafe { *p = 3 }; // `p` is stale
+}
+
+fn ok_stale_after_retag() {
+    let mut b = MyBox3::new(1);
+    // Derive every access from the same pointer instead...
+    let p = b.as_mut_ptr();
+    unsafe {
+        *p = 2;
+        *p += 1;
+    }
+    // ...or simply get a fresh one after the other is done with.
+    *b.get_mut() += 1;
+    assert_eq!(*b.get(), 4);
+}
+
+crate::registry::demo!("Aliasing: what MyBox3's raw pointer must not break", tags: ["unsafe"]);
+
+pub fn demo() {
+    ok_mut_while_shared();
+    ok_write_through_shared();
+    ok_stale_after_retag();
+
+    #[cfg(miri)]
+    if let Ok(which) = std::env::var("ALIASING_UB") {
+        match which.as_str() {
+            "mut_while_shared" => ub_mut_while_shared(),
+            "write_through_shared" => ub_write_through_shared(),
+            "stale_after_retag" => ub_stale_after_retag(),
+            _ => panic!("ALIASING_UB: no such case {:?}", which),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// A global allocator that forwards to the system allocator while keeping
+// count of the bytes currently allocated, so demos can show memory that
+// is never given back, and of how many allocations have been made, so
+// they can show how often a container goes back to the allocator.
+//
+// The count is process-wide: anything allocating on another thread at the
+// same time shows up in it too. What a thread does inside `uncounted`
+// doesn't, which is for instrumentation (see replay.rs) that must not show
+// up in the demos' counts; what it allocates there, it must free there.
+//
+// With the `instrumented-alloc` feature it also hands every allocation and
+// free to alloc_tracker.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "instrumented-alloc")]
+use crate::alloc_tracker;
+
+pub struct Counting;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+// Calls to alloc, alloc_zeroed and realloc; a realloc counts as one.
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static UNCOUNTED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn counted() -> bool {
+    // During thread teardown the flag may already be gone.
+    UNCOUNTED.try_with(|u| !u.get()).unwrap_or(true)
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let p = unsafe { System.alloc(layout) };
+        if !p.is_null() {
+            if counted() {
+                LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            }
+            #[cfg(feature = "instrumented-alloc")]
+            alloc_tracker::on_alloc(p, layout);
+        }
+        p
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let p = unsafe { System.alloc_zeroed(layout) };
+        if !p.is_null() {
+            if counted() {
+                LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            }
+            #[cfg(feature = "instrumented-alloc")]
+            alloc_tracker::on_alloc(p, layout);
+        }
+        p
+    }
+
+    unsafe fn dealloc(&self, p: *mut u8, layout: Layout) {
+        #[cfg(feature = "instrumented-alloc")]
+        let layout = alloc_tracker::on_dealloc(p, layout);
+        unsafe { System.dealloc(p, layout) };
+        if counted() {
+            LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+    }
+
+    unsafe fn realloc(&self, p: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let q = unsafe { System.realloc(p, layout, new_size) };
+        if !q.is_null() {
+            // Tracked as a free of the old block and an allocation of the
+            // new one. (A mismatch here is only reported, not corrected:
+            // System has been handed the wrong layout by now.)
+            #[cfg(feature = "instrumented-alloc")]
+            {
+                alloc_tracker::on_dealloc(p, layout);
+                alloc_tracker::on_alloc(q, Layout::from_size_align(new_size, layout.align()).unwrap());
+            }
+            if counted() {
+                LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+                LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+                ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        q
+    }
+}
+
+#[global_allocator]
+pub static GLOBAL: Counting = Counting;
+
+pub fn live_bytes() -> usize {
+    LIVE_BYTES.load(Ordering::Relaxed)
+}
+
+pub fn allocations() -> usize {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+/// Runs `f` with nothing this thread allocates or frees counted.
+pub fn uncounted<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            UNCOUNTED.set(self.0);
+        }
+    }
+
+    let _restore = Restore(UNCOUNTED.replace(true));
+    f()
+}
@@ -0,0 +1,165 @@
+// With the `instrumented-alloc` feature, the counting allocator also
+// remembers the layout of every live allocation, tagged with whichever
+// demo was running when it was made. That gives per-demo alloc/free
+// counts (and so per-demo leaks), and it catches something none of the
+// type-level tricks here can: a dealloc whose Layout isn't the one the
+// block was allocated with. The system allocator happens not to care on
+// most platforms, which is exactly why such bugs go unnoticed.
+//
+// The tracker runs inside the global allocator, so it must not allocate
+// itself: live allocations go in a fixed-size, open-addressed table of
+// atomics. If that fills up, further allocations simply go untracked.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::alloc_counter::GLOBAL;
+
+const SLOTS: usize = 1 << 14;
+// Every demo run takes one, as does every part of a demo that is set
+// apart with `set_demo` of its own (see rc_leak.rs).
+const MAX_DEMOS: usize = 128;
+
+// Slot addresses: 0 has never been used, TOMBSTONE has been freed since.
+const EMPTY: usize = 0;
+const TOMBSTONE: usize = 1;
+
+struct Slot {
+    addr: AtomicUsize,
+    size: AtomicUsize,
+    align: AtomicUsize,
+    demo: AtomicUsize,
+}
+
+static TABLE: [Slot; SLOTS] = [const {
+    Slot {
+        addr: AtomicUsize::new(EMPTY),
+        size: AtomicUsize::new(0),
+        align: AtomicUsize::new(0),
+        demo: AtomicUsize::new(0),
+    }
+}; SLOTS];
+
+// Demo 0 is everything that runs before the first `set_demo`.
+static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static ALLOCS: [AtomicUsize; MAX_DEMOS] = [const { AtomicUsize::new(0) }; MAX_DEMOS];
+static FREES: [AtomicUsize; MAX_DEMOS] = [const { AtomicUsize::new(0) }; MAX_DEMOS];
+static UNTRACKED: AtomicUsize = AtomicUsize::new(0);
+
+static MISMATCHES: AtomicUsize = AtomicUsize::new(0);
+// The most recent mismatch: the size it was freed with, and the one it
+// was allocated with.
+static LAST_MISMATCH: [AtomicUsize; 2] = [const { AtomicUsize::new(0) }; 2];
+
+fn probe(addr: usize) -> impl Iterator<Item = &'static Slot> {
+    let start = (addr >> 4).wrapping_mul(0x9E37_79B9_7F4A_7C15) % SLOTS;
+    (0..SLOTS).map(move |i| &TABLE[(start + i) % SLOTS])
+}
+
+/// Attributes allocations made from now on (on any thread) to `name`.
+pub fn set_demo(name: &'static str) {
+    let mut names = NAMES.lock().unwrap();
+    if names.is_empty() {
+        names.push("main");
+    }
+    if names.len() < MAX_DEMOS {
+        names.push(name);
+        CURRENT.store(names.len() - 1, Ordering::Relaxed);
+    }
+}
+
+pub fn on_alloc(p: *mut u8, layout: Layout) {
+    let demo = CURRENT.load(Ordering::Relaxed);
+    ALLOCS[demo].fetch_add(1, Ordering::Relaxed);
+    for slot in probe(p as usize) {
+        let a = slot.addr.load(Ordering::Relaxed);
+        if (a == EMPTY || a == TOMBSTONE)
+            && slot.addr.compare_exchange(a, p as usize, Ordering::Acquire, Ordering::Relaxed).is_ok()
+        {
+            slot.size.store(layout.size(), Ordering::Relaxed);
+            slot.align.store(layout.align(), Ordering::Relaxed);
+            slot.demo.store(demo, Ordering::Relaxed);
+            return;
+        }
+    }
+    UNTRACKED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Forgets `p`, returning the layout it was really allocated with (which
+/// is what has to be passed on to the system allocator), or `layout`
+/// itself if `p` was never tracked.
+pub fn on_dealloc(p: *mut u8, layout: Layout) -> Layout {
+    for slot in probe(p as usize) {
+        match slot.addr.load(Ordering::Acquire) {
+            EMPTY => break,
+            a if a == p as usize => {
+                let size = slot.size.load(Ordering::Relaxed);
+                let align = slot.align.load(Ordering::Relaxed);
+                FREES[slot.demo.load(Ordering::Relaxed)].fetch_add(1, Ordering::Relaxed);
+                slot.addr.store(TOMBSTONE, Ordering::Release);
+                if (size, align) != (layout.size(), layout.align()) {
+                    MISMATCHES.fetch_add(1, Ordering::Relaxed);
+                    LAST_MISMATCH[0].store(layout.size(), Ordering::Relaxed);
+                    LAST_MISMATCH[1].store(size, Ordering::Relaxed);
+                }
+                return Layout::from_size_align(size, align).unwrap();
+            }
+            _ => {}
+        }
+    }
+    layout
+}
+
+// The blocks demo `i` allocated that are still live, and their bytes.
+fn live(i: usize) -> (usize, usize) {
+    TABLE
+        .iter()
+        .filter(|s| !matches!(s.addr.load(Ordering::Acquire), EMPTY | TOMBSTONE) && s.demo.load(Ordering::Relaxed) == i)
+        .fold((0, 0), |(blocks, bytes), s| (blocks + 1, bytes + s.size.load(Ordering::Relaxed)))
+}
+
+/// The blocks allocated while `name` was the demo (the latest one by that
+/// name) that haven't been freed since, and how many bytes they come to.
+pub fn leaked(name: &str) -> (usize, usize) {
+    let i = NAMES.lock().unwrap().iter().rposition(|n| *n == name);
+    i.map_or((0, 0), live)
+}
+
+/// Prints allocation and free counts for every demo that allocated, with
+/// what is still live of it, and any mismatched deallocations.
+pub fn report() {
+    let names = NAMES.lock().unwrap().clone();
+    for (i, name) in names.iter().enumerate() {
+        let (allocs, frees) = (ALLOCS[i].load(Ordering::Relaxed), FREES[i].load(Ordering::Relaxed));
+        if allocs > 0 {
+            println!(
+                "alloc stats: {:<18} {:>8} allocs {:>8} frees {:>6} live {:>8} bytes",
+                name,
+                allocs,
+                frees,
+                allocs - frees,
+                live(i).1
+            );
+        }
+    }
+    println!("alloc stats: {} untracked, {} mismatched deallocs",
+             UNTRACKED.load(Ordering::Relaxed),
+             MISMATCHES.load(Ordering::Relaxed));
+}
+
+crate::registry::demo!("Per-demo allocation stats and mismatched layouts", tags: ["alloc"]);
+
+pub fn demo() {
+    // Free a block with the wrong size. Through `std::alloc::dealloc`
+    // that would be UB; calling our own allocator directly, it's defined
+    // to be caught and corrected.
+    let mismatches = MISMATCHES.load(Ordering::Relaxed);
+    unsafe {
+        let p = GLOBAL.alloc(Layout::from_size_align(32, 8).unwrap());
+        GLOBAL.dealloc(p, Layout::from_size_align(16, 8).unwrap());
+    }
+    assert_eq!(MISMATCHES.load(Ordering::Relaxed), mismatches + 1);
+    assert_eq!([LAST_MISMATCH[0].load(Ordering::Relaxed), LAST_MISMATCH[1].load(Ordering::Relaxed)], [16, 32]);
+}
@@ -0,0 +1,77 @@
+// A typed arena: `alloc` moves a value into the arena and hands back a
+// reference that lives as long as the arena does, and everything is
+// destroyed together when the arena goes.
+//
+// Values live in chunks that are never reallocated (a full chunk is
+// followed by a new, bigger one), so a reference handed out stays valid
+// however many more values come after it. The chunks are MyVecs, whose
+// PhantomData (via MyUnique) tells dropck that dropping the arena drops
+// `T`s; Arena needs no Drop impl of its own.
+
+use std::cell::RefCell;
+
+use crate::my_vec::MyVec;
+use crate::{recorder, PrintOnDrop};
+
+pub struct Arena<T> {
+    chunks: RefCell<Vec<MyVec<T>>>,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Arena { chunks: RefCell::new(Vec::new()) }
+    }
+
+    pub fn alloc(&self, t: T) -> &T {
+        let mut chunks = self.chunks.borrow_mut();
+        let full = chunks.last().is_none_or(|c| c.len() == c.capacity());
+        if full {
+            let mut chunk = MyVec::new();
+            chunk.reserve(chunks.last().map_or(16, |c| c.capacity() * 2));
+            chunks.push(chunk);
+        }
+        let chunk = chunks.last_mut().unwrap();
+        chunk.push(t);
+        // Safety: the chunk never grows past the capacity reserved above,
+        // so the value stays where it is until the arena (and with it the
+        // borrow of `self`) goes away.
+        unsafe { &*chunk.as_ptr().add(chunk.len() - 1) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.borrow().iter().map(MyVec::len).sum()
+    }
+}
+
+crate::registry::demo! {
+    "Arena: references that live as long as the arena",
+    tags: ["lifetimes", "collections"],
+}
+
+pub fn demo() {
+    let v = PrintOnDrop::new("arena_v", 13);
+    recorder::take();
+    {
+        let arena = Arena::new();
+        let a = arena.alloc(PrintOnDrop::new("arena_a", &v));
+        // Plenty more, across two chunks; `a` is still where it was.
+        for _ in 0..20 {
+            arena.alloc(PrintOnDrop::new("arena_n", &v));
+        }
+        let b = arena.alloc(PrintOnDrop::new("arena_b", &v));
+        println!("Arena: {} values, first {:?}, last {:?}", arena.len(), a.name(), b.name());
+        // Nothing dropped until the arena is.
+        assert!(recorder::take_drops().is_empty());
+    }
+    let drops = recorder::take_drops();
+    assert_eq!((drops.len(), &*drops[0], &*drops[21]), (22, "arena_a", "arena_b"));
+
+    // References into the arena can't outlive it:
+    //
+    // let r;
+    // {
+    //     let arena = Arena::new();
+    //     r = arena.alloc(1); // won't compile: `arena` does not live long enough
+    // }
+    // println!("{}", r);
+}
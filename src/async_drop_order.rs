@@ -0,0 +1,82 @@
+// When do values inside an async fn get dropped?
+//
+// An async fn's locals live in its future, for as long as the future
+// holds them. If the future runs to completion, they drop at the end of
+// their scope, as in a normal function. If the future is dropped while
+// suspended at an `.await`, everything it holds at that point is dropped
+// right then, in the middle of the function body; code after the
+// `.await`, including any explicit cleanup, never runs. And even a future
+// that is never polled owns its arguments.
+
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::task::{Context, Poll, Waker};
+
+use crate::borrowing_future::block_on;
+use crate::{recorder, PrintOnDrop};
+
+/// Returns Pending once, then Ready: one suspension point.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn yield_now() -> YieldNow {
+    YieldNow(false)
+}
+
+async fn two_steps() {
+    let _a = PrintOnDrop::new("async_a", 1);
+    yield_now().await;
+    let _b = PrintOnDrop::new("async_b", 2);
+    // Dropped before the next suspension, so never held across it.
+    drop(PrintOnDrop::new("async_tmp", 3));
+    yield_now().await;
+    println!("two_steps: finished");
+}
+
+async fn takes_arg(_arg: PrintOnDrop<i32>) {
+    yield_now().await;
+}
+
+fn f_completed() {
+    block_on(two_steps());
+    assert_eq!(recorder::take_drops(), ["async_tmp", "async_b", "async_a"]);
+}
+
+fn f_dropped_mid_poll() {
+    let mut cx = Context::from_waker(Waker::noop());
+    {
+        let mut fut = pin!(two_steps());
+        // Runs up to the first `.await`, with `_a` alive.
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+        println!("two_steps: suspended, dropping the future");
+    }
+    // Only `_a` existed; "finished" was never printed.
+    assert_eq!(recorder::take_drops(), ["async_a"]);
+}
+
+fn f_never_polled() {
+    let fut = takes_arg(PrintOnDrop::new("async_arg", 4));
+    // Nothing has run, but the future already owns `_arg`.
+    drop(fut);
+    assert_eq!(recorder::take_drops(), ["async_arg"]);
+}
+
+crate::registry::demo!("When values inside an async fn are dropped", tags: ["async", "drop-order"]);
+
+pub fn demo() {
+    recorder::take();
+    f_completed();
+    f_dropped_mid_poll();
+    f_never_polled();
+}
@@ -0,0 +1,222 @@
+// Send and Sync are inferred from a type's fields, so (as with variance)
+// nothing in a definition says which a type has, and changing a field
+// can silently change them. These checks say, for every wrapper type in
+// the crate, which it has and which it must not, and as `const` items
+// break the build when that changes.
+//
+// `assert_impl!(T: Trait)` is a call to a function bounded by the trait.
+// `assert_not_impl!(T: Trait)` is the usual trick for the opposite, which
+// Rust has no bound for: a blanket impl of a helper trait for every type,
+// plus a second one for types with `Trait`, so that naming the helper's
+// method is ambiguous (E0283) exactly when `T: Trait`.
+//
+// Most of the collections come out as neither: they keep NonNulls (or
+// raw pointers) that are neither Send nor Sync, and have no unsafe impls
+// forwarding `T`'s, as MyUnique gives MyBox3 and MyVec. That is the safe
+// default, and what is checked here; it's the types with impls (and the
+// markers) whose claims matter most.
+
+use std::cell::Cell;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::rc::Rc;
+
+use crate::arena::Arena;
+use crate::borrowing_future::SumFuture;
+use crate::branded::{BrandedIndex, BrandedSlice};
+use crate::drop_queue::DropQueue;
+use crate::encoder::{Encoder, Json};
+use crate::endian::{BigEndian, U32};
+use crate::family::{Partition, VecFamily};
+use crate::fd_state::{Fd, Open};
+use crate::ffi_handle::{Context, Handle};
+use crate::ffi_owned::Widget;
+use crate::foreign_cstring::ForeignCString;
+use crate::intrusive_list::IntrusiveList;
+use crate::main_thread::{MainThreadToken, Window};
+use crate::my_arc::{MyArc, MyWeak};
+use crate::my_cell_box::MyCellBox;
+use crate::my_hash_map::MyHashMap;
+use crate::my_iter::{MyIter, MyIterMut};
+use crate::my_linked_list::{CursorMut, MyLinkedList};
+use crate::my_small_vec::MySmallVec;
+use crate::my_unique::MyUnique;
+use crate::my_vec::{Drain, MyVec};
+use crate::once_box::{OnceBox, SyncOnceBox};
+use crate::phantom_owner::OwnedSlice;
+use crate::scope_guard::ScopeGuard;
+use crate::scoped_threads::Scope;
+use crate::send_sync::{SendNotSync, SyncNotSend};
+use crate::slab::Slab;
+use crate::small_box::SmallBox;
+use crate::tagged_box::TaggedBox;
+use crate::transaction::{Connection, Transaction};
+use crate::unwind_safe::{LooseView, PairWriter};
+use crate::visitor::Eval;
+use crate::{MyBox1, MyBox2, MyBox3};
+
+
+macro_rules! assert_impl {
+    ($($t:ty),+ : $tr:path) => {
+        $(const _: fn() = || {
+            fn check<T: ?Sized + $tr>() {}
+            check::<$t>();
+        };)+
+    };
+}
+pub(crate) use assert_impl;
+
+macro_rules! assert_not_impl {
+    ($($t:ty),+ : $tr:path) => {
+        $(const _: fn() = || {
+            trait AmbiguousIfImpl<A> {
+                fn some_item() {}
+            }
+            impl<T: ?Sized> AmbiguousIfImpl<()> for T {}
+            #[allow(dead_code)]
+            struct Invalid;
+            impl<T: ?Sized + $tr> AmbiguousIfImpl<Invalid> for T {}
+            let _ = <$t as AmbiguousIfImpl<_>>::some_item;
+        };)+
+    };
+}
+pub(crate) use assert_not_impl;
+
+macro_rules! assert_impl_send {
+    ($($t:ty),+ $(,)?) => { $crate::auto_trait_tests::assert_impl!($($t),+ : Send); };
+}
+pub(crate) use assert_impl_send;
+
+macro_rules! assert_impl_sync {
+    ($($t:ty),+ $(,)?) => { $crate::auto_trait_tests::assert_impl!($($t),+ : Sync); };
+}
+pub(crate) use assert_impl_sync;
+
+macro_rules! assert_not_impl_send {
+    ($($t:ty),+ $(,)?) => { $crate::auto_trait_tests::assert_not_impl!($($t),+ : Send); };
+}
+pub(crate) use assert_not_impl_send;
+
+macro_rules! assert_not_impl_sync {
+    ($($t:ty),+ $(,)?) => { $crate::auto_trait_tests::assert_not_impl!($($t),+ : Sync); };
+}
+pub(crate) use assert_not_impl_sync;
+
+// The boxes, with MyBox2's bare NonNull the odd one out.
+assert_impl_send!(MyBox1<i32>, MyBox3<i32>, MyUnique<i32>, MyArc<i32>);
+assert_impl_sync!(MyBox1<i32>, MyBox3<i32>, MyUnique<i32>, MyArc<i32>);
+assert_not_impl_send!(MyBox2<i32>);
+assert_not_impl_sync!(MyBox2<i32>);
+// What they forward from `T`. MyArc needs more: moving a clone to
+// another thread shares the `T`.
+assert_impl_send!(MyBox3<Cell<i32>>, MyUnique<Cell<i32>>);
+assert_not_impl_sync!(MyBox3<Cell<i32>>, MyUnique<Cell<i32>>);
+assert_not_impl_send!(MyBox3<Rc<i32>>, MyUnique<Rc<i32>>, MyArc<Cell<i32>>);
+assert_not_impl_sync!(MyBox3<Rc<i32>>, MyUnique<Rc<i32>>, MyArc<Cell<i32>>);
+// A MyWeak can become a MyArc, so it is what a MyArc is.
+assert_impl_send!(MyWeak<i32>);
+assert_impl_sync!(MyWeak<i32>);
+assert_not_impl_send!(MyWeak<Cell<i32>>);
+assert_not_impl_sync!(MyWeak<Cell<i32>>);
+
+// The cells: OnceBox is Send but not Sync, like Cell; SyncOnceBox is
+// both, like OnceLock, but only Sync for a Send + Sync `T`.
+assert_impl_send!(OnceBox<i32>, SyncOnceBox<i32>);
+assert_impl_sync!(SyncOnceBox<i32>);
+assert_not_impl_sync!(OnceBox<i32>, SyncOnceBox<Cell<i32>>);
+assert_not_impl_send!(OnceBox<Rc<i32>>, MyCellBox<i32>);
+assert_not_impl_sync!(MyCellBox<i32>);
+
+// Collections and views.
+assert_impl_send!(MyVec<i32>, DropQueue<i32>, Arena<i32>);
+assert_impl_sync!(MyVec<i32>, DropQueue<i32>);
+assert_not_impl_sync!(Arena<i32>);
+assert_not_impl_send!(
+    Drain<'static, i32>,
+    MyIter<'static, i32>,
+    MyIterMut<'static, i32>,
+    MySmallVec<i32, 2>,
+    SmallBox<i32, 2>,
+    Slab<i32>,
+    MyHashMap<i32, i32>,
+    MyLinkedList<i32>,
+    CursorMut<'static, i32>,
+    IntrusiveList<'static, i32>,
+    TaggedBox<i32>,
+    OwnedSlice<i32>,
+    SumFuture<'static, i32>,
+    Transaction<'static>,
+    LooseView<'static>,
+    PairWriter<'static>,
+);
+assert_not_impl_sync!(
+    Drain<'static, i32>,
+    MyIter<'static, i32>,
+    MyIterMut<'static, i32>,
+    MySmallVec<i32, 2>,
+    SmallBox<i32, 2>,
+    Slab<i32>,
+    MyHashMap<i32, i32>,
+    MyLinkedList<i32>,
+    CursorMut<'static, i32>,
+    IntrusiveList<'static, i32>,
+    TaggedBox<i32>,
+    OwnedSlice<i32>,
+    SumFuture<'static, i32>,
+    Transaction<'static>,
+    LooseView<'static>,
+    PairWriter<'static>,
+);
+
+// FFI: a Widget may move, but not be shared; a ForeignCString may do
+// both; the context-bound handles may do neither.
+assert_impl_send!(Widget, ForeignCString);
+assert_impl_sync!(ForeignCString);
+assert_not_impl_sync!(Widget);
+assert_not_impl_send!(Context, Handle<'static>);
+assert_not_impl_sync!(Context, Handle<'static>);
+
+// Markers: the two odd combinations, and a token that may go nowhere.
+assert_impl_send!(SendNotSync<i32>);
+assert_not_impl_sync!(SendNotSync<i32>);
+assert_impl_sync!(SyncNotSend<i32>);
+assert_not_impl_send!(SyncNotSend<i32>);
+assert_not_impl_send!(MainThreadToken);
+assert_not_impl_sync!(MainThreadToken);
+
+// Pure type-level tags add nothing, so these are as Send and Sync as
+// their data.
+assert_impl_send!(
+    Window,
+    Connection,
+    BrandedIndex<'static>,
+    BrandedSlice<'static, 'static, i32>,
+    Fd<Open>,
+    U32<BigEndian>,
+    Encoder<Json>,
+    Partition<VecFamily>,
+    Eval<i64>,
+    Scope<'static, 'static>,
+    ScopeGuard<fn()>,
+);
+assert_impl_sync!(
+    Window,
+    Connection,
+    BrandedIndex<'static>,
+    BrandedSlice<'static, 'static, i32>,
+    Fd<Open>,
+    U32<BigEndian>,
+    Encoder<Json>,
+    Partition<VecFamily>,
+    Eval<i64>,
+    Scope<'static, 'static>,
+    ScopeGuard<fn()>,
+);
+
+// And the unwinding pair (see unwind_safe.rs).
+assert_impl!(MyBox3<i32>, LooseView<'static>, OnceBox<i32> : UnwindSafe);
+assert_impl!(MyBox3<i32> : RefUnwindSafe);
+assert_not_impl!(PairWriter<'static>, MyCellBox<i32> : UnwindSafe);
+// Through MyUnique's PhantomData<T> (see my_unique.rs).
+#[cfg(not(repr = "nonnull"))]
+assert_not_impl!(MyBox3<&mut i32> : UnwindSafe);
+assert_not_impl!(OnceBox<i32>, MyCellBox<i32> : RefUnwindSafe);
@@ -0,0 +1,85 @@
+// A hand-written Future that works through borrowed data a little at a
+// time, yielding between steps, as an `async fn` taking a `&'a [T]` would.
+//
+// It reaches the data through a raw pointer (as futures built on C
+// callbacks or io_uring buffers do), so, once again, it's the
+// PhantomData<&'a [T]> that stops the future from outliving the slice:
+// a future is just a value, and can be stored, moved, and polled long
+// after the function that created it has returned.
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::{pin, Pin};
+use std::ptr::NonNull;
+use std::task::{Context, Poll, Waker};
+
+pub struct SumFuture<'a, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    pos: usize,
+    acc: i64,
+    _pd: PhantomData<&'a [T]>,
+}
+
+pub fn sum_slowly<T: Copy + Into<i64>>(data: &[T]) -> SumFuture<'_, T> {
+    SumFuture { ptr: NonNull::from(data).cast(), len: data.len(), pos: 0, acc: 0, _pd: PhantomData }
+}
+
+impl<T: Copy + Into<i64>> Future for SumFuture<'_, T> {
+    type Output = i64;
+
+    // One element per poll.
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<i64> {
+        // SumFuture holds no self-references, so it is Unpin.
+        let this = self.get_mut();
+        if this.pos == this.len {
+            return Poll::Ready(this.acc);
+        }
+        // Safety: `'a` keeps the slice alive and unmodified.
+        this.acc += unsafe { this.ptr.add(this.pos).read() }.into();
+        this.pos += 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Polls `f` to completion on the current thread, returning its output
+/// and how many times it had to be polled. Busy-loops rather than
+/// sleeping, since all our futures wake themselves immediately.
+pub fn block_on<F: Future>(f: F) -> (F::Output, usize) {
+    let mut f = pin!(f);
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut polls = 0;
+    loop {
+        polls += 1;
+        if let Poll::Ready(out) = f.as_mut().poll(&mut cx) {
+            return (out, polls);
+        }
+    }
+}
+
+crate::registry::demo!("A hand-written Future over borrowed data", tags: ["async", "lifetimes"]);
+
+pub fn demo() {
+    let data = vec![1u8, 2, 3, 4];
+    let fut = sum_slowly(&data);
+    let (sum, polls) = block_on(fut);
+    println!("SumFuture: sum {} after {} polls", sum, polls);
+    assert_eq!((sum, polls), (10, 5));
+
+    // A future that outlives the data it borrows:
+    //
+    // let fut;
+    // {
+    //     let data = vec![1u8, 2, 3];
+    //     fut = sum_slowly(&data); // won't compile: `data` does not live long enough
+    // }
+    // block_on(fut);
+    //
+    // or the data being changed while a future is still reading it:
+    //
+    // let mut data = vec![1u8, 2, 3];
+    // let fut = sum_slowly(&data);
+    // data.push(4); // won't compile: cannot borrow `data` as mutable
+    // block_on(fut);
+}
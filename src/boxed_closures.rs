@@ -0,0 +1,93 @@
+// Box<dyn FnOnce()> closures, kept in the crate's own containers. A boxed
+// closure's captures are dropped through its vtable, by the drop glue of
+// whatever concrete closure type it was made from, and only once: when
+// it is called (the call consumes it), or, if it never is, when the
+// container holding it drops it.
+
+use std::alloc::{dealloc, Layout};
+use std::ptr;
+
+use lesson::lesson;
+
+use crate::my_vec::MyVec;
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder, MyBox3};
+
+impl<T> MyBox3<T> {
+    /// Moves the `T` out, and frees the box.
+    pub fn into_inner(b: Self) -> T {
+        let p = MyBox3::into_raw(b);
+        unsafe {
+            let t = ptr::read(p);
+            if size_of::<T>() != 0 {
+                dealloc(p.cast(), Layout::new::<T>());
+            }
+            t
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() -> u64>;
+
+fn job(name: &'static str) -> Job {
+    let v = print_on_drop!(name, 0);
+    Box::new(move || v.id())
+}
+
+fn in_a_vec() {
+    let mut jobs = MyVec::new();
+    for name in ["bc_first", "bc_second", "bc_third"] {
+        jobs.push(job(name));
+    }
+    for j in jobs.drain(..2) {
+        j();
+        mark("bc_called");
+    }
+    // The third is never called, and goes with the vector.
+    drop(jobs);
+    mark("bc_vec_dropped");
+}
+
+fn in_a_box() {
+    let b = MyBox3::new(job("bc_uncalled"));
+    drop(b);
+    mark("bc_box_dropped");
+
+    let b = MyBox3::new(job("bc_called_from_box"));
+    MyBox3::into_inner(b)();
+    mark("bc_box_called");
+
+    // A closure has no Debug, but can be traced opaquely, and called in
+    // place if it's an Fn:
+    //
+    // let b = MyBox3::new(print_on_drop!("bc_closure", || 7)); // won't compile: E0277, `{closure}` doesn't implement `Debug`
+    let b = MyBox3::new(print_on_drop!(opaque "bc_closure", || 7));
+    assert_eq!((b.1)(), 7);
+    assert!(format!("{:?}", *b).contains("<no debug>"));
+}
+
+/// A closure boxed as `dyn FnOnce()` forgets its type, but not how to
+/// drop its captures: the vtable has the concrete closure's drop glue in
+/// it, next to its size, alignment and `call_once`. Calling it moves it
+/// out of its box and consumes it, dropping the captures with the call,
+/// and freeing the box. Not calling it leaves it to whatever holds it:
+/// MyVec drops its elements in order, through the vtable, then frees its
+/// buffer.
+///
+/// To call one that's in a container, it has to be moved out first, by
+/// draining a MyVec or with MyBox3::into_inner, since calling an FnOnce
+/// takes it by value.
+#[lesson(title = "Box<dyn FnOnce()> in MyVec and MyBox3: dropped called or not", tags = ["drop-order", "unsize"])]
+pub fn demo() {
+    recorder::take();
+    in_a_vec();
+    assert_eq!(
+        recorder::take_drops(),
+        ["bc_first", "bc_called", "bc_second", "bc_called", "bc_third", "bc_vec_dropped"]
+    );
+    in_a_box();
+    assert_eq!(
+        recorder::take_drops(),
+        ["bc_uncalled", "bc_box_dropped", "bc_called_from_box", "bc_box_called", "bc_closure"]
+    );
+}
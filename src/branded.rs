@@ -0,0 +1,79 @@
+// Branded indices ("generativity"): an index that has been bounds-checked
+// once against a slice carries a unique type-level brand tying it to that
+// slice, so every later access through it can skip the check.
+//
+// The brand is a lifetime, `'brand`, that `with_brand` makes up fresh for
+// each call: the closure must work for *any* `'brand`, so inside it the
+// brand can't be equated with any other. For that to hold, the brand must
+// be invariant. If it were covariant (PhantomData<&'brand ()>), rustc
+// could shrink two different brands to a common lifetime, and an index
+// checked against a long slice would be accepted by a short one. The
+// phantom `fn(&'brand ()) -> &'brand ()` is covariant in its return and
+// contravariant in its argument, which adds up to invariant.
+
+use std::marker::PhantomData;
+
+type Brand<'brand> = PhantomData<fn(&'brand ()) -> &'brand ()>;
+
+pub struct BrandedSlice<'brand, 'a, T> {
+    data: &'a [T],
+    _brand: Brand<'brand>,
+}
+
+#[derive(Clone, Copy)]
+pub struct BrandedIndex<'brand> {
+    idx: usize,
+    _brand: Brand<'brand>,
+}
+
+pub fn with_brand<'a, T, R>(data: &'a [T], f: impl for<'brand> FnOnce(BrandedSlice<'brand, 'a, T>) -> R) -> R {
+    f(BrandedSlice { data, _brand: PhantomData })
+}
+
+impl<'brand, T> BrandedSlice<'brand, '_, T> {
+    /// The one bounds check.
+    pub fn index(&self, idx: usize) -> Option<BrandedIndex<'brand>> {
+        (idx < self.data.len()).then_some(BrandedIndex { idx, _brand: PhantomData })
+    }
+
+    pub fn indices(&self) -> impl Iterator<Item = BrandedIndex<'brand>> + use<'brand, T> {
+        (0..self.data.len()).map(|idx| BrandedIndex { idx, _brand: PhantomData })
+    }
+
+    pub fn get(&self, i: BrandedIndex<'brand>) -> &T {
+        // Safety: `i` was checked against a slice with our brand, and the
+        // only such slice is this one, whose length can't change.
+        unsafe { self.data.get_unchecked(i.idx) }
+    }
+}
+
+crate::registry::demo! {
+    "Branded indices: bounds-checked once, tied to their slice",
+    tags: ["variance", "lifetimes"],
+}
+
+pub fn demo() {
+    let xs = [10, 20, 30, 40];
+    let total = with_brand(&xs, |s| {
+        let last = s.index(3).unwrap();
+        assert!(s.index(4).is_none());
+        s.indices().map(|i| *s.get(i)).sum::<i32>() + s.get(last)
+    });
+    println!("BrandedSlice: sum + last = {}", total);
+    assert_eq!(total, 140);
+
+    // An index from one slice can't be used with another, even of the
+    // same type:
+    //
+    // let long = [1, 2, 3, 4, 5];
+    // let short = [1];
+    // with_brand(&long, |l| {
+    //     let i = l.index(4).unwrap();
+    //     with_brand(&short, |s| {
+    //         s.get(i); // won't compile: E0521, borrowed data escapes outside of closure
+    //     });
+    // });
+    //
+    // With `type Brand<'brand> = PhantomData<&'brand ()>`, that compiles,
+    // and reads past the end of `short`.
+}
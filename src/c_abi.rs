@@ -0,0 +1,111 @@
+// MyBox3 from C (see c/mybox3.h): `extern "C"` functions that hand out a
+// box as an opaque pointer, and take it back to drop it.
+//
+// The conventions are the ones into_raw.rs is for. `mybox3_new_*` is
+// `into_raw`: from then on, no MyBox3 exists, so nothing on the Rust side
+// owns the value, and the C caller does, by convention only. `mybox3_drop_*`
+// is `from_raw` and a drop. Between the two, every promise the types made
+// is the caller's to keep: that the box is dropped once (Drop, and
+// MyBox3's ownership of its `T`), and, for `mybox3_new_ref`, that it is
+// dropped before what it borrows (the lifetime, and what dropck checks
+// thanks to the PhantomData). C has no way to say any of it.
+//
+// c/mybox3_test.c uses them as a C program would; the demo calls it.
+
+use std::ffi::{c_char, c_int, CStr};
+
+use crate::{recorder, MyBox3, PrintOnDrop};
+
+#[no_mangle]
+pub extern "C" fn mybox3_new_i32(v: i32) -> *mut i32 {
+    MyBox3::into_raw(MyBox3::new(v))
+}
+
+/// # Safety
+///
+/// `b` must have come from `mybox3_new_i32`, and not have been dropped.
+#[no_mangle]
+pub unsafe extern "C" fn mybox3_get_i32(b: *const i32) -> i32 {
+    unsafe { *b }
+}
+
+/// # Safety
+///
+/// As for `mybox3_get_i32`.
+#[no_mangle]
+pub unsafe extern "C" fn mybox3_set_i32(b: *mut i32, v: i32) {
+    unsafe { *b = v }
+}
+
+/// # Safety
+///
+/// `b` must be null, or have come from `mybox3_new_i32` and not have been
+/// dropped.
+#[no_mangle]
+pub unsafe extern "C" fn mybox3_drop_i32(b: *mut i32) {
+    if !b.is_null() {
+        drop(unsafe { MyBox3::from_raw(b) });
+    }
+}
+
+/// # Safety
+///
+/// `v` must be valid for reads, and stay so until the box is dropped.
+#[no_mangle]
+pub unsafe extern "C" fn mybox3_new_ref(v: *const i32) -> *mut &'static i32 {
+    // The lifetime is made up: from here on it's the caller's to keep.
+    MyBox3::into_raw(MyBox3::new(unsafe { &*v }))
+}
+
+/// # Safety
+///
+/// `b` must have come from `mybox3_new_ref`, and not have been dropped.
+#[no_mangle]
+pub unsafe extern "C" fn mybox3_get_ref(b: *const &'static i32) -> i32 {
+    unsafe { **b }
+}
+
+/// # Safety
+///
+/// `b` must be null, or have come from `mybox3_new_ref` and not have been
+/// dropped.
+#[no_mangle]
+pub unsafe extern "C" fn mybox3_drop_ref(b: *mut &'static i32) {
+    if !b.is_null() {
+        drop(unsafe { MyBox3::from_raw(b) });
+    }
+}
+
+/// # Safety
+///
+/// `name` must be a NUL-terminated string, valid for reads during the call.
+#[no_mangle]
+pub unsafe extern "C" fn mybox3_new_print_on_drop(name: *const c_char, v: i32) -> *mut PrintOnDrop<i32> {
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned();
+    MyBox3::into_raw(MyBox3::new(PrintOnDrop::new(name, v)))
+}
+
+/// # Safety
+///
+/// `b` must be null, or have come from `mybox3_new_print_on_drop` and not
+/// have been dropped.
+#[no_mangle]
+pub unsafe extern "C" fn mybox3_drop_print_on_drop(b: *mut PrintOnDrop<i32>) {
+    if !b.is_null() {
+        drop(unsafe { MyBox3::from_raw(b) });
+    }
+}
+
+extern "C" {
+    fn mybox3_c_test() -> c_int;
+}
+
+crate::registry::demo!("MyBox3 exported to C, and used from C", tags: ["ffi"]);
+
+pub fn demo() {
+    recorder::take();
+    let failed_at = unsafe { mybox3_c_test() };
+    assert_eq!(failed_at, 0, "check failed at c/mybox3_test.c:{}", failed_at);
+    // Dropped from C, in the order C chose, by Rust's destructors.
+    assert_eq!(recorder::take_drops(), ["c_abi_second", "c_abi_first"]);
+}
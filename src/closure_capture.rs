@@ -0,0 +1,93 @@
+// What a closure captures, it holds like a struct holds its fields: a
+// `move` closure owns its captures, and drops them when it is dropped
+// (not when it is called, however often); a closure that borrows leaves
+// the value where it was, to be dropped there. Calling an FnOnce consumes
+// the closure, so its captures go with the call, at the latest.
+
+use lesson::lesson;
+
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+fn moved_in() {
+    let v = print_on_drop!("cc_moved", 1);
+    let c = move || v.id();
+    c();
+    c();
+    mark("cc_called");
+    drop(c);
+    mark("cc_closure_dropped");
+}
+
+fn borrowed() {
+    let v = print_on_drop!("cc_borrowed", 1);
+    {
+        // Only a `&PrintOnDrop`, so the closure is even Copy: dropping it
+        // does nothing at all.
+        let c = || v.id();
+        c();
+    }
+    mark("cc_closure_dropped");
+}
+
+fn call_once<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+fn consumed() {
+    // Moves its capture out, and drops it, when called.
+    let v = print_on_drop!("cc_consumed", 1);
+    let c = move || drop(v);
+    mark("cc_before_call");
+    c();
+    mark("cc_after_call");
+
+    // Doesn't, but is itself consumed, by `call_once` taking it by value:
+    // its capture goes at the end of that call.
+    let w = print_on_drop!("cc_passed", 2);
+    let id = call_once(move || w.id());
+    mark("cc_after_call_once");
+    assert!(id > 0);
+}
+
+struct Pair {
+    a: PrintOnDrop<i32>,
+    b: PrintOnDrop<i32>,
+}
+
+fn disjoint() {
+    // The closure uses, and so moves, only `pair.a`: the rest of `pair`
+    // stays where it is, until the end of the function.
+    let pair = Pair { a: print_on_drop!("cc_pair_a", 1), b: print_on_drop!("cc_pair_b", 2) };
+    let c = move || pair.a.id();
+    c();
+    drop(c);
+    mark("cc_closure_dropped");
+    assert_eq!(pair.b.name(), "cc_pair_b");
+}
+
+/// A `move` closure owns what it captures, and drops it when the closure
+/// is dropped: calling it, even many times, changes nothing. A closure
+/// that only borrows a value leaves it to be dropped where it was
+/// declared, after the closure (which the borrow checker insists on).
+///
+/// An FnOnce that moves a capture out and drops it drops it during the
+/// call; one that doesn't still loses its captures when the call is done,
+/// since calling an FnOnce consumes it. And edition 2021 closures capture
+/// only the fields they use: moving `pair.a` into a closure leaves
+/// `pair.b` to be dropped with what's left of `pair`.
+#[lesson(title = "Closure captures: dropped with the closure, or where they were", tags = ["drop-order"])]
+pub fn demo() {
+    recorder::take();
+    moved_in();
+    assert_eq!(recorder::take_drops(), ["cc_called", "cc_moved", "cc_closure_dropped"]);
+    borrowed();
+    assert_eq!(recorder::take_drops(), ["cc_closure_dropped", "cc_borrowed"]);
+    consumed();
+    assert_eq!(
+        recorder::take_drops(),
+        ["cc_before_call", "cc_consumed", "cc_after_call", "cc_passed", "cc_after_call_once"]
+    );
+    disjoint();
+    assert_eq!(recorder::take_drops(), ["cc_pair_a", "cc_closure_dropped", "cc_pair_b"]);
+}
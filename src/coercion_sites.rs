@@ -0,0 +1,124 @@
+// Where subtyping does its work: a `&'static str` may be used wherever a
+// `&'a str` is expected, because `'static: 'a` and references are
+// covariant. The places it's used are ordinary: a `let` with a type, an
+// assignment, a call's argument, a return value, a field. variance_tests.rs
+// says which types are covariant; this says where that matters, and
+// what the same code does when the type in question is invariant
+// instead, behind a `&mut` or in a Cell (or a MyCellBox): each pair below
+// differs only in that.
+
+use std::cell::Cell;
+
+use lesson::lesson;
+
+use crate::my_cell_box::MyCellBox;
+use crate::{print_on_drop, MyBox3};
+
+fn longest<'a>(a: &'a str, b: &'a str) -> &'a str {
+    if a.len() >= b.len() {
+        a
+    } else {
+        b
+    }
+}
+
+fn overwrite<'a>(slot: &mut &'a str, with: &'a str) {
+    *slot = with;
+}
+
+struct Named<'a> {
+    name: &'a str,
+}
+
+fn assigned(local: &str) {
+    // In a `let` with a type, and in an assignment.
+    let mut short: &str = local;
+    assert_eq!(short, "cs_local");
+    short = "static";
+    assert_eq!(short, "static");
+
+    // Behind a `&mut`, the other way round doesn't work for the same
+    // reason it mustn't: it would store the shorter reference where the
+    // longer one is expected.
+    //
+    // let mut long: &'static str = "static";
+    // let r: &mut &str = &mut long;
+    // *r = local; // won't compile: lifetime may not live long enough
+}
+
+fn arguments(local: &str) {
+    // Both arguments end up as `&'a str` for the `'a` of `local`.
+    assert_eq!(longest("a static string", local), "a static string");
+
+    // A `&mut &'a str` is invariant in `'a`: passing `&mut long` fixes
+    // `'a` as `'static`, and `with` can't live that long.
+    let mut slot = "static";
+    overwrite(&mut slot, "also static");
+    assert_eq!(slot, "also static");
+    //
+    // let mut long: &'static str = "static";
+    // overwrite(&mut long, local); // won't compile: lifetime may not live long enough
+}
+
+fn returned<'a>() -> &'a str {
+    // A `&'static str` returned as a `&'a str`.
+    "static"
+}
+
+// A MyBox3 is covariant, so it shortens on the way out; a MyCellBox, like
+// a Cell, is invariant, so it doesn't.
+fn returned_box<'a>(b: MyBox3<&'static str>) -> MyBox3<&'a str> {
+    b
+}
+//
+// fn returned_cell_box<'a>(b: MyCellBox<&'static str>) -> MyCellBox<&'a str> {
+//     b // won't compile: lifetime may not live long enough
+// }
+
+fn fields(local: &str) {
+    // Struct fields are coercion sites too, and Named is covariant.
+    let n = Named { name: "static" };
+    let shorter: Named<'_> = n;
+    let both = [shorter, Named { name: local }];
+    assert_eq!(both[1].name, "cs_local");
+
+    // A Cell<&'static str> can't be viewed as a Cell<&'a str>, or `set`
+    // could put a short-lived reference in it.
+    let c: Cell<&'static str> = Cell::new("static");
+    let b: MyCellBox<&'static str> = MyCellBox::new("static");
+    assert_eq!((c.get(), b.get()), ("static", "static"));
+    //
+    // let r: &Cell<&str> = &c;
+    // r.set(local); // won't compile: lifetime may not live long enough
+    //
+    // let r: &MyCellBox<&str> = &b;
+    // r.set(local); // won't compile: lifetime may not live long enough
+}
+
+/// Subtyping on lifetimes (a `&'static str` used as a `&'a str`) happens at
+/// the same places as any other coercion: a `let` with a type, an
+/// assignment, a function's arguments, its return value, and the fields
+/// of a struct literal. Each compiles only because the type involved is
+/// covariant in the lifetime being shortened: references, Box, MyBox3,
+/// structs whose fields are.
+///
+/// Make the same type invariant, by putting it behind a `&mut` or in a
+/// Cell or a MyCellBox, and the same code is rejected: an invariant type's
+/// lifetime can't be shortened anywhere, and a shorter reference can't be
+/// written through it into a place that expects a longer one.
+#[lesson(
+    title = "Coercion sites: where subtyping applies, and where invariance stops it",
+    tags = ["variance", "lifetimes"],
+    notes = [
+        "`*(&mut long) = local` won't compile: `&mut &'static str` is invariant",
+        "Returning a MyCellBox<&'static str> as a MyCellBox<&'a str> won't compile",
+    ],
+)]
+pub fn demo() {
+    let local = print_on_drop!("cs_local", String::from("cs_local"));
+    assigned(&local.1);
+    arguments(&local.1);
+    assert_eq!(returned(), "static");
+    assert_eq!(*returned_box(MyBox3::new("static")), "static");
+    fields(&local.1);
+}
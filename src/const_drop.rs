@@ -0,0 +1,79 @@
+// Drop in const contexts: a `const fn`, or the initializer of a `const`
+// or `static`, may make values that have destructors, but may not drop
+// them, since destructors can't be run at compile time. What such a value
+// can do instead is get out of the const context alive: be returned, be
+// forgotten, or be the const's value.
+//
+// That last case has a twist: a `const` is not a place, but a value
+// pasted in wherever it's named, so each use makes a new one, dropped at
+// run time like any other.
+
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
+
+use lesson::lesson;
+
+use crate::{recorder, PrintOnDrop, State};
+
+// PrintOnDrop::new isn't const (it numbers and announces each value), but
+// the tuple struct's constructor is. Id 0, since no number was taken.
+const fn const_print_on_drop(name: &'static str, t: i32) -> PrintOnDrop<i32> {
+    PrintOnDrop(Cow::Borrowed(name), t, State::Valid, 0, PhantomData)
+}
+
+const PASTED: PrintOnDrop<i32> = const_print_on_drop("cd_pasted", 1);
+
+// None of these may drop their PrintOnDrop:
+//
+// const fn discard(p: PrintOnDrop<i32>) {} // won't compile: E0493, destructor of `PrintOnDrop<i32>` cannot be evaluated at compile-time
+// const fn overwrite(mut p: PrintOnDrop<i32>) -> PrintOnDrop<i32> { p = const_print_on_drop("cd_new", 2); p } // won't compile: E0493, destructor of `PrintOnDrop<i32>` cannot be evaluated at compile-time
+// const LOCAL: u64 = { let p = const_print_on_drop("cd_local", 3); p.3 }; // won't compile: E0493, destructor of `PrintOnDrop<i32>` cannot be evaluated at compile-time
+
+// These may: the value leaves alive, or not at all.
+const fn pass_through(p: PrintOnDrop<i32>) -> PrintOnDrop<i32> {
+    p
+}
+
+const fn forget(p: PrintOnDrop<i32>) {
+    mem::forget(p);
+}
+
+const fn keep(p: PrintOnDrop<i32>) -> ManuallyDrop<PrintOnDrop<i32>> {
+    ManuallyDrop::new(p)
+}
+
+// Evaluated at compile time; nothing was dropped, so nothing is recorded.
+const _: () = forget(const_print_on_drop("cd_forgotten", 4));
+const KEPT: ManuallyDrop<PrintOnDrop<i32>> = keep(const_print_on_drop("cd_kept", 5));
+
+/// A value with a destructor may be made in a `const fn` or a const
+/// initializer, but not dropped there: going out of scope, being
+/// overwritten, or being a temporary are all errors (E0493), since
+/// destructors can't run at compile time. It has to leave alive: returned,
+/// forgotten with mem::forget, wrapped in a ManuallyDrop, or as the value
+/// of the const itself.
+///
+/// A const is a value, not a place: each use of one makes a fresh copy,
+/// which is dropped at run time like any other value. So a const with a
+/// destructor runs it once per use, and never for the const itself. (A
+/// `static` is a place, and is never dropped.)
+#[lesson(
+    title = "Drop in const contexts: not allowed, and what to do instead",
+    tags = ["drop-order"],
+    notes = [
+        "`const fn discard(p: PrintOnDrop<i32>) {}` won't compile: E0493",
+        "A const used twice is two values, each dropped at run time",
+    ],
+)]
+pub fn demo() {
+    recorder::take();
+    drop(PASTED);
+    let _again = PASTED;
+    drop(pass_through(const_print_on_drop("cd_passed", 6)));
+    // A ManuallyDrop isn't dropped even at run time.
+    let kept = KEPT;
+    assert_eq!(kept.name(), "cd_kept");
+    drop(_again);
+    assert_eq!(recorder::take_drops(), ["cd_pasted", "cd_passed", "cd_pasted"]);
+}
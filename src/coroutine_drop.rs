@@ -0,0 +1,98 @@
+// What async fns are made of: a coroutine, a closure that can `yield`,
+// compiled to a state machine that holds, in each suspended state, the
+// locals still alive across that `yield`. Resumed to completion, it drops
+// them as any function would; dropped while suspended, it drops whatever
+// the state it's in holds, then and there, and the code after the
+// `yield` never runs. async_drop_order.rs, one layer down.
+//
+// Unstable (`coroutines`, `coroutine_trait`), so behind the `coroutines`
+// feature.
+
+use std::ops::{Coroutine, CoroutineState};
+use std::pin::pin;
+
+use lesson::lesson;
+
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder};
+
+fn completed() {
+    let mut co = pin!(
+        #[coroutine]
+        || {
+            let a = print_on_drop!("co_a", 1);
+            yield 1;
+            let b = print_on_drop!("co_b", 2);
+            yield 2;
+            a.id() + b.id()
+        }
+    );
+    assert!(matches!(co.as_mut().resume(()), CoroutineState::Yielded(1)));
+    assert!(matches!(co.as_mut().resume(()), CoroutineState::Yielded(2)));
+    // `b`, then `a`, at the end of the body, before it returns.
+    assert!(matches!(co.as_mut().resume(()), CoroutineState::Complete(_)));
+    mark("co_completed");
+}
+
+fn suspended() {
+    let co = #[coroutine]
+    || {
+        let a = print_on_drop!("co_a", 1);
+        // Dropped before the `yield`, so not part of the suspended state.
+        drop(print_on_drop!("co_gone", 0));
+        yield;
+        let b = print_on_drop!("co_b", 2);
+        yield;
+        mark("co_never_reached");
+        drop(a);
+        drop(b);
+    };
+    let mut co = Box::pin(co);
+    co.as_mut().resume(());
+    co.as_mut().resume(());
+    mark("co_suspended");
+    // Both are held across the second `yield`: dropped with the coroutine,
+    // `b` first.
+    drop(co);
+    mark("co_dropped");
+}
+
+fn never_resumed() {
+    // Owns its captures from the start; its locals were never made.
+    let captured = print_on_drop!("co_captured", 1);
+    let co = #[coroutine]
+    move || {
+        let _local = print_on_drop!("co_local", 2);
+        yield;
+        captured.id()
+    };
+    mark("co_made");
+    drop(co);
+}
+
+/// A coroutine is a closure that can `yield`: each `resume` runs it to its
+/// next `yield`, or to the end. The compiler turns it into a state
+/// machine, with one state per `yield`, holding the locals alive across
+/// that point; `async fn` is built on the same machinery, with `.await`
+/// yielding.
+///
+/// Resumed to completion, a coroutine drops its locals as a function
+/// would, at the end of their scopes. Dropped while suspended, it drops
+/// what its current state holds, in reverse order of declaration, and
+/// nothing after the `yield` runs; values dropped before the `yield`
+/// aren't in that state at all. Dropped before it was ever resumed, it
+/// holds only its captures.
+#[lesson(
+    title = "Coroutines: what a suspended state holds, and when it's dropped",
+    tags = ["drop-order", "async"],
+    notes = ["Needs the unstable `coroutines` and `coroutine_trait` features"],
+)]
+pub fn demo() {
+    recorder::take();
+    completed();
+    assert_eq!(recorder::take_drops(), ["co_b", "co_a", "co_completed"]);
+    suspended();
+    assert_eq!(recorder::take_drops(), ["co_gone", "co_suspended", "co_b", "co_a", "co_dropped"]);
+    never_resumed();
+    assert_eq!(recorder::take_drops(), ["co_made", "co_captured"]);
+}
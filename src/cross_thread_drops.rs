@@ -0,0 +1,200 @@
+// Drop order across threads. Within a thread it is as fixed as ever; what
+// the threads do relative to one another is not, except where they
+// synchronize: a scoped thread is joined before `thread::scope` returns,
+// so everything it dropped was dropped before anything the scope's
+// caller drops afterwards. That join is what lets a value moved into the
+// thread borrow the caller's locals at all.
+//
+// The recorder keeps each thread's events apart, so the merged order
+// comes from an observer subscribed for every thread (the event bus, see
+// observer.rs), stamping each drop with a clock all threads share and
+// with the lane (the thread) it happened on. An Interleavings says which
+// merged orders are legal: each lane's own drops in its own order, and
+// across lanes only the given happens-before edges.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use lesson::lesson;
+
+use crate::alloc_counter::uncounted;
+use crate::observer::{subscribe, DropObserver};
+use crate::print_on_drop;
+use crate::recorder::{self, Event};
+
+thread_local! {
+    // Which lane this thread's drops go in, if it is one of the demo's.
+    static LANE: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+// One drop, as the bus saw it.
+#[derive(Clone, Debug, PartialEq)]
+struct Stamped {
+    at: u64,
+    lane: &'static str,
+    name: Cow<'static, str>,
+}
+
+struct Bus {
+    clock: AtomicU64,
+    events: Mutex<Vec<Stamped>>,
+}
+
+impl DropObserver for Bus {
+    fn on_record(&self, event: &Event) {
+        let (Event::Drop(name, ..), Some(lane)) = (event, LANE.try_with(Cell::get).ok().flatten()) else {
+            return;
+        };
+        // Taken before the lock, so the clock orders drops as they happened,
+        // whichever thread gets the lock first.
+        let at = self.clock.fetch_add(1, Ordering::SeqCst);
+        uncounted(|| self.events.lock().unwrap().push(Stamped { at, lane, name: name.clone() }));
+    }
+}
+
+impl Bus {
+    // Everything since the last `take`, in the clock's order.
+    fn take(&self) -> Vec<Stamped> {
+        let mut events = std::mem::take(&mut *self.events.lock().unwrap());
+        events.sort_by_key(|e| e.at);
+        events
+    }
+}
+
+static BUS: Bus = Bus { clock: AtomicU64::new(0), events: Mutex::new(Vec::new()) };
+
+/// The merged orders a run may legally produce.
+struct Interleavings<'a> {
+    /// Each lane's drops, in the order that lane makes them.
+    lanes: &'a [(&'static str, Vec<&'static str>)],
+    /// `(a, b)`: `a` is dropped before `b`, whatever their lanes.
+    before: &'a [(&'static str, &'static str)],
+}
+
+impl Interleavings<'_> {
+    fn admits(&self, timeline: &[Stamped]) -> Result<(), String> {
+        if let Some(e) = timeline.iter().find(|e| self.lanes.iter().all(|(lane, _)| *lane != e.lane)) {
+            return Err(format!("{} dropped on lane {}, which wasn't expected", e.name, e.lane));
+        }
+        for (lane, expected) in self.lanes {
+            let got: Vec<&str> = timeline.iter().filter(|e| e.lane == *lane).map(|e| &*e.name).collect();
+            if got != *expected {
+                return Err(format!("{}: dropped {:?}, expected {:?}", lane, got, expected));
+            }
+        }
+        let at = |name: &str| timeline.iter().find(|e| e.name == name).map(|e| e.at);
+        for (a, b) in self.before {
+            match (at(a), at(b)) {
+                (Some(x), Some(y)) if x < y => {}
+                _ => return Err(format!("{} wasn't dropped before {}", a, b)),
+            }
+        }
+        Ok(())
+    }
+}
+
+const LANES: [&str; 3] = ["t0", "t1", "t2"];
+const MOVED: [&str; 3] = ["ct_moved0", "ct_moved1", "ct_moved2"];
+const LOCAL: [&str; 3] = ["ct_local0", "ct_local1", "ct_local2"];
+
+// Each thread is handed a PrintOnDrop borrowing `v`, and makes one of its
+// own borrowing `w`. The first drops what it was handed before making its
+// own; the others leave it with the closure, which is dropped after the
+// body's locals.
+fn round() {
+    LANE.set(Some("main"));
+    let v = print_on_drop!("ct_v", 13);
+    let w = print_on_drop!("ct_w", 42);
+    thread::scope(|s| {
+        for i in 0..LANES.len() {
+            let moved = print_on_drop!(MOVED[i], &v);
+            let w = &w;
+            s.spawn(move || {
+                LANE.set(Some(LANES[i]));
+                assert_eq!(moved.1 .1, 13);
+                if i == 0 {
+                    drop(moved);
+                }
+                let _local = print_on_drop!(LOCAL[i], w);
+            });
+        }
+        // Outside the scope, a thread could outlive `v`:
+        //
+        // let r = &v;
+        // thread::spawn(move || drop(print_on_drop!("ct_escaped", r))); // won't compile: E0597, `v` does not live long enough
+        //
+        // Somewhere among the threads' drops; where is up to the scheduler.
+        drop(print_on_drop!("ct_mid", ()));
+    });
+    drop(w);
+    drop(v);
+    LANE.set(None);
+}
+
+fn expected_lanes() -> Vec<(&'static str, Vec<&'static str>)> {
+    let mut lanes = vec![("main", vec!["ct_mid", "ct_w", "ct_v"])];
+    for i in 0..LANES.len() {
+        let order = if i == 0 { vec![MOVED[i], LOCAL[i]] } else { vec![LOCAL[i], MOVED[i]] };
+        lanes.push((LANES[i], order));
+    }
+    lanes
+}
+
+/// Values that borrow the caller's locals can be moved into scoped
+/// threads, and dropped there, because the scope joins every thread
+/// before it returns: all their drops happen before the caller drops what
+/// they borrowed. Within each thread the order is the usual one (a value
+/// moved into the closure is dropped with the closure, after the body's
+/// own locals, unless dropped sooner); across threads any interleaving is
+/// legal, and which one a run gets is up to the scheduler.
+///
+/// The expectation is therefore not one order but a set of them: each
+/// thread's sequence, and the happens-before edges the join adds. Every
+/// run, however interleaved, must be admitted.
+#[lesson(
+    title = "Drop order across scoped threads: per-thread sequences and the join",
+    tags = ["threads", "drop-order"],
+    notes = ["Spawned with thread::spawn, not in a scope, a value borrowing `v` won't compile: E0597"],
+)]
+pub fn demo() {
+    let lanes = expected_lanes();
+    let mut before = Vec::new();
+    for i in 0..LANES.len() {
+        before.extend([(MOVED[i], "ct_w"), (LOCAL[i], "ct_w")]);
+    }
+    let legal = Interleavings { lanes: &lanes, before: &before };
+
+    let _bus = subscribe(&BUS);
+    BUS.take();
+    let mut seen = HashSet::new();
+    let mut last = Vec::new();
+    crate::quiet_drops(|| {
+        for _ in 0..20 {
+            round();
+            last = BUS.take();
+            if let Err(e) = legal.admits(&last) {
+                panic!("illegal interleaving: {}", e);
+            }
+            seen.insert(last.iter().map(|e| e.name.clone()).collect::<Vec<_>>());
+        }
+    });
+    println!("cross_thread_drops: {} distinct interleavings in 20 runs; the last:", seen.len());
+    for e in &last {
+        println!("  {:>3} {:<4} {}", e.at, e.lane, e.name);
+    }
+    recorder::take();
+
+    // Out of order within a lane, or after the join: not admitted.
+    let position = |name| last.iter().position(|e| e.name == name).unwrap();
+    let mut swapped = last.clone();
+    swapped.swap(position("ct_local1"), position("ct_moved1"));
+    assert!(legal.admits(&swapped).is_err());
+    let mut late = last.clone();
+    late.retain(|e| e.name != "ct_moved2");
+    late.push(Stamped { at: u64::MAX, lane: "t2", name: Cow::Borrowed("ct_moved2") });
+    assert_eq!(legal.admits(&late), Err("ct_moved2 wasn't dropped before ct_w".to_string()));
+}
@@ -0,0 +1,159 @@
+// Decoder<'a, T>: the other half of encoder.rs, and the template for any
+// generic that only *produces* its `T`. It reads `T`s out of a byte slice
+// it borrows, one at a time, and hands each one over as it is made; it
+// never keeps one. So `T` only appears in what `next` returns, and the
+// field that carries it is a PhantomData<fn() -> T>:
+//
+//   - not PhantomData<T>, which would claim a `T` is owned: dropck would
+//     take dropping a Decoder as dropping a `T`, and a Decoder would only
+//     be Send and Sync if `T` were, though it is only ever a slice and an
+//     offset;
+//   - not PhantomData<fn(T)>, which would make it contravariant, as if it
+//     consumed `T`s, so a Decoder<&'static str> couldn't be used as a
+//     Decoder<&'a str>, though handing out longer-lived values than asked
+//     for is always fine.
+//
+// Errors are phantom_errors.rs's ParseError<T>, which says what was being
+// decoded without holding one either.
+
+use std::marker::PhantomData;
+use std::mem::{needs_drop, size_of};
+use std::rc::Rc;
+
+use lesson::lesson;
+
+use crate::auto_trait_tests::{assert_impl_send, assert_impl_sync};
+use crate::phantom_errors::ParseError;
+use crate::variance_tests::assert_covariant;
+use crate::{recorder, PrintOnDrop};
+
+pub trait FromBytes: Sized {
+    /// Reads one value from the start of `bytes`, returning it and how
+    /// many bytes it took, or None if they don't start with one.
+    fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)>;
+}
+
+/// Two little-endian `i16`s.
+#[derive(Debug, PartialEq)]
+pub struct Point {
+    pub x: i16,
+    pub y: i16,
+}
+
+impl FromBytes for Point {
+    fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)> {
+        let x = i16::from_le_bytes(bytes.get(0..2)?.try_into().unwrap());
+        let y = i16::from_le_bytes(bytes.get(2..4)?.try_into().unwrap());
+        Some((Point { x, y }, 4))
+    }
+}
+
+/// A name (its length in one byte, then UTF-8) and a little-endian `u32`,
+/// made into a PrintOnDrop of that name, so that it shows who drops it.
+pub struct Tagged(PrintOnDrop<u32>);
+
+impl FromBytes for Tagged {
+    fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)> {
+        let len = *bytes.first()? as usize;
+        let name = std::str::from_utf8(bytes.get(1..1 + len)?).ok()?;
+        let value = u32::from_le_bytes(bytes.get(1 + len..5 + len)?.try_into().unwrap());
+        Some((Tagged(PrintOnDrop::new(name.to_string(), value)), 5 + len))
+    }
+}
+
+pub struct Decoder<'a, T> {
+    input: &'a [u8],
+    at: usize,
+    _decodes: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: FromBytes> Decoder<'a, T> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Decoder { input, at: 0, _decodes: PhantomData }
+    }
+}
+
+impl<T: FromBytes> Iterator for Decoder<'_, T> {
+    type Item = Result<T, ParseError<T>>;
+
+    // After an error, there is nothing more to decode: where the next
+    // value would start is anyone's guess.
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.input[self.at..];
+        if rest.is_empty() {
+            return None;
+        }
+        match T::from_bytes(rest) {
+            Some((t, len)) => {
+                self.at += len;
+                Some(Ok(t))
+            }
+            None => {
+                self.at = self.input.len();
+                Some(Err(ParseError::new(&format!("{:?}", rest))))
+            }
+        }
+    }
+}
+
+// Covariant in `T`, and Send and Sync whatever it is.
+assert_covariant!(for<'a> Decoder<'static, &'a str>);
+assert_impl_send!(Decoder<'static, Rc<u8>>);
+assert_impl_sync!(Decoder<'static, Rc<u8>>);
+
+fn tagged(name: &str, value: u32) -> Vec<u8> {
+    let mut bytes = vec![name.len() as u8];
+    bytes.extend_from_slice(name.as_bytes());
+    bytes.extend_from_slice(&value.to_le_bytes());
+    bytes
+}
+
+/// A producer-style generic, one that makes `T`s but never holds one,
+/// carries `T` as PhantomData<fn() -> T>: a function returning a `T`.
+/// That is covariant in `T`, like owning one would be, but says nothing
+/// about ownership, so a Decoder has no drop glue for `T`, and is Send
+/// and Sync as its fields are, whatever it decodes.
+///
+/// Here, decoding Tagged values (each a PrintOnDrop) shows it: whoever
+/// takes one from the decoder drops it, and dropping a decoder part way
+/// through drops nothing.
+#[lesson(
+    title = "Decoder<T>: an output-only type parameter, as PhantomData<fn() -> T>",
+    tags = ["typestate", "variance", "auto-traits"],
+)]
+pub fn demo() {
+    let mut bytes = Vec::new();
+    for v in [3i16, -4, 5, 6] {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    let points: Result<Vec<Point>, _> = Decoder::new(&bytes).collect();
+    assert_eq!(points.unwrap(), [Point { x: 3, y: -4 }, Point { x: 5, y: 6 }]);
+
+    // Cut short, the second one is an error, which says what it was for.
+    let mut cut = Decoder::<Point>::new(&bytes[..6]);
+    assert!(cut.next().unwrap().is_ok());
+    let e = cut.next().unwrap().unwrap_err();
+    assert_eq!(e.input(), "[5, 0]");
+    assert!(e.to_string().ends_with("decoder::Point"));
+    assert!(cut.next().is_none());
+
+    recorder::take();
+    let mut bytes = tagged("dec_a", 1);
+    bytes.extend(tagged("dec_b", 2));
+    bytes.extend(tagged("dec_c", 3));
+    let (a, b);
+    {
+        let mut decoder = Decoder::<Tagged>::new(&bytes);
+        a = decoder.next().unwrap().unwrap();
+        b = decoder.next().unwrap().unwrap();
+    }
+    assert_eq!((a.0.name(), b.0 .1), ("dec_a", 2));
+    assert!(recorder::take_drops().is_empty());
+    drop(b);
+    drop(a);
+    assert_eq!(recorder::take_drops(), ["dec_b", "dec_a"]);
+
+    // What it decodes doesn't change what a Decoder is.
+    assert!(!needs_drop::<Decoder<Tagged>>());
+    assert_eq!(size_of::<Decoder<Tagged>>(), size_of::<Decoder<Point>>());
+}
@@ -0,0 +1,131 @@
+// `#[derive(Clone)]` on a struct generic over `T` puts a `T: Clone` bound
+// on the impl, whatever the fields are: the derive only sees the struct's
+// parameters, not whether a field needs them to be Clone. For a struct
+// whose only use of `T` is a PhantomData, the bound is never needed
+// (PhantomData<T> is Clone, Copy, Default, Debug and PartialEq for every
+// `T`), and gets in the way: an Id<T> for a `T` that can't be cloned
+// can't be copied either, though it is only a number.
+//
+// Id<T> is the same struct as DerivedId<T>, with the impls written out,
+// bounded on nothing. phantom_errors.rs does the same for its errors.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use lesson::lesson;
+
+use crate::auto_trait_tests::{assert_impl, assert_not_impl};
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// Neither Clone, nor Copy, nor Default, nor Debug, nor PartialEq.
+struct NoTraits;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+struct DerivedId<T> {
+    raw: u32,
+    _of: PhantomData<fn() -> T>,
+}
+
+struct Id<T> {
+    raw: u32,
+    _of: PhantomData<fn() -> T>,
+}
+
+impl<T> Id<T> {
+    fn new(raw: u32) -> Self {
+        Id { raw, _of: PhantomData }
+    }
+}
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> Default for Id<T> {
+    fn default() -> Self {
+        Id::new(0)
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Id({})", self.raw)
+    }
+}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+// Derived, each impl takes after `T`...
+assert_impl!(DerivedId<u32>: Clone);
+assert_impl!(DerivedId<u32>: Copy);
+assert_impl!(DerivedId<u32>: Default);
+assert_not_impl!(DerivedId<NoTraits>: Clone);
+assert_not_impl!(DerivedId<NoTraits>: Copy);
+assert_not_impl!(DerivedId<NoTraits>: Default);
+assert_not_impl!(DerivedId<NoTraits>: fmt::Debug);
+assert_not_impl!(DerivedId<NoTraits>: PartialEq);
+assert_not_impl!(DerivedId<PrintOnDrop<i32>>: Clone);
+assert_not_impl!(DerivedId<String>: Copy);
+// ...and written out, none of them does.
+assert_impl!(Id<NoTraits>: Clone);
+assert_impl!(Id<NoTraits>: Copy);
+assert_impl!(Id<NoTraits>: Default);
+assert_impl!(Id<NoTraits>: fmt::Debug);
+assert_impl!(Id<NoTraits>: PartialEq);
+assert_impl!(Id<PrintOnDrop<i32>>: Copy);
+
+// The derived impls at work, for a `T` that has what they ask for:
+fn derived() -> DerivedId<u32> {
+    let id = DerivedId::<u32>::default();
+    let copy = id;
+    assert_eq!(id, copy);
+    DerivedId { raw: id.raw + 1, ..copy }
+}
+
+// And for one that doesn't:
+//
+// fn derived_no_traits() {
+//     let id = DerivedId::<NoTraits>::default(); // won't compile: E0599, the associated function or constant `default` exists for struct `DerivedId<NoTraits>`, but its trait bounds were not satisfied
+//     let id = DerivedId::<NoTraits> { raw: 1, _of: PhantomData };
+//     let copy = id.clone(); // won't compile: E0599, the method `clone` exists for struct `DerivedId<NoTraits>`, but its trait bounds were not satisfied
+// }
+
+/// A `#[derive(Clone)]` (or Copy, Default, Debug, PartialEq...) on a
+/// generic struct bounds the impl on each type parameter being Clone too,
+/// even if the only field that mentions it is a PhantomData, which is
+/// Clone regardless. So a derived `DerivedId<T>` is only Copy for a `T`
+/// that is, and an id of something that can't be cloned, a PrintOnDrop
+/// say, can't be copied, though all it holds is a `u32`.
+///
+/// Writing the impls out, each for every `T`, is the fix: Id<T> is
+/// Copy, Default, Debug and PartialEq whatever `T` is.
+#[lesson(
+    title = "Derives on a struct with a PhantomData<T> field bound `T`, and hand-written impls that don't",
+    tags = ["traits"],
+    notes = ["Default or Clone on a DerivedId<NoTraits> won't compile: E0599"],
+)]
+pub fn demo() {
+    assert_eq!(derived().raw, 1);
+
+    // Ids of PrintOnDrops, copied around freely, indexing a vector of
+    // them.
+    recorder::take();
+    let values = [print_on_drop!("db_a", 1), print_on_drop!("db_b", 2)];
+    let ids: Vec<Id<PrintOnDrop<i32>>> = vec![Id::new(1), Id::default(), Id::new(1)];
+    let copied = ids.clone();
+    assert_eq!(ids, copied);
+    assert_eq!(format!("{:?}", ids[0]), "Id(1)");
+    let named: Vec<&str> = copied.iter().map(|id| values[id.raw as usize].name()).collect();
+    assert_eq!(named, ["db_b", "db_a", "db_b"]);
+    let _none = Id::<NoTraits>::default();
+    drop(values);
+    assert_eq!(recorder::take_drops(), ["db_a", "db_b"]);
+}
@@ -0,0 +1,77 @@
+// A destructor that panics. On its own, that's a panic like any other,
+// and unwinds like one; but a destructor that panics while another panic
+// is unwinding (which is when destructors run the most) can't be
+// unwound from, since there is no way to carry two panics at once, and
+// the process aborts. No catch_unwind can stop it, so the demo runs it in
+// a child process: the program runs itself again, as registry.rs does for
+// unsound demos, and looks at how the child died.
+//
+// Behind the `abort-demos` feature, since it needs this program's binary
+// to run (not the Python bindings', say) and makes a mess of stderr.
+
+use std::env;
+use std::time::Duration;
+
+use crate::registry::{run_child, ChildExit};
+use crate::unwind_safe::quietly;
+use crate::{print_on_drop, recorder};
+
+// Set in the environment of the child, which panics twice.
+const CHILD: &str = "DOUBLE_PANIC_CHILD";
+
+struct PanicOnDrop;
+
+impl Drop for PanicOnDrop {
+    fn drop(&mut self) {
+        panic!("dp: panicking in drop");
+    }
+}
+
+fn double_panic() -> ! {
+    let _never = print_on_drop!("dp_never", 1);
+    let _panics = PanicOnDrop;
+    let _unwound = print_on_drop!("dp_unwound", 2);
+    // Unwinding drops `_unwound`, then `_panics`, which aborts: `_never`
+    // is never dropped.
+    panic!("dp: the first panic");
+}
+
+crate::registry::demo! {
+    "A destructor panicking during a panic: abort, watched from a child process",
+    tags: ["panics", "drop-order"],
+}
+
+pub fn demo() {
+    if env::var_os(CHILD).is_some() {
+        double_panic();
+    }
+
+    // One panic is just that, even from a destructor: caught, and with
+    // everything else dropped as usual.
+    recorder::take();
+    let r = quietly(|| {
+        std::panic::catch_unwind(|| {
+            let _v = print_on_drop!("dp_v", 1);
+            drop(PanicOnDrop);
+        })
+    });
+    assert!(r.is_err());
+    assert_eq!(recorder::take_drops(), ["dp_v"]);
+
+    let child = run_child("double_panic", &[(CHILD, "1"), ("RUST_BACKTRACE", "0")], Duration::from_secs(10))
+        .expect("couldn't run a child process");
+    let (stdout, stderr) = (&child.stdout, &child.stderr);
+    let ChildExit::Exited(status) = &child.exit else {
+        panic!("double_panic: the child didn't abort, but hung");
+    };
+    println!("double_panic: child {}", child.exit);
+    assert!(!status.success());
+    // SIGABRT: an abort, not an exit with a panic's status of 101.
+    #[cfg(unix)]
+    assert_eq!(child.exit.signal(), Some(6));
+    assert!(stderr.contains("dp: the first panic"));
+    assert!(stderr.contains("dp: panicking in drop"));
+    assert!(stderr.contains("panic in a destructor during cleanup"));
+    assert!(stdout.contains("dp_unwound#"));
+    assert!(!stdout.contains("dp_never#"));
+}
@@ -0,0 +1,105 @@
+// DropQueue<T> takes ownership of values whose destruction should wait:
+// they are dropped together, in the order they were pushed, when the
+// queue is flushed (or itself dropped), rather than one by one as each
+// goes out of scope.
+//
+// That is the shape of deferred reclamation (hazard pointers, epochs,
+// RCU) in miniature: a writer that unlinks a value some reader may still
+// be looking at "retires" it into a queue, and the queue is only flushed
+// once no reader can be. Here the borrow checker already knows who is
+// looking, so there is nothing to wait for, but the effect on drop order
+// is the same.
+//
+// The storage is a MyVec<T>, so DropQueue needs no Drop impl (or
+// PhantomData) of its own: MyVec's already tells dropck that the queue
+// drops `T`s, and nothing more.
+
+use crate::my_vec::MyVec;
+use crate::{recorder, PrintOnDrop};
+
+pub struct DropQueue<T> {
+    pending: MyVec<T>,
+}
+
+impl<T> DropQueue<T> {
+    pub fn new() -> Self {
+        DropQueue { pending: MyVec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Takes `t`, to be dropped at the next flush.
+    pub fn push(&mut self, t: T) {
+        self.pending.push(t);
+    }
+
+    /// Drops everything pushed so far, oldest first.
+    pub fn flush(&mut self) {
+        self.pending.drain(..).for_each(drop);
+    }
+}
+
+fn f_scoped() {
+    {
+        let _a = PrintOnDrop::new("dq_a", 1);
+        let _b = PrintOnDrop::new("dq_b", 2);
+        let _c = PrintOnDrop::new("dq_c", 3);
+    }
+    // Locals go in reverse order, as soon as their scope ends.
+    assert_eq!(recorder::take_drops(), ["dq_c", "dq_b", "dq_a"]);
+}
+
+fn f_deferred() {
+    let mut queue = DropQueue::new();
+    {
+        let a = PrintOnDrop::new("dq_a", 1);
+        let b = PrintOnDrop::new("dq_b", 2);
+        let c = PrintOnDrop::new("dq_c", 3);
+        queue.push(a);
+        queue.push(b);
+        queue.push(c);
+    }
+    // Nothing has been dropped with the scope...
+    assert!(recorder::take_drops().is_empty());
+    println!("DropQueue: {} pending", queue.len());
+    // ...until the flush, which goes in push order.
+    queue.flush();
+    assert_eq!(recorder::take_drops(), ["dq_a", "dq_b", "dq_c"]);
+
+    // Whatever is still pending when the queue goes goes with it.
+    queue.push(PrintOnDrop::new("dq_d", 4));
+    drop(queue);
+    assert_eq!(recorder::take_drops(), ["dq_d"]);
+}
+
+fn f_borrowing() {
+    // The queue may hold values that borrow from locals declared before
+    // it, which outlive it:
+    let v = PrintOnDrop::new("dq_v", 13);
+    let mut queue = DropQueue::new();
+    queue.push(PrintOnDrop::new("dq_ref", &v));
+    queue.flush();
+    queue.push(PrintOnDrop::new("dq_ref2", &v));
+
+    // but, postponed or not, values can't be dropped after what they
+    // borrow from:
+    //
+    // let (mut _queue, v2); // won't compile: `v2` does not live long enough
+    // v2 = PrintOnDrop::new("v2", 13);
+    // _queue = DropQueue::new();
+    // _queue.push(PrintOnDrop::new("dq_bad", &v2));
+}
+
+crate::registry::demo! {
+    "DropQueue: drops deferred until a flush",
+    tags: ["drop-order", "collections"],
+}
+
+pub fn demo() {
+    recorder::take();
+    f_scoped();
+    f_deferred();
+    f_borrowing();
+}
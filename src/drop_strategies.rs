@@ -0,0 +1,74 @@
+// How much does *when* values are dropped cost? The same number of
+// PrintOnDrops (with printing turned off, but still recorded) created and
+// destroyed three ways:
+//
+// - eagerly: each boxed and dropped at the end of its own loop iteration,
+//   as plain scoping would have it;
+// - deferred: each boxed and moved into a DropQueue, flushed at the end;
+// - in an arena: no box per value, one chunk per many, and all of them
+//   destroyed together when the arena goes.
+//
+// The first two allocate (and free) once per value; only the arena saves
+// on that, which is most of the difference. The timings are printed as a
+// summary table at the end of the demo.
+
+use std::time::{Duration, Instant};
+
+use crate::alloc_counter::allocations;
+use crate::arena::Arena;
+use crate::drop_queue::DropQueue;
+use crate::{quiet_drops, recorder, MyBox3, PrintOnDrop};
+
+const N: usize = 100_000;
+
+// Runs `f`, returning how long it took and how many allocations it made,
+// and checking that it dropped all `N` values.
+fn measure(f: impl FnOnce()) -> (Duration, usize) {
+    recorder::take();
+    recorder::set_capacity(N);
+    let before = allocations();
+    let start = Instant::now();
+    f();
+    let elapsed = start.elapsed();
+    let allocs = allocations() - before;
+    assert_eq!((recorder::take().len(), recorder::lost()), (N, 0));
+    (elapsed, allocs)
+}
+
+fn eager() {
+    for i in 0..N {
+        let _b = MyBox3::new(PrintOnDrop::new("ds_eager", i));
+    }
+}
+
+fn deferred() {
+    let mut queue = DropQueue::new();
+    for i in 0..N {
+        queue.push(MyBox3::new(PrintOnDrop::new("ds_deferred", i)));
+    }
+    queue.flush();
+}
+
+fn arena() {
+    let arena = Arena::new();
+    for i in 0..N {
+        arena.alloc(PrintOnDrop::new("ds_arena", i));
+    }
+}
+
+crate::registry::demo!("Eager, deferred and arena drops compared", tags: ["drop-order", "bench"]);
+
+pub fn demo() {
+    let results = quiet_drops(|| {
+        [("eager", measure(eager)), ("deferred", measure(deferred)), ("arena", measure(arena))]
+    });
+    recorder::set_capacity(recorder::DEFAULT_CAPACITY);
+
+    println!("drop strategies, {} values:", N);
+    for (name, (elapsed, allocs)) in results {
+        println!("  {:<9} {:>10.2?} {:>8} allocations", name, elapsed, allocs);
+    }
+    let allocs = |i: usize| results[i].1 .1;
+    assert!(allocs(0) >= N && allocs(1) >= N);
+    assert!(allocs(2) < 20);
+}
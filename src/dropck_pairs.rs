@@ -0,0 +1,118 @@
+// f2's lesson, generalized: a value, a container holding something that
+// borrows it, and the two orders they could be declared in. Value first,
+// and the container goes first, while the value is still there: sound.
+// Container first, and the value goes first, so the container's drop
+// sees it dangling: unsound, and exactly what a missing PhantomData lets
+// through.
+//
+// `dropck_pair!` takes the scenario once, as a body assigning the two,
+// and registers both orders as demos of their own:
+//
+//     dropck_pair! {
+//         mybox2: "MyBox2, which rustc can't tell apart from MyBox3",
+//         tags: ["dropck"],
+//         |v, _mb| {
+//             print_on_drop!(v = 13);
+//             _mb = MyBox2::new(print_on_drop!("mb", &v));
+//         }
+//     }
+//
+// gives `mybox2_value_first` and `mybox2_container_first`. The latter is
+// marked unsound, so it only runs when asked for (by name, or with
+// `--unsound`), and then in a child process of its own (see registry.rs),
+// where whatever the UB does can't take the rest of the run with it.
+// Each runs inside a scope marker of its own name (see scope_guard.rs),
+// so that the output shows which order it was that dropped what.
+//
+// For a container that gets dropck right, container-first doesn't
+// compile, and neither does its dropck_pair!; see f1 and f3 in main.rs.
+// One that only does so in some builds says when the container-first
+// order is there, with a `container_first: #[cfg(..)]` line after its
+// tags (see eyepatch.rs).
+
+use crate::registry::Demo;
+use crate::scope_guard::scope;
+use crate::unit_marker::WithUnit;
+use crate::{print_on_drop, MyBox2};
+
+macro_rules! dropck_pair {
+    (
+        $name:ident: $description:literal,
+        tags: [$($tag:literal),*],
+        $(container_first: #[$cf:meta],)?
+        |$v:ident, $c:ident| $body:block
+    ) => {
+        pub mod $name {
+            use super::*;
+
+            pub fn value_first() {
+                scope!(concat!(stringify!($name), "_value_first"));
+                let ($v, $c);
+                $body
+            }
+
+            $(#[$cf])?
+            pub fn container_first() {
+                scope!(concat!(stringify!($name), "_container_first"));
+                let ($c, $v);
+                $body
+            }
+
+            pub const VALUE_FIRST: Demo = Demo {
+                path: concat!(module_path!(), "_value_first"),
+                description: concat!($description, " (value first: sound)"),
+                tags: &[$($tag),*],
+                expected_drops: None,
+                run: value_first,
+                lesson: None,
+                unsound: false,
+            };
+
+            $(#[$cf])?
+            pub const CONTAINER_FIRST: Demo = Demo {
+                path: concat!(module_path!(), "_container_first"),
+                description: concat!($description, " (container first: unsound)"),
+                tags: &[$($tag),*],
+                expected_drops: None,
+                run: container_first,
+                lesson: None,
+                unsound: true,
+            };
+        }
+    };
+}
+
+dropck_pair! {
+    mybox2: "MyBox2: accepted in both orders, without a PhantomData",
+    tags: ["dropck"],
+    container_first: #[cfg(not(feature = "no-eyepatch"))],
+    |v2, _mb2| {
+        print_on_drop!(v2 = 13);
+        _mb2 = MyBox2::new(print_on_drop!("mb2", &v2));
+    }
+}
+
+// The same with a closure as the payload: its drop glue drops the
+// PrintOnDrop it captured, which still borrows. MyBox3 only compiles the
+// value-first order, as in f4; MyBox2 takes both, as before.
+dropck_pair! {
+    mybox2_closure: "MyBox2 holding a closure that owns a borrowing value",
+    tags: ["dropck"],
+    container_first: #[cfg(not(feature = "no-eyepatch"))],
+    |v2c, _mb2c| {
+        print_on_drop!(v2c = 13);
+        let p = print_on_drop!("mb2c", &v2c);
+        _mb2c = MyBox2::new(move || p.id());
+    }
+}
+
+// A `()` where MyBox3 has its PhantomData<T> (see unit_marker.rs): the
+// same size, and no more use to dropck than no field at all.
+dropck_pair! {
+    with_unit: "A box with a `()` marker instead of a PhantomData<T>",
+    tags: ["dropck"],
+    |vu, _mbu| {
+        print_on_drop!(vu = 13);
+        _mbu = WithUnit::new(print_on_drop!("mbu", &vu));
+    }
+}
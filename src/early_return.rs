@@ -0,0 +1,91 @@
+// Leaving a function early, by `return` or `?`, drops what leaving it at
+// the end would: every local initialized so far, in reverse order of
+// declaration, after the temporaries of the returned expression. Locals
+// not yet reached were never made, and ones moved out of have nothing
+// left to drop; the compiler keeps a drop flag for any local it can't
+// tell about until run time.
+
+use lesson::lesson;
+
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+fn returns_early(early: bool) -> u64 {
+    let a = print_on_drop!("er_a", 1);
+    let b = print_on_drop!("er_b", 2);
+    if early {
+        // The temporary first, then `b` and `a`; `c` doesn't exist yet.
+        return print_on_drop!("er_temp", 0).id();
+    }
+    let c = print_on_drop!("er_c", 3);
+    a.id() + b.id() + c.id()
+}
+
+#[derive(Debug)]
+struct Failed(PrintOnDrop<i32>);
+
+fn check(ok: bool) -> Result<PrintOnDrop<i32>, Failed> {
+    if ok {
+        Ok(print_on_drop!("er_ok", 1))
+    } else {
+        Err(Failed(print_on_drop!("er_error", 2)))
+    }
+}
+
+fn question_mark(ok: bool) -> Result<u64, Failed> {
+    let a = print_on_drop!("er_a", 1);
+    // On Err, `?` returns the error, which isn't dropped here but handed
+    // to the caller, and drops `a`.
+    let checked = check(ok)?;
+    let c = print_on_drop!("er_c", 3);
+    Ok(a.id() + checked.id() + c.id())
+}
+
+fn moved_out(early: bool) {
+    let a = print_on_drop!("er_moved", 1);
+    let b = print_on_drop!("er_b", 2);
+    if early {
+        // Only decided at run time: `a`'s drop flag says not to drop it
+        // again on the way out.
+        drop(a);
+        mark("er_returning");
+        return;
+    }
+    mark("er_end");
+    assert!(a.id() < b.id());
+}
+
+/// A `return` or a `?` drops what the end of the function would have:
+/// first the temporaries of the returned expression, then every local
+/// initialized so far, in reverse order of declaration. Locals declared
+/// after the early exit were never made, and aren't dropped.
+///
+/// `?` on an Err returns the error (through From), which isn't dropped
+/// where it was made, but handed to the caller with whatever it owns. And
+/// a local moved out of before the exit isn't dropped a second time:
+/// where whether it was moved is only known at run time, a hidden drop
+/// flag on the stack keeps track.
+#[lesson(
+    title = "Early exits: return and ?, and which locals they drop",
+    tags = ["drop-order"],
+)]
+pub fn demo() {
+    recorder::take();
+    returns_early(true);
+    assert_eq!(recorder::take_drops(), ["er_temp", "er_b", "er_a"]);
+    returns_early(false);
+    assert_eq!(recorder::take_drops(), ["er_c", "er_b", "er_a"]);
+
+    let e = question_mark(false).unwrap_err();
+    assert_eq!(recorder::take_drops(), ["er_a"]);
+    assert_eq!(e.0.name(), "er_error");
+    drop(e);
+    assert_eq!(recorder::take_drops(), ["er_error"]);
+    assert!(question_mark(true).is_ok());
+    assert_eq!(recorder::take_drops(), ["er_c", "er_ok", "er_a"]);
+
+    moved_out(true);
+    assert_eq!(recorder::take_drops(), ["er_moved", "er_returning", "er_b"]);
+    moved_out(false);
+    assert_eq!(recorder::take_drops(), ["er_end", "er_b", "er_moved"]);
+}
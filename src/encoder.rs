@@ -0,0 +1,107 @@
+// Encoder<F>: one encoder type, parameterized by an output format `F`
+// that it never stores. `F` is only there so that the right trait impl
+// gets picked, at compile time, for each call; there is no `F` value, and
+// no vtable.
+//
+// Writing `struct Encoder<F: Format> { out: Vec<u8> }` is rejected with
+// E0392 ("type parameter `F` is never used"): rustc needs every parameter
+// to appear in a field, so it can work out variance and auto traits.
+// PhantomData<F> is that field. (For a pure tag like this, which we never
+// own or produce, PhantomData<fn() -> F> would serve equally well.)
+
+use std::marker::PhantomData;
+
+pub trait Format {
+    fn begin(out: &mut Vec<u8>);
+    fn field(out: &mut Vec<u8>, first: bool, name: &str, value: i64);
+    fn end(out: &mut Vec<u8>);
+}
+
+/// `{"name":value,...}`
+pub struct Json;
+
+/// Per field: name length (1 byte), name, value (8 bytes, little-endian).
+pub struct Binary;
+
+impl Format for Json {
+    fn begin(out: &mut Vec<u8>) {
+        out.push(b'{');
+    }
+    fn field(out: &mut Vec<u8>, first: bool, name: &str, value: i64) {
+        if !first {
+            out.push(b',');
+        }
+        out.extend_from_slice(format!("\"{}\":{}", name, value).as_bytes());
+    }
+    fn end(out: &mut Vec<u8>) {
+        out.push(b'}');
+    }
+}
+
+impl Format for Binary {
+    fn begin(_: &mut Vec<u8>) {}
+    fn field(out: &mut Vec<u8>, _: bool, name: &str, value: i64) {
+        out.push(name.len().try_into().expect("field name too long"));
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    fn end(_: &mut Vec<u8>) {}
+}
+
+pub struct Encoder<F: Format> {
+    out: Vec<u8>,
+    fields: usize,
+    _format: PhantomData<F>,
+}
+
+impl<F: Format> Encoder<F> {
+    pub fn new() -> Self {
+        let mut out = Vec::new();
+        F::begin(&mut out);
+        Encoder { out, fields: 0, _format: PhantomData }
+    }
+
+    pub fn field(mut self, name: &str, value: i64) -> Self {
+        F::field(&mut self.out, self.fields == 0, name, value);
+        self.fields += 1;
+        self
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        F::end(&mut self.out);
+        self.out
+    }
+}
+
+// Generic code is written once, against any format.
+fn encode_point<F: Format>(x: i64, y: i64) -> Vec<u8> {
+    Encoder::<F>::new().field("x", x).field("y", y).finish()
+}
+
+crate::registry::demo! {
+    "Encoder<F>: a format chosen at compile time, never stored",
+    tags: ["typestate"],
+}
+
+pub fn demo() {
+    let json = encode_point::<Json>(3, -4);
+    assert_eq!(json, br#"{"x":3,"y":-4}"#);
+    println!("Encoder<Json>: {}", String::from_utf8(json).unwrap());
+
+    let bin = encode_point::<Binary>(3, -4);
+    let mut expected = vec![1, b'x'];
+    expected.extend_from_slice(&3i64.to_le_bytes());
+    expected.extend_from_slice(&[1, b'y']);
+    expected.extend_from_slice(&(-4i64).to_le_bytes());
+    assert_eq!(bin, expected);
+    println!("Encoder<Binary>: {:?}", bin);
+
+    // The format costs nothing at run time.
+    assert_eq!(size_of::<Encoder<Json>>(), size_of::<Encoder<Binary>>());
+
+    // And without the PhantomData:
+    //
+    // struct BadEncoder<F: Format> { // won't compile: E0392, parameter `F` is never used
+    //     out: Vec<u8>,
+    // }
+}
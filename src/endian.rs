@@ -0,0 +1,90 @@
+// U32<E>: a 32-bit integer stored as four bytes in byte order `E`, the way
+// it appears in a file header or network packet. The byte order exists
+// only at the type level, in a PhantomData<E>; at run time a U32<E> is
+// just its `[u8; 4]`.
+//
+// Because U32<BigEndian> and U32<LittleEndian> are different types, a
+// value read from a big-endian field can't accidentally be compared with,
+// or written into, a little-endian one; converting is explicit.
+
+use std::marker::PhantomData;
+
+pub trait Endianness {
+    fn to_bytes(n: u32) -> [u8; 4];
+    fn from_bytes(b: [u8; 4]) -> u32;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BigEndian;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LittleEndian;
+
+impl Endianness for BigEndian {
+    fn to_bytes(n: u32) -> [u8; 4] {
+        n.to_be_bytes()
+    }
+    fn from_bytes(b: [u8; 4]) -> u32 {
+        u32::from_be_bytes(b)
+    }
+}
+
+impl Endianness for LittleEndian {
+    fn to_bytes(n: u32) -> [u8; 4] {
+        n.to_le_bytes()
+    }
+    fn from_bytes(b: [u8; 4]) -> u32 {
+        u32::from_le_bytes(b)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct U32<E: Endianness> {
+    bytes: [u8; 4],
+    _order: PhantomData<E>,
+}
+
+impl<E: Endianness> U32<E> {
+    pub fn new(n: u32) -> Self {
+        U32 { bytes: E::to_bytes(n), _order: PhantomData }
+    }
+
+    /// Reinterprets four raw bytes, e.g. from a buffer, as being in order `E`.
+    pub fn from_raw(bytes: [u8; 4]) -> Self {
+        U32 { bytes, _order: PhantomData }
+    }
+
+    pub fn get(self) -> u32 {
+        E::from_bytes(self.bytes)
+    }
+
+    pub fn raw(self) -> [u8; 4] {
+        self.bytes
+    }
+
+    pub fn convert<F: Endianness>(self) -> U32<F> {
+        U32::new(self.get())
+    }
+}
+
+crate::registry::demo!("U32<E>: byte order at the type level", tags: ["typestate"]);
+
+pub fn demo() {
+    let header = [0x00, 0x00, 0x01, 0x02];
+    let be = U32::<BigEndian>::from_raw(header);
+    let le = U32::<LittleEndian>::from_raw(header);
+    println!("U32: {:?} is {} big-endian, {} little-endian", header, be.get(), le.get());
+
+    let converted: U32<LittleEndian> = be.convert();
+    assert_eq!(converted.get(), be.get());
+    assert_eq!(converted.raw(), [0x02, 0x01, 0x00, 0x00]);
+    assert_eq!(U32::<BigEndian>::new(258), be);
+    assert_eq!(size_of::<U32<BigEndian>>(), 4);
+
+    // Mixing byte orders:
+    //
+    // let _same = be == le; // won't compile: mismatched types
+    //
+    // let mut field: U32<BigEndian> = U32::new(0);
+    // field = le; // won't compile: mismatched types
+    // field.get();
+}
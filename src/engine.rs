@@ -0,0 +1,135 @@
+// The demos as a library, for programs (a GUI, a web service, a grader)
+// that want to run them and hear what they do, rather than run this
+// program and read its output:
+//
+//     let result = why_phantom_data::run_demo("scope_guard", &mut my_observer)?;
+//     assert!(result.failure.is_none());
+//     for e in &result.trace.events { ... }
+//
+// The observer hears of every PrintOnDrop made and dropped on the calling
+// thread while the demo runs, and needn't be `'static`, since it is only
+// lent for the call (see observer.rs). What other threads do, it doesn't
+// hear: a DropObserver `subscribe`d for the whole program does.
+//
+// While a demo runs, PrintOnDrop's own printing is muted (for the whole
+// program: see quiet_drops), but whatever else the demo prints, it still
+// prints. Unsound demos aren't run: the command line runs them in a child
+// process, which a library has no business starting.
+//
+// Any demo may be run any number of times in one process: none counts on
+// a static it left behind last time being as it was at startup (a test
+// in the demo below runs the ones that used to, twice). One needs the
+// program's help: MainThreadToken only exists on the thread marked with
+// `mark_main_thread` (see main_thread.rs), and without it that demo shows
+// less.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+
+use crate::observer::{self, DropObserver};
+use crate::registry;
+use crate::trace::{DemoInfo, Trace};
+use crate::{print_on_drop, quiet_drops, State};
+
+#[derive(Debug)]
+pub struct DemoResult {
+    pub demo: DemoInfo,
+    /// What the demo left in the recorder, whether or not it failed.
+    pub trace: Trace,
+    /// If one of the demo's checks failed, its panic message.
+    pub failure: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RunError {
+    NoSuchDemo(String),
+    Unsound(String),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::NoSuchDemo(name) => write!(f, "no demo is called {:?}", name),
+            RunError::Unsound(name) => write!(f, "{} is unsound, and only runs in a child process", name),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+/// Every demo, in the order the command line runs them.
+pub fn demos() -> Vec<DemoInfo> {
+    registry::all().into_iter().map(DemoInfo::from).collect()
+}
+
+/// Runs the sound demo called `name` on this thread, lending it `observer`.
+pub fn run_demo(name: &str, observer: &mut dyn DropObserver) -> Result<DemoResult, RunError> {
+    let Some(demo) = registry::all().into_iter().find(|d| d.name() == name) else {
+        return Err(RunError::NoSuchDemo(name.to_string()));
+    };
+    if demo.unsound {
+        return Err(RunError::Unsound(name.to_string()));
+    }
+    let run = || panic::catch_unwind(AssertUnwindSafe(|| demo.run_here()));
+    let (trace, failure) = match quiet_drops(|| observer::with_observer(observer, run)) {
+        Ok(trace) => (trace, None),
+        Err(payload) => {
+            let msg = match payload.downcast::<String>() {
+                Ok(msg) => *msg,
+                Err(payload) => payload.downcast_ref::<&str>().unwrap_or(&"(not a string)").to_string(),
+            };
+            (Trace::take(name), Some(msg))
+        }
+    };
+    Ok(DemoResult { demo: DemoInfo::from(demo), trace, failure })
+}
+
+// An embedder's observer: with state of its own, and borrowed.
+#[derive(Default)]
+struct Log(Mutex<Vec<String>>);
+
+impl DropObserver for Log {
+    fn on_construct(&self, name: &str, _: u64) {
+        self.0.lock().unwrap().push(format!("+{}", name));
+    }
+
+    fn on_drop(&self, name: &str, _: u64, _: State, _: &dyn fmt::Debug) {
+        self.0.lock().unwrap().push(format!("-{}", name));
+    }
+}
+
+crate::registry::demo!("run_demo: the demos as a library, with an observer", tags: ["drop-order"]);
+
+pub fn demo() {
+    let mut log = Log::default();
+    let result = run_demo("scope_guard", &mut log).unwrap();
+    assert_eq!(result.failure, None);
+    assert_eq!(result.demo.tags, ["drop-order"]);
+    // scope_guard checks (and so takes) its own drops, leaving nothing.
+    assert!(result.trace.events.is_empty());
+    let log = log.0.into_inner().unwrap();
+    println!("run_demo: scope_guard, observed {:?}", log);
+    assert_eq!(log[..4], ["+sg_a", "+sg_b", "-sg_b", "-sg_a"]);
+
+    // Lent for the call only: the observer hears no more after it.
+    let mut log = Log::default();
+    run_demo("scope_guard", &mut log).unwrap();
+    drop(print_on_drop!("engine_after", 0));
+    assert!(!log.0.into_inner().unwrap().iter().any(|e| e.ends_with("engine_after")));
+
+    assert_eq!(run_demo("nonesuch", &mut Log::default()).err(), Some(RunError::NoSuchDemo("nonesuch".into())));
+    assert_eq!(
+        run_demo("with_unit_container_first", &mut Log::default()).err(),
+        Some(RunError::Unsound("with_unit_container_first".into()))
+    );
+    assert_eq!(demos().len(), registry::all().len());
+
+    // Again and again, to the same effect, for those with statics of
+    // their own.
+    for name in ["observer", "thread_local_drop", "main_thread", "my_vec"] {
+        for _ in 0..2 {
+            assert_eq!(run_demo(name, &mut Log::default()).unwrap().failure, None, "{}", name);
+        }
+    }
+}
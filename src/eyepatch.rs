@@ -0,0 +1,70 @@
+// What `#[may_dangle]` buys, apart from PhantomData. The comment at the
+// top of lib.rs wonders whether, without the eyepatch, MyBox2 would need
+// a PhantomData at all; the `no-eyepatch` feature builds MyBox2 and
+// MyBox3 with plain Drop impls, to see:
+//
+//     cargo run -- eyepatch
+//     cargo run --features no-eyepatch -- eyepatch
+//
+// Without the eyepatch, dropck assumes a Drop impl may use whatever its
+// `T` borrows, and talks to PhantomData no more: a box declared before a
+// value its payload borrows is turned down, whatever that payload is.
+// So MyBox2's unsound container-first orders no longer compile, and it
+// needs no PhantomData to be sound. But MyBox3 loses what Box allows it,
+// a payload with no drop glue (a bare reference, or a closure capturing
+// only one) borrowing a value declared after the box.
+//
+// The functions that stop compiling are behind `not(feature =
+// "no-eyepatch")` where they are defined, and listed here, with what it
+// is about each that takes the eyepatch. The list is checked against
+// them in the build with it, and the demo says, for the build it is in,
+// which ones are there.
+
+use crate::{print_on_drop, recorder, MyBox2, MyBox3};
+
+macro_rules! needs_eyepatch {
+    ($($f:path: $why:literal,)*) => {
+        // With the eyepatch, each one is there.
+        #[cfg(not(feature = "no-eyepatch"))]
+        const _: &[fn()] = &[$($f),*];
+
+        const NEEDS_EYEPATCH: &[(&str, &str)] = &[$((stringify!($f), $why)),*];
+    };
+}
+
+needs_eyepatch! {
+    crate::dropck_pairs::mybox2::container_first: "MyBox2, container first, of a PrintOnDrop borrowing the value",
+    crate::dropck_pairs::mybox2_closure::container_first: "MyBox2, container first, of a closure owning such a PrintOnDrop",
+    crate::f5: "MyBox3, container first, of a closure capturing just a reference",
+    crate::payload_matrix::container_first_refs: "MyBox3, container first, of a bare reference",
+}
+
+crate::registry::demo!("What #[may_dangle] buys, apart from PhantomData (see --features no-eyepatch)", tags: ["dropck"]);
+
+pub fn demo() {
+    let eyepatch = cfg!(not(feature = "no-eyepatch"));
+    println!("eyepatch: MyBox2 and MyBox3 built {} #[may_dangle]", if eyepatch { "with" } else { "without" });
+    for (f, why) in NEEDS_EYEPATCH {
+        let there = if eyepatch { "compiles" } else { "won't compile: E0597" };
+        println!("eyepatch: {:<52} {:<21} {}", f, there, why);
+    }
+
+    // Value first compiles either way, and drops the same.
+    recorder::take();
+    {
+        let v = print_on_drop!("ep_v", 13);
+        let _mb2 = MyBox2::new(print_on_drop!("ep_mb2", &v));
+        let _mb3 = MyBox3::new(print_on_drop!("ep_mb3", &v));
+        let r = &v;
+        let _f = MyBox3::new(move || r.id());
+    }
+    assert_eq!(recorder::take_drops(), ["ep_mb3", "ep_mb2", "ep_v"]);
+
+    // And with it, the sound ones of those that need it run as ever.
+    #[cfg(not(feature = "no-eyepatch"))]
+    {
+        crate::f5();
+        crate::payload_matrix::container_first_refs();
+        assert_eq!(recorder::take_drops(), ["v5"]);
+    }
+}
@@ -0,0 +1,98 @@
+// The "family" pattern: Rust has no higher-kinded types, so we can't write
+// `struct Partition<C<_>>` for "some container constructor C". Instead, a
+// zero-sized family type stands in for the constructor, and a generic
+// associated type maps each element type to the family's container:
+// VecFamily::Member<T> = Vec<T>, BoxFamily::Member<T> = Box<[T]>, and so on.
+//
+// Types that are generic over a family usually never hold a family value
+// (there's nothing in one), so they carry a PhantomData<F> to use the
+// parameter. Here `Partition<F>` is such a type: an algorithm object whose
+// only configuration is which kind of container it produces.
+
+use std::marker::PhantomData;
+
+use crate::my_vec::MyVec;
+
+pub trait Family {
+    type Member<T>;
+    fn build<T>(items: impl Iterator<Item = T>) -> Self::Member<T>;
+    fn len<T>(m: &Self::Member<T>) -> usize;
+}
+
+pub struct VecFamily;
+pub struct BoxFamily;
+pub struct MyVecFamily;
+
+impl Family for VecFamily {
+    type Member<T> = Vec<T>;
+    fn build<T>(items: impl Iterator<Item = T>) -> Vec<T> {
+        items.collect()
+    }
+    fn len<T>(m: &Vec<T>) -> usize {
+        m.len()
+    }
+}
+
+impl Family for BoxFamily {
+    type Member<T> = Box<[T]>;
+    fn build<T>(items: impl Iterator<Item = T>) -> Box<[T]> {
+        items.collect()
+    }
+    fn len<T>(m: &Box<[T]>) -> usize {
+        m.len()
+    }
+}
+
+impl Family for MyVecFamily {
+    type Member<T> = MyVec<T>;
+    fn build<T>(items: impl Iterator<Item = T>) -> MyVec<T> {
+        let mut v = MyVec::new();
+        for t in items {
+            v.push(t);
+        }
+        v
+    }
+    fn len<T>(m: &MyVec<T>) -> usize {
+        m.len()
+    }
+}
+
+/// Splits items by a predicate, into containers of family `F`.
+pub struct Partition<F: Family> {
+    _family: PhantomData<F>,
+}
+
+impl<F: Family> Partition<F> {
+    pub fn new() -> Self {
+        Partition { _family: PhantomData }
+    }
+
+    pub fn run<T>(&self, items: Vec<T>, pred: impl Fn(&T) -> bool) -> (F::Member<T>, F::Member<T>) {
+        let (yes, no): (Vec<T>, Vec<T>) = items.into_iter().partition(pred);
+        (F::build(yes.into_iter()), F::build(no.into_iter()))
+    }
+}
+
+// One generic algorithm, any family.
+fn count_evens<F: Family>(xs: Vec<i32>) -> (usize, usize) {
+    let (evens, odds) = Partition::<F>::new().run(xs, |x| x % 2 == 0);
+    (F::len(&evens), F::len(&odds))
+}
+
+crate::registry::demo! {
+    "Families: type constructors without higher-kinded types",
+    tags: ["typestate"],
+}
+
+pub fn demo() {
+    let xs = || (1..=7).collect::<Vec<i32>>();
+    let (evens, odds): (Vec<i32>, Vec<i32>) = Partition::<VecFamily>::new().run(xs(), |x| x % 2 == 0);
+    println!("Partition<VecFamily>: {:?} {:?}", evens, odds);
+    let (evens, _): (Box<[i32]>, _) = Partition::<BoxFamily>::new().run(xs(), |x| x % 2 == 0);
+    println!("Partition<BoxFamily>: {:?}", evens);
+
+    assert_eq!(count_evens::<VecFamily>(xs()), (3, 4));
+    assert_eq!(count_evens::<BoxFamily>(xs()), (3, 4));
+    assert_eq!(count_evens::<MyVecFamily>(xs()), (3, 4));
+    assert_eq!(size_of::<Partition<MyVecFamily>>(), 0);
+}
@@ -0,0 +1,173 @@
+// Typestate: Fd<Open> and Fd<Closed> are the same bits (a file descriptor
+// number), told apart only by a phantom state parameter. `read` exists
+// only on Fd<Open>, and `close` consumes an Fd<Open> to produce an
+// Fd<Closed>, so reading after closing, or closing twice, doesn't compile.
+//
+// Drop can't be implemented for Fd<Open> alone (E0366: a Drop impl must
+// cover every instantiation of the type), so the state parameter carries
+// its drop behavior instead: an open descriptor is closed when dropped,
+// a closed one is left alone.
+//
+// The illegal transitions are `compile_fail` doctests on Fd, each with
+// the error it must fail with, so `cargo test` checks that they still
+// don't compile (which is why this module is public).
+
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::sync::Mutex;
+
+// The "kernel": descriptors currently open.
+static OPEN_FDS: Mutex<Option<HashSet<i32>>> = Mutex::new(None);
+
+fn sys_open() -> i32 {
+    let mut fds = OPEN_FDS.lock().unwrap();
+    let fds = fds.get_or_insert_with(HashSet::new);
+    let fd = (3..).find(|fd| !fds.contains(fd)).unwrap();
+    fds.insert(fd);
+    fd
+}
+
+fn sys_close(fd: i32) {
+    let closed = OPEN_FDS.lock().unwrap().as_mut().is_some_and(|fds| fds.remove(&fd));
+    assert!(closed, "close of fd {} that isn't open", fd);
+}
+
+fn sys_open_count() -> usize {
+    OPEN_FDS.lock().unwrap().as_ref().map_or(0, HashSet::len)
+}
+
+pub trait FdState {
+    fn drop_fd(fd: i32);
+}
+
+pub struct Open;
+pub struct Closed;
+
+impl FdState for Open {
+    fn drop_fd(fd: i32) {
+        println!("Fd<Open>: closing fd {} on drop", fd);
+        sys_close(fd);
+    }
+}
+
+impl FdState for Closed {
+    fn drop_fd(_: i32) {}
+}
+
+/// A file descriptor, open or closed as `S` says.
+///
+/// Each illegal transition is a compile error. Reading a closed fd:
+///
+/// ```compile_fail,E0599
+/// # use why_phantom_data::fd_state::Fd;
+/// let mut c = Fd::open().close();
+/// c.read(&mut [0; 4]);
+/// ```
+///
+/// or reading one through the binding it was closed from:
+///
+/// ```compile_fail,E0382
+/// # use why_phantom_data::fd_state::Fd;
+/// let mut f = Fd::open();
+/// let _c = f.close();
+/// f.read(&mut [0; 4]);
+/// ```
+///
+/// Closing twice, either way:
+///
+/// ```compile_fail,E0382
+/// # use why_phantom_data::fd_state::Fd;
+/// let d = Fd::open();
+/// let e = d.close();
+/// d.close();
+/// drop(e);
+/// ```
+///
+/// ```compile_fail,E0599
+/// # use why_phantom_data::fd_state::Fd;
+/// Fd::open().close().close();
+/// ```
+///
+/// Passing a closed fd off as an open one:
+///
+/// ```compile_fail,E0308
+/// # use why_phantom_data::fd_state::{Fd, Open};
+/// let g: Fd<Open> = Fd::open().close();
+/// drop(g);
+/// ```
+///
+/// Or inventing a state of our own:
+///
+/// ```compile_fail,E0277
+/// # use why_phantom_data::fd_state::Fd;
+/// let h: Option<Fd<u8>> = None;
+/// drop(h);
+/// ```
+///
+/// What is legal, for comparison:
+///
+/// ```
+/// # use why_phantom_data::fd_state::Fd;
+/// let mut f = Fd::open();
+/// assert_eq!(f.read(&mut [0; 4]), 4);
+/// drop(f.close());
+/// ```
+pub struct Fd<S: FdState> {
+    raw: i32,
+    _state: PhantomData<S>,
+}
+
+impl Fd<Open> {
+    pub fn open() -> Self {
+        Fd { raw: sys_open(), _state: PhantomData }
+    }
+
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        let data = b"phantom";
+        let n = buf.len().min(data.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        n
+    }
+
+    pub fn close(self) -> Fd<Closed> {
+        // Don't let our own Drop close it a second time.
+        let this = ManuallyDrop::new(self);
+        sys_close(this.raw);
+        Fd { raw: this.raw, _state: PhantomData }
+    }
+}
+
+impl<S: FdState> Fd<S> {
+    pub fn raw(&self) -> i32 {
+        self.raw
+    }
+}
+
+impl<S: FdState> Drop for Fd<S> {
+    fn drop(&mut self) {
+        S::drop_fd(self.raw);
+    }
+}
+
+crate::registry::demo!("Typestate: Fd<Open> and Fd<Closed>", tags: ["typestate"]);
+
+pub fn demo() {
+    let before = sys_open_count();
+    let mut a = Fd::open();
+    let mut buf = [0; 4];
+    let n = a.read(&mut buf);
+    println!("Fd<Open>: read {:?} from fd {}", std::str::from_utf8(&buf[..n]).unwrap(), a.raw());
+    let a = a.close();
+    println!("Fd<Closed>: fd {} is closed, dropping it does nothing", a.raw());
+    drop(a);
+
+    {
+        // Closed by its destructor instead.
+        let _b = Fd::open();
+        assert_eq!(sys_open_count(), before + 1);
+    }
+    assert_eq!(sys_open_count(), before);
+
+    // Each illegal transition is a compile error: see Fd's doctests.
+}
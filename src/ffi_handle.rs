@@ -0,0 +1,134 @@
+// Wrapping a C library whose handles borrow from a context:
+//
+//     ctx_t *ctx_new(void);
+//     void ctx_free(ctx_t *);
+//     handle_t *handle_open(ctx_t *, int key);  /* valid until ctx_free */
+//     int handle_read(const handle_t *);
+//     void handle_close(handle_t *);
+//
+// A `handle_t *` is just a pointer, so nothing stops C code from using one
+// after `ctx_free`. On the Rust side, Handle<'ctx> stores only the raw
+// pointer too; its one connection to the Context is a PhantomData<&'ctx
+// Context>, which makes every handle borrow the context it came from, so
+// the context can't be freed (or moved) while a handle is alive.
+
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use crate::{print_on_drop, recorder};
+
+// The "C library", simulated in Rust so that the demo can check that it is
+// being used correctly.
+#[allow(non_camel_case_types)]
+mod sys {
+    pub struct ctx_t {
+        pub open_handles: usize,
+        pub values: Vec<i32>,
+    }
+
+    pub struct handle_t {
+        pub ctx: *mut ctx_t,
+        pub key: i32,
+    }
+
+    pub extern "C" fn ctx_new() -> *mut ctx_t {
+        Box::into_raw(Box::new(ctx_t { open_handles: 0, values: vec![10, 20, 30] }))
+    }
+
+    pub unsafe extern "C" fn ctx_free(ctx: *mut ctx_t) {
+        let ctx = unsafe { Box::from_raw(ctx) };
+        // A real library would just leave the handles dangling.
+        assert_eq!(ctx.open_handles, 0, "ctx_free with handles still open");
+    }
+
+    pub unsafe extern "C" fn handle_open(ctx: *mut ctx_t, key: i32) -> *mut handle_t {
+        unsafe { (*ctx).open_handles += 1 };
+        Box::into_raw(Box::new(handle_t { ctx, key }))
+    }
+
+    pub unsafe extern "C" fn handle_read(h: *const handle_t) -> i32 {
+        unsafe { (&(*(*h).ctx).values)[(*h).key as usize] }
+    }
+
+    pub unsafe extern "C" fn handle_close(h: *mut handle_t) {
+        let h = unsafe { Box::from_raw(h) };
+        unsafe { (*h.ctx).open_handles -= 1 };
+    }
+}
+
+pub struct Context {
+    raw: NonNull<sys::ctx_t>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Context { raw: NonNull::new(sys::ctx_new()).expect("ctx_new failed") }
+    }
+
+    // `&self` is enough: the C library does its own bookkeeping, and many
+    // handles may be open at once.
+    pub fn open(&self, key: i32) -> Handle<'_> {
+        let raw = unsafe { sys::handle_open(self.raw.as_ptr(), key) };
+        Handle { raw: NonNull::new(raw).expect("handle_open failed"), _ctx: PhantomData }
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe { sys::ctx_free(self.raw.as_ptr()) };
+    }
+}
+
+pub struct Handle<'ctx> {
+    raw: NonNull<sys::handle_t>,
+    _ctx: PhantomData<&'ctx Context>,
+}
+
+impl Handle<'_> {
+    pub fn read(&self) -> i32 {
+        unsafe { sys::handle_read(self.raw.as_ptr()) }
+    }
+}
+
+impl Drop for Handle<'_> {
+    fn drop(&mut self) {
+        unsafe { sys::handle_close(self.raw.as_ptr()) };
+    }
+}
+
+crate::registry::demo!("FFI handles that borrow from a context", tags: ["ffi", "lifetimes"]);
+
+pub fn demo() {
+    recorder::take();
+    {
+        // Neither is Debug (a raw pointer is all there is to show), so
+        // they are traced opaquely.
+        let ctx = print_on_drop!(opaque "fh_ctx", Context::new());
+        let h1 = print_on_drop!(opaque "fh_h1", ctx.1.open(0));
+        let h2 = print_on_drop!(opaque "fh_h2", ctx.1.open(2));
+        println!("Handle: read {} and {}", h1.1.read(), h2.1.read());
+    }
+    // Locals drop in reverse order: both handles are closed before the
+    // context is freed.
+    assert_eq!(recorder::take_drops(), ["fh_h2", "fh_h1", "fh_ctx"]);
+
+    // Freeing the context while a handle is open:
+    //
+    // let ctx = Context::new();
+    // let h = ctx.open(0);
+    // drop(ctx); // won't compile: cannot move out of `ctx` because it is borrowed
+    // h.read();
+    //
+    // or letting a handle escape the context's scope:
+    //
+    // let h;
+    // {
+    //     let ctx = Context::new();
+    //     h = ctx.open(1); // won't compile: `ctx` does not live long enough
+    // }
+    // h.read();
+    //
+    // Without the PhantomData (and `'ctx`), both compile, and the first one
+    // trips the assertion in `ctx_free`; a real C library would instead
+    // have `handle_read` read freed memory.
+}
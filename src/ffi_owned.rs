@@ -0,0 +1,129 @@
+// The owning side of FFI: a C library hands out a pointer to a struct we
+// may not look inside, and expects it back when we are done:
+//
+//     typedef struct widget widget_t;   /* opaque */
+//     widget_t *widget_new(void);
+//     void widget_poke(widget_t *);     /* not thread-safe */
+//     int widget_pokes(const widget_t *);
+//     void widget_free(widget_t *);
+//
+// On the Rust side, `widget_t` is declared as the nomicon suggests: a
+// zero-sized `#[repr(C)]` struct that can't be constructed, with a
+// PhantomData<(*mut u8, PhantomPinned)> making it !Send, !Sync and !Unpin,
+// since we know nothing about what's behind the pointer.
+//
+// Widget owns one. Its PhantomData<sys::widget_t> records that ownership
+// (it inherits `widget_t`'s auto traits), and its Drop calls
+// `widget_free`. Then we decide, from the library's documentation, which
+// auto traits to restore by hand: a widget may be used from any thread,
+// one thread at a time, so Widget is Send; `widget_poke` on a shared
+// widget would race, so it is not Sync.
+
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+use crate::auto_trait_tests::assert_not_impl_sync;
+
+#[allow(non_camel_case_types)]
+mod sys {
+    use std::marker::{PhantomData, PhantomPinned};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[repr(C)]
+    pub struct widget_t {
+        _data: [u8; 0],
+        _marker: PhantomData<(*mut u8, PhantomPinned)>,
+    }
+
+    // What the library actually allocates; private to the "C" side.
+    struct WidgetImpl {
+        pokes: i32,
+    }
+
+    // So the demo can check that every widget gets freed.
+    pub static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+    pub extern "C" fn widget_new() -> *mut widget_t {
+        LIVE.fetch_add(1, Ordering::Relaxed);
+        Box::into_raw(Box::new(WidgetImpl { pokes: 0 })) as *mut widget_t
+    }
+
+    pub unsafe extern "C" fn widget_poke(w: *mut widget_t) {
+        unsafe { (*(w as *mut WidgetImpl)).pokes += 1 };
+    }
+
+    pub unsafe extern "C" fn widget_pokes(w: *const widget_t) -> i32 {
+        unsafe { (*(w as *const WidgetImpl)).pokes }
+    }
+
+    pub unsafe extern "C" fn widget_free(w: *mut widget_t) {
+        drop(unsafe { Box::from_raw(w as *mut WidgetImpl) });
+        LIVE.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub struct Widget {
+    raw: NonNull<sys::widget_t>,
+    _owns: PhantomData<sys::widget_t>,
+}
+
+// Safety: the library allows a widget to be used from whichever thread,
+// as long as it is used by one thread at a time. There is no `Sync`
+// impl: `&Widget` only allows `pokes`, but C makes no promises about even
+// reading a widget concurrently with nothing else going on.
+unsafe impl Send for Widget {}
+
+impl Widget {
+    pub fn new() -> Self {
+        Widget { raw: NonNull::new(sys::widget_new()).expect("widget_new failed"), _owns: PhantomData }
+    }
+
+    pub fn poke(&mut self) {
+        unsafe { sys::widget_poke(self.raw.as_ptr()) };
+    }
+
+    pub fn pokes(&self) -> i32 {
+        unsafe { sys::widget_pokes(self.raw.as_ptr()) }
+    }
+}
+
+impl Drop for Widget {
+    fn drop(&mut self) {
+        unsafe { sys::widget_free(self.raw.as_ptr()) };
+    }
+}
+
+fn live() -> usize {
+    sys::LIVE.load(Ordering::Relaxed)
+}
+
+crate::registry::demo!("FFI: owning an opaque C struct", tags: ["ffi", "auto-traits"]);
+
+pub fn demo() {
+    let before = live();
+    let mut w = Widget::new();
+    w.poke();
+    // Send: the widget may move to another thread, and is freed there.
+    let pokes = thread::spawn(move || {
+        w.poke();
+        w.pokes()
+    })
+    .join()
+    .unwrap();
+    println!("Widget: poked {} times, live widgets: {}", pokes, live() - before);
+    assert_eq!(live(), before);
+
+    // Not Sync, so two threads can't share one:
+    assert_not_impl_sync!(Widget);
+    //
+    // let w = Widget::new();
+    // thread::scope(|s| {
+    //     s.spawn(|| w.pokes()); // won't compile: `NonNull<widget_t>` cannot be shared between threads safely
+    // });
+    //
+    // And without the `unsafe impl Send`, the `thread::spawn` above would
+    // be rejected too: `_owns` (and the NonNull) make Widget !Send + !Sync
+    // until we say otherwise.
+}
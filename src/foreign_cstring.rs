@@ -0,0 +1,140 @@
+// ForeignCString: an owned, NUL-terminated string allocated by a C
+// library's allocator, which must go back to that library's `free` rather
+// than to Rust's. That rules out CString (whose buffer belongs to Rust's
+// allocator), so we write the equivalent by hand: a raw pointer, a
+// PhantomData<CStr> saying that we own a CStr's worth of data (much as
+// CString owns a Box<CStr>), and a Drop calling the library's `free`.
+//
+// The simulated C allocator keeps track of what it handed out, so the
+// demo can show that nothing leaks and catch double frees that real C
+// would turn into heap corruption.
+
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+mod sys {
+    use std::alloc::{self, Layout};
+    use std::collections::HashMap;
+    use std::ffi::c_char;
+    use std::ptr;
+    use std::sync::Mutex;
+
+    pub static HEAP: Mutex<Option<HashMap<usize, Layout>>> = Mutex::new(None);
+    pub static BAD_FREES: Mutex<usize> = Mutex::new(0);
+
+    pub unsafe extern "C" fn c_strndup(s: *const c_char, n: usize) -> *mut c_char {
+        let layout = Layout::array::<u8>(n + 1).unwrap();
+        unsafe {
+            let p = alloc::alloc(layout);
+            ptr::copy_nonoverlapping(s as *const u8, p, n);
+            *p.add(n) = 0;
+            HEAP.lock().unwrap().get_or_insert_with(HashMap::new).insert(p as usize, layout);
+            p as *mut c_char
+        }
+    }
+
+    /// Unlike C's `free`, tolerates (and counts) pointers it doesn't know.
+    pub unsafe extern "C" fn c_free(p: *mut c_char) {
+        let layout = HEAP.lock().unwrap().get_or_insert_with(HashMap::new).remove(&(p as usize));
+        match layout {
+            Some(layout) => unsafe { alloc::dealloc(p as *mut u8, layout) },
+            None => *BAD_FREES.lock().unwrap() += 1,
+        }
+    }
+}
+
+pub struct ForeignCString {
+    ptr: NonNull<c_char>,
+    _owns: PhantomData<CStr>,
+}
+
+// Safety: the C allocator may be called from any thread, and a CStr is
+// immutable, so sharing one is fine too.
+unsafe impl Send for ForeignCString {}
+unsafe impl Sync for ForeignCString {}
+
+impl ForeignCString {
+    /// Panics if `s` contains a NUL byte.
+    pub fn new(s: &str) -> Self {
+        assert!(!s.contains('\0'), "interior NUL");
+        let p = unsafe { sys::c_strndup(s.as_ptr() as *const c_char, s.len()) };
+        ForeignCString { ptr: NonNull::new(p).unwrap(), _owns: PhantomData }
+    }
+
+    pub fn as_c_str(&self) -> &CStr {
+        unsafe { CStr::from_ptr(self.ptr.as_ptr()) }
+    }
+
+    /// Gives up ownership, e.g. to pass the string to C code that will
+    /// free it.
+    pub fn into_raw(self) -> *mut c_char {
+        let p = self.ptr.as_ptr();
+        mem::forget(self);
+        p
+    }
+
+    /// Safety: `p` must come from `into_raw` (or the C allocator), and be
+    /// owned by no one else.
+    pub unsafe fn from_raw(p: *mut c_char) -> Self {
+        ForeignCString { ptr: NonNull::new(p).unwrap(), _owns: PhantomData }
+    }
+}
+
+impl Drop for ForeignCString {
+    fn drop(&mut self) {
+        unsafe { sys::c_free(self.ptr.as_ptr()) };
+    }
+}
+
+fn live() -> usize {
+    sys::HEAP.lock().unwrap().as_ref().map_or(0, HashMap::len)
+}
+
+fn bad_frees() -> usize {
+    *sys::BAD_FREES.lock().unwrap()
+}
+
+crate::registry::demo! {
+    "ForeignCString: a string that goes back to C's free",
+    tags: ["ffi", "alloc"],
+}
+
+pub fn demo() {
+    let before = live();
+    let s = ForeignCString::new("hello from C");
+    println!("ForeignCString: {:?}", s.as_c_str());
+
+    // Round trip through a raw pointer: ownership leaves and comes back.
+    let p = s.into_raw();
+    assert_eq!(live(), before + 1);
+    let s = unsafe { ForeignCString::from_raw(p) };
+    assert_eq!(s.as_c_str().to_bytes(), b"hello from C");
+    drop(s);
+    assert_eq!(live(), before);
+
+    // Leaking is safe, but the leak is visible to the C allocator.
+    mem::forget(ForeignCString::new("leaked"));
+    assert_eq!(live(), before + 1);
+
+    // Two owners of the same pointer: the second drop is a double free.
+    // Only `unsafe` code can get us here; the safe API moves ownership.
+    let p = ForeignCString::new("twice").into_raw();
+    let (a, b) = unsafe { (ForeignCString::from_raw(p), ForeignCString::from_raw(p)) };
+    let bad_before = bad_frees();
+    drop(a);
+    drop(b);
+    println!("ForeignCString: caught {} double free(s)", bad_frees() - bad_before);
+    assert_eq!(bad_frees(), bad_before + 1);
+
+    // And the borrowed `&CStr` can't outlive its owner:
+    //
+    // let c: &CStr;
+    // {
+    //     let s = ForeignCString::new("short");
+    //     c = s.as_c_str(); // won't compile: `s` does not live long enough
+    // }
+    // println!("{:?}", c);
+}
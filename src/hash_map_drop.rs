@@ -0,0 +1,65 @@
+// The order a std HashMap drops its values in: its iteration order, which
+// depends on the hashes, and so on the RandomState each map is made with.
+// A new map gets new keys, so the same entries may be dropped in a
+// different order by every map, within one run of the program and across
+// runs. The order is unspecified, and the demo doesn't check it: only that
+// every value is dropped, once, which is what `drops_any_order` is for.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::BuildHasher;
+
+use lesson::lesson;
+
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+const KEYS: [&str; 8] = ["hm_0", "hm_1", "hm_2", "hm_3", "hm_4", "hm_5", "hm_6", "hm_7"];
+
+fn filled<S: BuildHasher>(hasher: S) -> HashMap<usize, PrintOnDrop<usize>, S> {
+    let mut map = HashMap::with_hasher(hasher);
+    for (i, name) in KEYS.into_iter().enumerate() {
+        map.insert(i, print_on_drop!(name, i));
+    }
+    map
+}
+
+/// A HashMap drops its entries in iteration order, which is whatever
+/// order its hashes put them in. With the default RandomState, every map
+/// is seeded differently, so the same keys inserted the same way may be
+/// dropped in a different order by every map, and by every run of the
+/// program. Two maps built with clones of one RandomState hash alike, and
+/// (with the same insertions) drop alike.
+///
+/// So there is no order to expect, only a set: every value dropped, each
+/// exactly once. The demo's expected drops say just that, and the
+/// registry checks them as a multiset.
+#[lesson(
+    title = "HashMap: drop order as unspecified as iteration order",
+    tags = ["drop-order", "collections"],
+    notes = ["Orders may repeat by chance: nothing is asserted about them but that they hold the same drops"],
+    drops_any_order = ["hm_0", "hm_1", "hm_2", "hm_3", "hm_4", "hm_5", "hm_6", "hm_7"],
+)]
+pub fn demo() {
+    let mut orders = BTreeSet::new();
+    for _ in 0..8 {
+        recorder::take();
+        drop(filled(RandomState::new()));
+        let order: Vec<_> = recorder::take_drops().into_iter().map(|d| d.into_owned()).collect();
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, KEYS);
+        orders.insert(order);
+    }
+    println!("HashMap: 8 maps, seeded 8 ways, dropped in {} different orders", orders.len());
+
+    // The same seed twice: the same order.
+    let seed = RandomState::new();
+    recorder::take();
+    drop(filled(seed.clone()));
+    let first = recorder::take_drops();
+    drop(filled(seed));
+    assert_eq!(recorder::take_drops(), first);
+
+    // What's left for the registry to check, in no particular order.
+    drop(filled(RandomState::new()));
+}
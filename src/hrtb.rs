@@ -0,0 +1,103 @@
+// A callback bound by `for<'a> Fn(&'a T)`: one that takes a `&T` of any
+// lifetime at all, including ones that only exist inside the holder's own
+// methods. That's what lets the holder call it on values it makes itself
+// and drops right after; a callback bound by `Fn(&'a T)` for some one
+// `'a` the caller picks can only be handed references that outlive `'a`,
+// which a local never does.
+//
+// The holder stores just the callback, so `T` only appears in bounds, and
+// has to be given a field anyway: PhantomData<fn(&T)>, which says the
+// holder takes `&T`s rather than owning a `T`. So it has no say in
+// dropck, and doesn't inherit `T`'s Send and Sync.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+use crate::my_vec::MyVec;
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+pub struct Callback<T, F> {
+    f: F,
+    _takes: PhantomData<fn(&T)>,
+}
+
+impl<T, F: for<'a> Fn(&'a T)> Callback<T, F> {
+    pub fn new(f: F) -> Self {
+        Callback { f, _takes: PhantomData }
+    }
+
+    pub fn call(&self, t: &T) {
+        (self.f)(t)
+    }
+
+    pub fn each(&self, items: &MyVec<T>) {
+        items.iter().for_each(&self.f);
+    }
+}
+
+impl<F: for<'a> Fn(&'a PrintOnDrop<i32>)> Callback<PrintOnDrop<i32>, F> {
+    // A value no caller can borrow for longer than this call, since it
+    // doesn't outlive it.
+    fn call_on_fresh(&self, name: &'static str) {
+        let fresh = print_on_drop!(name, 0);
+        self.call(&fresh);
+    }
+}
+
+// The same with one fixed lifetime, for comparison: as good when the
+// references come from outside...
+pub struct FixedCallback<'a, T, F: Fn(&'a T)> {
+    f: F,
+    _takes: PhantomData<fn(&'a T)>,
+}
+
+impl<'a, T, F: Fn(&'a T)> FixedCallback<'a, T, F> {
+    pub fn new(f: F) -> Self {
+        FixedCallback { f, _takes: PhantomData }
+    }
+
+    pub fn call(&self, t: &'a T) {
+        (self.f)(t)
+    }
+
+    // ...but not for its own values:
+    //
+    // fn call_on_fresh(&self, t: T) { (self.f)(&t) } // won't compile: E0597, `t` does not live long enough
+}
+
+// `T` is only in the bounds, so the field is required:
+//
+// pub struct Unmarked<T, F: Fn(&T)> { f: F } // won't compile: E0392, type parameter `T` is never used
+
+crate::registry::demo!("Higher-ranked callbacks: for<'a> Fn(&'a T), and a phantom T", tags: ["lifetimes", "variance"]);
+
+pub fn demo() {
+    recorder::take();
+    let seen = Cell::new(0);
+    let cb = Callback::new(|p: &PrintOnDrop<i32>| seen.set(seen.get() + p.1));
+    cb.call_on_fresh("hr_fresh");
+    assert_eq!(recorder::take_drops(), ["hr_fresh"]);
+
+    let mut items = MyVec::new();
+    items.push(print_on_drop!("hr_1", 1));
+    items.push(print_on_drop!("hr_2", 2));
+    cb.each(&items);
+    cb.call(&print_on_drop!("hr_temp", 3));
+    assert_eq!(seen.get(), 6);
+    assert_eq!(recorder::take_drops(), ["hr_temp"]);
+
+    // What the higher-ranked bound rules out: a callback that keeps the
+    // reference, since it may be to anything, however short-lived. With
+    // one fixed lifetime, that's fine, as long as what it keeps outlives
+    // the callback.
+    let kept = Cell::new(None);
+    let keeper = FixedCallback::new(|p: &PrintOnDrop<i32>| kept.set(Some(p)));
+    for p in items.iter() {
+        keeper.call(p);
+    }
+    assert_eq!(kept.get().map(PrintOnDrop::name), Some("hr_2"));
+    //
+    // let kept = Cell::new(None);
+    // let keeper = Callback::new(|p: &PrintOnDrop<i32>| kept.set(Some(p))); // won't compile: E0521, borrowed data escapes outside of closure
+    // keeper.call(items.iter().next().unwrap());
+}
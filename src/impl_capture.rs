@@ -0,0 +1,106 @@
+// What does a returned `impl Trait` borrow? Whatever the compiler decides
+// it *captures*, which isn't necessarily what the hidden type uses. In
+// edition 2024 it captures every lifetime and type parameter in scope; in
+// 2021 (this crate) every type parameter, and the lifetimes we name in
+// `+ 'a` or `use<..>`. Capturing too much means the caller must keep an
+// argument alive (and unmodified) for as long as the iterator or future,
+// even though it was only looked at up front.
+//
+// `+ use<'a>` says exactly which lifetimes are captured, but (for now) it
+// must still list every type parameter. A named return type has no such
+// problem: MyIter<'a, T> and SumFuture<'a, T> mention only what they
+// really borrow, through their PhantomData<&'a [T]>, and nothing else.
+
+use std::fmt::Display;
+use std::future::Future;
+
+use crate::borrowing_future::{block_on, sum_slowly, SumFuture};
+use crate::my_iter::MyIter;
+use crate::my_vec::MyVec;
+
+pub struct Filter {
+    pub min: u32,
+}
+
+// What edition 2024 would infer: `filter` stays borrowed as long as the
+// iterator lives.
+fn above_all_captured<'a, 'b>(data: &'a MyVec<u32>, filter: &'b Filter) -> impl Iterator<Item = &'a u32> + use<'a, 'b> {
+    let min = filter.min;
+    data.iter().filter(move |&&x| x >= min)
+}
+
+// Only `data` is.
+fn above<'a>(data: &'a MyVec<u32>, filter: &Filter) -> impl Iterator<Item = &'a u32> + use<'a> {
+    let min = filter.min;
+    data.iter().filter(move |&&x| x >= min)
+}
+
+// A type parameter always gets captured. With `L = &'l str`, that ties
+// the iterator to `'l`...
+fn labelled_opaque<'a, L: Display>(data: &'a MyVec<u32>, label: L) -> impl Iterator<Item = &'a u32> + use<'a, L> {
+    println!("impl_capture: iterating {}", label);
+    data.iter()
+}
+
+// ...and leaving it out of `use<..>` is an error ("`impl Trait` must
+// mention all type parameters in scope"). A named type has no `L`.
+fn labelled<'a, L: Display>(data: &'a MyVec<u32>, label: L) -> MyIter<'a, u32> {
+    println!("impl_capture: iterating {}", label);
+    data.iter()
+}
+
+// The same goes for futures: this one doesn't borrow `label`.
+fn sum_labelled<'a>(data: &'a [u8], label: &str) -> impl Future<Output = i64> + use<'a> {
+    println!("impl_capture: summing {}", label);
+    sum_slowly(data)
+}
+
+fn sum_labelled_named<'a, L: Display>(data: &'a [u8], label: L) -> SumFuture<'a, u8> {
+    println!("impl_capture: summing {}", label);
+    sum_slowly(data)
+}
+
+crate::registry::demo!("What a returned `impl Trait` captures", tags: ["lifetimes"]);
+
+pub fn demo() {
+    let mut data = MyVec::new();
+    for x in [1, 5, 10] {
+        data.push(x);
+    }
+
+    let mut filter = Filter { min: 5 };
+    let big = above(&data, &filter);
+    filter.min = 0;
+    assert_eq!(big.count(), 2);
+
+    let all = above_all_captured(&data, &filter);
+    // filter.min = 100; // won't compile: E0506, cannot assign to `filter.min` because it is borrowed
+    assert_eq!(all.count(), 3);
+
+    let it;
+    {
+        let label = String::from("data");
+        it = labelled(&data, label.as_str());
+    }
+    assert_eq!(it.count(), 3);
+    //
+    // let it;
+    // {
+    //     let label = String::from("data");
+    //     it = labelled_opaque(&data, label.as_str()); // won't compile: E0597, `label` does not live long enough
+    // }
+    // it.count();
+    //
+    // Fine as long as `L` outlives the iterator anyway.
+    assert_eq!(labelled_opaque(&data, "data").count(), 3);
+
+    let bytes = [1u8, 2, 3];
+    let fut;
+    let fut2;
+    {
+        let label = String::from("bytes");
+        fut = sum_labelled(&bytes, &label);
+        fut2 = sum_labelled_named(&bytes, &label);
+    }
+    assert_eq!((block_on(fut).0, block_on(fut2).0), (6, 6));
+}
@@ -0,0 +1,66 @@
+// Two ways for an owner to drop a `T` it holds through a raw pointer:
+//
+// - `ptr::read(p)` and let the copy go out of scope. That moves the whole
+//   value onto the stack first (a memcpy of `size_of::<T>()` bytes), only
+//   needs a `T: Sized`, and breaks any promise (like Pin's) that the value
+//   is dropped where it lies.
+// - `ptr::drop_in_place(p)`, which runs the destructor on the value where
+//   it is. No copy, and it works for `dyn Trait` and slices too, since
+//   the drop glue comes from the pointer's metadata.
+//
+// MyBox2 and MyBox3 both use the latter. Below, a value that checks its
+// own address on drop tells the two apart.
+
+use std::cell::Cell;
+use std::mem;
+use std::ptr::{self, NonNull};
+
+use crate::{MyBox2, MyBox3};
+
+thread_local! {
+    static DROPPED_AT: Cell<usize> = const { Cell::new(0) };
+}
+
+// Big enough that a copy would cost something, and notes where it was
+// when dropped.
+struct Heavy {
+    _data: [u8; 4096],
+}
+
+impl Drop for Heavy {
+    fn drop(&mut self) {
+        DROPPED_AT.set(self as *mut Heavy as usize);
+    }
+}
+
+// What MyBox2's Drop used to do.
+unsafe fn drop_by_read<T>(p: NonNull<T>) {
+    unsafe { drop(ptr::read(p.as_ptr())) };
+}
+
+crate::registry::demo!("Dropping in place versus reading out", tags: ["unsafe"]);
+
+pub fn demo() {
+    let b = MyBox2::new(Heavy { _data: [0; 4096] });
+    let home = b.v.as_ptr() as usize;
+    drop(b);
+    assert_eq!(DROPPED_AT.get(), home, "MyBox2 moved the value before dropping it");
+
+    let b = MyBox3::new(Heavy { _data: [0; 4096] });
+    let home = &*b as *const Heavy as usize;
+    drop(b);
+    assert_eq!(DROPPED_AT.get(), home, "MyBox3 moved the value before dropping it");
+
+    // The `ptr::read` way drops a copy somewhere on the stack instead.
+    let mut heavy = mem::ManuallyDrop::new(Heavy { _data: [0; 4096] });
+    let p = NonNull::from(&mut *heavy);
+    unsafe { drop_by_read(p) };
+    assert_ne!(DROPPED_AT.get(), p.as_ptr() as usize);
+
+    // And only drop_in_place can drop an unsized value:
+    //
+    // let s: &mut [String] = &mut [String::new()];
+    // unsafe { drop(ptr::read(s as *mut [String])) }; // won't compile: E0277, the size for values of type `[String]` cannot be known at compilation time
+    let mut s = mem::ManuallyDrop::new([String::from("in place")]);
+    unsafe { ptr::drop_in_place(&mut *s as &mut [String] as *mut [String]) };
+}
@@ -0,0 +1,84 @@
+// The escape hatches every owning pointer ends up needing: `into_raw`
+// gives up ownership for a raw pointer (to pass through FFI, say, or to
+// stash in an intrusive structure), `from_raw` takes it back, and `leak`
+// gives it up for good, in exchange for a reference that may live as long
+// as the `T` can.
+//
+// In between into_raw and from_raw, nothing owns the value: no MyBox3
+// exists, so no PhantomData says "drops a `T`", and dropck lets the value
+// outlive whatever it borrows from, should we never call from_raw. Leaking
+// is safe; it's using the raw pointer after that which isn't.
+
+use std::mem::ManuallyDrop;
+use std::ptr::NonNull;
+
+use crate::my_unique::MyUnique;
+use crate::{print_on_drop, recorder, MyBox3, PrintOnDrop};
+
+impl<T: ?Sized> MyBox3<T> {
+    pub fn into_raw(b: Self) -> *mut T {
+        ManuallyDrop::new(b).v.as_ptr()
+    }
+
+    /// # Safety
+    ///
+    /// `p` must have come from `into_raw`, and not have been passed to
+    /// `from_raw` since.
+    pub unsafe fn from_raw(p: *mut T) -> Self {
+        MyBox3 { v: MyUnique::new(unsafe { NonNull::new_unchecked(p) }) }
+    }
+
+    pub fn leak<'a>(b: Self) -> &'a mut T
+    where
+        T: 'a,
+    {
+        unsafe { &mut *MyBox3::into_raw(b) }
+    }
+}
+
+fn f_round_trip() {
+    recorder::take();
+    let b = MyBox3::new(PrintOnDrop::new("raw_a", 1));
+    let id = b.id();
+    let p = MyBox3::into_raw(b);
+    // No MyBox3, so nothing is dropped here...
+    assert!(recorder::take_drops().is_empty());
+    unsafe { (*p).1 += 1 };
+    // ...until ownership is taken back, and the new MyBox3 goes.
+    let b = unsafe { MyBox3::from_raw(p) };
+    assert_eq!((b.id(), b.1), (id, 2));
+    drop(b);
+    assert_eq!(recorder::take_drop_ids(), [id]);
+
+    // Unsized values survive the round trip, metadata and all.
+    #[cfg(feature = "unsize")]
+    {
+        let s: MyBox3<[i32]> = MyBox3::new([1, 2, 3]);
+        let p = MyBox3::into_raw(s);
+        let s = unsafe { MyBox3::from_raw(p) };
+        assert_eq!(s[..], [1, 2, 3]);
+    }
+}
+
+fn f_leak() {
+    let label: &'static mut PrintOnDrop<i32> = MyBox3::leak(MyBox3::new(PrintOnDrop::new("raw_leaked", 7)));
+    label.1 += 1;
+    println!("leaked: {:?}", label);
+    // It is never dropped, so it never shows up in the trace.
+    assert!(recorder::take_drops().is_empty());
+
+    // A leaked value can only live as long as what it borrows, so
+    // leaking one that borrows a local gets a reference tied to it:
+    let v = PrintOnDrop::new("raw_v", 13);
+    let leaked = MyBox3::leak(MyBox3::new(print_on_drop!(borrowing &v)));
+    println!("leaked: {:?}", leaked);
+    //
+    // let leaked: &'static mut _ = MyBox3::leak(MyBox3::new(PrintOnDrop::new("raw_bad", &v))); // won't compile: `v` does not live long enough
+}
+
+crate::registry::demo!("into_raw, from_raw and leak", tags: ["unsafe"]);
+
+pub fn demo() {
+    f_round_trip();
+    f_leak();
+}
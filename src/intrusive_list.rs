@@ -0,0 +1,95 @@
+// An intrusive singly-linked list: the list allocates nothing, and instead
+// threads itself through link fields embedded in nodes that live somewhere
+// else (here, on the stack).
+//
+// The list only ever holds raw pointers to those nodes, so on its own it
+// has no relationship to them at all; nothing would stop a node from being
+// dropped while still linked. The `PhantomData<&'a Node<T>>` is what ties
+// the list to the nodes' lifetime: it makes the list behave, for borrowck,
+// as if it held a shared borrow of every node it has been given. Note that
+// this is *not* PhantomData<T>: the list doesn't own anything.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use crate::PrintOnDrop;
+
+pub struct Node<T> {
+    value: T,
+    next: Cell<Option<NonNull<Node<T>>>>,
+    linked: Cell<bool>,
+}
+
+impl<T> Node<T> {
+    pub fn new(value: T) -> Self {
+        Node { value, next: Cell::new(None), linked: Cell::new(false) }
+    }
+}
+
+pub struct IntrusiveList<'a, T> {
+    head: Option<NonNull<Node<T>>>,
+    _pd: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> IntrusiveList<'a, T> {
+    pub fn new() -> Self {
+        IntrusiveList { head: None, _pd: PhantomData }
+    }
+
+    /// Links `node` in at the front. Panics if it is already in a list,
+    /// since it only has room for one set of links.
+    pub fn push_front(&mut self, node: &'a Node<T>) {
+        assert!(!node.linked.replace(true), "node is already linked");
+        node.next.set(self.head);
+        self.head = Some(NonNull::from(node));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + use<'a, T> {
+        let mut cur = self.head;
+        std::iter::from_fn(move || {
+            // Sound because of `'a`: every node we were given is borrowed
+            // for at least as long as we have been around.
+            let node: &'a Node<T> = unsafe { cur?.as_ref() };
+            cur = node.next.get();
+            Some(&node.value)
+        })
+    }
+}
+
+crate::registry::demo! {
+    "An intrusive list threaded through stack-allocated nodes",
+    tags: ["lifetimes", "collections"],
+    drops: ["il_2", "il_1"],
+}
+
+pub fn demo() {
+    let n1 = Node::new(PrintOnDrop::new("il_1", 1));
+    let n2 = Node::new(PrintOnDrop::new("il_2", 2));
+    let mut list = IntrusiveList::new();
+    list.push_front(&n1);
+    list.push_front(&n2);
+    let names: Vec<_> = list.iter().map(|p| p.name()).collect();
+    println!("IntrusiveList: {:?}", names);
+
+    // A node must outlive the list it is linked into:
+    //
+    // let mut list = IntrusiveList::new();
+    // {
+    //     let n3 = Node::new(PrintOnDrop::new("il_3", 3));
+    //     list.push_front(&n3); // won't compile: `n3` does not live long enough
+    // }
+    // for p in list.iter() { println!("{:?}", p); }
+    //
+    // and, since the list still "borrows" it, a node can't be moved or
+    // dropped while the list is in use:
+    //
+    // let mut list = IntrusiveList::new();
+    // let n4 = Node::new(PrintOnDrop::new("il_4", 4));
+    // list.push_front(&n4);
+    // drop(n4); // won't compile: cannot move out of `n4` because it is borrowed
+    // for p in list.iter() { println!("{:?}", p); }
+    //
+    // Replace `_pd` with a PhantomData<*const Node<T>> (and drop `'a`) and
+    // both of these compile, printing freed memory.
+}
@@ -0,0 +1,305 @@
+// Just enough JSON for traces and demo metadata (see trace.rs) to be
+// saved, diffed and loaded by other tools: null, booleans, unsigned
+// integers, strings, arrays and objects. No floats, since nothing here
+// has any. Objects keep their keys in order, so that what is written is
+// the same every time, and diffs well.
+
+use std::fmt::{self, Write};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        Value::Number(n)
+    }
+}
+
+// Compact, on one line: one value per line is what a trace file holds.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (k, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    // Byte offset into the input.
+    pub at: usize,
+    pub msg: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.msg, self.at)
+    }
+}
+
+pub fn parse(s: &str) -> Result<Value, ParseError> {
+    let mut p = Parser { s: s.as_bytes(), at: 0 };
+    let v = p.value()?;
+    p.skip_whitespace();
+    if p.at != p.s.len() {
+        return Err(p.error("trailing characters"));
+    }
+    Ok(v)
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &'static str) -> ParseError {
+        ParseError { at: self.at, msg }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.s.get(self.at), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.at += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.s.get(self.at).copied()
+    }
+
+    fn expect(&mut self, c: u8, msg: &'static str) -> Result<(), ParseError> {
+        if self.peek() != Some(c) {
+            return Err(self.error(msg));
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, word: &str, v: Value) -> Result<Value, ParseError> {
+        if !self.s[self.at..].starts_with(word.as_bytes()) {
+            return Err(self.error("expected a value"));
+        }
+        self.at += word.len();
+        Ok(v)
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.peek() {
+            Some(b'n') => self.keyword("null", Value::Null),
+            Some(b't') => self.keyword("true", Value::Bool(true)),
+            Some(b'f') => self.keyword("false", Value::Bool(false)),
+            Some(b'0'..=b'9') => self.number(),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => {
+                self.at += 1;
+                let mut items = Vec::new();
+                if self.peek() == Some(b']') {
+                    self.at += 1;
+                    return Ok(Value::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    match self.peek() {
+                        Some(b',') => self.at += 1,
+                        Some(b']') => break,
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+                self.at += 1;
+                Ok(Value::Array(items))
+            }
+            Some(b'{') => {
+                self.at += 1;
+                let mut fields = Vec::new();
+                if self.peek() == Some(b'}') {
+                    self.at += 1;
+                    return Ok(Value::Object(fields));
+                }
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected a key"));
+                    }
+                    let k = self.string()?;
+                    self.expect(b':', "expected `:`")?;
+                    fields.push((k, self.value()?));
+                    match self.peek() {
+                        Some(b',') => self.at += 1,
+                        Some(b'}') => break,
+                        _ => return Err(self.error("expected `,` or `}`")),
+                    }
+                }
+                self.at += 1;
+                Ok(Value::Object(fields))
+            }
+            Some(b'-') => Err(self.error("negative numbers aren't supported")),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, ParseError> {
+        let start = self.at;
+        while matches!(self.s.get(self.at), Some(b'0'..=b'9')) {
+            self.at += 1;
+        }
+        if matches!(self.s.get(self.at), Some(b'.' | b'e' | b'E')) {
+            return Err(self.error("only integers are supported"));
+        }
+        // All ASCII digits, so valid UTF-8.
+        let digits = std::str::from_utf8(&self.s[start..self.at]).unwrap();
+        digits.parse().map(Value::Number).map_err(|_| ParseError { at: start, msg: "number too large" })
+    }
+
+    // At the opening quote.
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.at += 1;
+        let mut out = String::new();
+        loop {
+            let start = self.at;
+            while !matches!(self.s.get(self.at), None | Some(b'"' | b'\\')) {
+                self.at += 1;
+            }
+            // The input was a &str, and this stops only at ASCII, so the
+            // run is valid UTF-8.
+            out.push_str(std::str::from_utf8(&self.s[start..self.at]).unwrap());
+            match self.s.get(self.at) {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.at += 1;
+                    return Ok(out);
+                }
+                Some(_) => {
+                    self.at += 1;
+                    let c = match self.s.get(self.at) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'u') => self.unicode_escape()?,
+                        _ => return Err(self.error("bad escape")),
+                    };
+                    self.at += 1;
+                    out.push(c);
+                }
+            }
+        }
+    }
+
+    // At the `u` of `\uXXXX`; leaves `at` on the escape's last digit.
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let hex = |p: &mut Self| -> Result<u32, ParseError> {
+            let digits = p.s.get(p.at + 1..p.at + 5).ok_or(p.error("bad \\u escape"))?;
+            let n = std::str::from_utf8(digits).ok().and_then(|d| u32::from_str_radix(d, 16).ok());
+            p.at += 4;
+            n.ok_or(p.error("bad \\u escape"))
+        };
+        let first = hex(self)?;
+        if !(0xd800..0xdc00).contains(&first) {
+            return char::from_u32(first).ok_or(self.error("unpaired surrogate"));
+        }
+        // A high surrogate, which must be followed by a low one.
+        if self.s.get(self.at + 1..self.at + 3) != Some(b"\\u") {
+            return Err(self.error("unpaired surrogate"));
+        }
+        self.at += 2;
+        let second = hex(self)?;
+        if !(0xdc00..0xe000).contains(&second) {
+            return Err(self.error("unpaired surrogate"));
+        }
+        Ok(char::from_u32(0x10000 + ((first - 0xd800) << 10) + (second - 0xdc00)).unwrap())
+    }
+}
@@ -0,0 +1,166 @@
+// Every pointer-holding type in the crate keeps its pointers as NonNull<T>
+// (or Option<NonNull<T>> where "no pointer" is a valid state), rather than
+// `*const T`/`*mut T`. That costs nothing, and buys two things:
+//
+// - a niche: a NonNull can't be null, so Option<MyVec<T>> can use null
+//   for None and stays the size of a MyVec<T>;
+// - covariance, where `*mut T` would have made the type invariant.
+//
+// These checks pin down the size and alignment of each type, and whether
+// it still has a niche for Option to use. They are `const` items, so a
+// type that loses its niche (or grows) by accident breaks the build, not
+// just the demo run. Those without one are so for a reason: their
+// Option<NonNull> fields have already used it up, or the pointer is
+// behind a Cell or an atomic, which hide niches. The tokens at the end
+// have no pointer to begin with. And those marked `unique niche` have one
+// as MyUnique does, which depends on the repr-* feature (see
+// my_unique.rs).
+//
+// `report` prints some of the same, for the build it's in, so that builds
+// with different representations can be compared: `cargo run -- layout-report`
+// saved from one, and `cargo run -- layout-report <saved>` from the other.
+
+use std::fs;
+use std::mem::{align_of, size_of};
+
+use crate::borrowing_future::SumFuture;
+use crate::branded::BrandedIndex;
+use crate::endian::{BigEndian, U32};
+use crate::fd_state::{Fd, Open};
+use crate::ffi_handle::{Context, Handle};
+use crate::ffi_owned::Widget;
+use crate::foreign_cstring::ForeignCString;
+use crate::intrusive_list::IntrusiveList;
+use crate::main_thread::MainThreadToken;
+use crate::my_arc::{MyArc, MyWeak};
+use crate::my_cell_box::MyCellBox;
+use crate::my_hash_map::MyHashMap;
+use crate::my_iter::{MyIter, MyIterMut};
+use crate::my_linked_list::{CursorMut, MyLinkedList};
+use crate::my_small_vec::MySmallVec;
+use crate::my_unique::{self, MyUnique};
+use crate::my_vec::{Drain, MyVec};
+use crate::once_box::{OnceBox, SyncOnceBox};
+use crate::slab::Slab;
+use crate::small_box::SmallBox;
+use crate::tagged_box::TaggedBox;
+use crate::transaction::Transaction;
+use crate::{MyBox2, MyBox3};
+
+// `check_layout!(T, words, niche)` for pointer-aligned types,
+// `check_layout!(T, size, align, niche)` for anything else.
+macro_rules! check_layout {
+    ($t:ty, $words:expr, $($niche:ident)+) => {
+        check_layout!($t, $words * size_of::<usize>(), align_of::<usize>(), $($niche)+);
+    };
+    ($t:ty, $size:expr, $align:expr, niche) => {
+        check_layout!(@ $t, $size, $align, true);
+    };
+    ($t:ty, $size:expr, $align:expr, no niche) => {
+        check_layout!(@ $t, $size, $align, false);
+    };
+    ($t:ty, $size:expr, $align:expr, unique niche) => {
+        check_layout!(@ $t, $size, $align, my_unique::NICHE);
+    };
+    (@ $t:ty, $size:expr, $align:expr, $niche:expr) => {
+        const _: () = {
+            assert!(size_of::<$t>() == $size, concat!("size of ", stringify!($t)));
+            assert!(align_of::<$t>() == $align, concat!("alignment of ", stringify!($t)));
+            assert!((size_of::<Option<$t>>() == size_of::<$t>()) == $niche, concat!("niche in ", stringify!($t)));
+        };
+    };
+}
+
+check_layout!(MyUnique<u64>, 1, unique niche);
+check_layout!(MyBox2<u64>, 1, niche);
+check_layout!(MyBox3<u64>, 1, unique niche);
+check_layout!(MyBox3<[u64]>, 2, unique niche);
+check_layout!(MyCellBox<u64>, 1, niche);
+check_layout!(TaggedBox<u64>, 1, niche);
+check_layout!(MyArc<u64>, 1, niche);
+check_layout!(MyWeak<u64>, 1, niche);
+check_layout!(OnceBox<u64>, 1, no niche);
+check_layout!(SyncOnceBox<u64>, 1, no niche);
+check_layout!(SmallBox<u64, 1>, 2, no niche);
+check_layout!(SmallBox<u64, 2>, 3, no niche);
+// A MyBox3 that grew would be caught:
+// check_layout!(MyBox3<u64>, 2, unique niche); // won't compile: E0080, evaluation panicked: size of MyBox3<u64>
+
+check_layout!(MyVec<u64>, 3, unique niche);
+check_layout!(Drain<'static, u64>, 4, niche);
+check_layout!(MyIter<'static, u64>, 2, niche);
+check_layout!(MyIterMut<'static, u64>, 2, niche);
+check_layout!(MySmallVec<u64, 2>, 5, no niche);
+check_layout!(Slab<u64>, 5, niche);
+check_layout!(MyHashMap<u64, u64>, 5, niche);
+check_layout!(MyLinkedList<u64>, 3, no niche);
+check_layout!(CursorMut<'static, u64>, 2, niche);
+check_layout!(IntrusiveList<'static, u64>, 1, no niche);
+
+check_layout!(SumFuture<'static, u64>, 4, niche);
+check_layout!(Transaction<'static>, 4, niche);
+check_layout!(Context, 1, niche);
+check_layout!(Handle<'static>, 1, niche);
+check_layout!(Widget, 1, niche);
+check_layout!(ForeignCString, 1, niche);
+
+check_layout!(MainThreadToken, 0, 1, no niche);
+check_layout!(BrandedIndex<'static>, 1, no niche);
+check_layout!(Fd<Open>, 4, 4, no niche);
+check_layout!(U32<BigEndian>, 4, 1, no niche);
+
+// What `report` shows of a type.
+fn layout<T>() -> String {
+    let niche = if size_of::<Option<T>>() == size_of::<T>() { "niche" } else { "no niche" };
+    format!("size {}, align {}, {}", size_of::<T>(), align_of::<T>(), niche)
+}
+
+fn rows() -> Vec<(&'static str, String)> {
+    let yes = |b| if b { "yes" } else { "no" }.to_string();
+    vec![
+        ("repr", my_unique::REPR.to_string()),
+        ("owns T, for dropck", yes(my_unique::OWNS)),
+        ("MyUnique<u64>", layout::<MyUnique<u64>>()),
+        ("MyBox3<u64>", layout::<MyBox3<u64>>()),
+        ("MyBox3<[u64]>", layout::<MyBox3<[u64]>>()),
+        ("MyVec<u64>", layout::<MyVec<u64>>()),
+        ("Drain<u64>", layout::<Drain<'static, u64>>()),
+        ("MySmallVec<u64, 2>", layout::<MySmallVec<u64, 2>>()),
+        ("Slab<u64>", layout::<Slab<u64>>()),
+        ("MyHashMap<u64, u64>", layout::<MyHashMap<u64, u64>>()),
+        ("Option<MyBox3<u64>>", layout::<Option<MyBox3<u64>>>()),
+        ("Option<MyVec<u64>>", layout::<Option<MyVec<u64>>>()),
+    ]
+}
+
+/// Prints MyUnique's representation in this build, and the layout of the
+/// types built on it.
+pub fn report() {
+    for (name, row) in rows() {
+        println!("{:<22}  {}", name, row);
+    }
+}
+
+/// Compares this build's `report` with one saved from another: for each
+/// row that differs, a line saying how.
+pub fn diff(path: &str) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut saved = Vec::new();
+    for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let (name, row) = line.split_once("  ").ok_or_else(|| format!("{}:{}: not a report line", path, i + 1))?;
+        saved.push((name.trim_end().to_string(), row.trim().to_string()));
+    }
+    let now = rows();
+    let mut differences = Vec::new();
+    for (name, was) in &saved {
+        match now.iter().find(|(n, _)| n == name) {
+            Some((_, row)) if row != was => differences.push(format!("{}: was {}, now {}", name, was, row)),
+            Some(_) => {}
+            None => differences.push(format!("{}: not reported this time", name)),
+        }
+    }
+    for (name, _) in now.iter().filter(|(n, _)| !saved.iter().any(|(s, _)| s == n)) {
+        differences.push(format!("{}: not saved", name));
+    }
+    Ok(differences)
+}
@@ -0,0 +1,465 @@
+// Illustration of a case where PhantomData is providing necessary ownership
+// info to rustc.
+//
+// MyBox2<T> uses just a NonNull<T> to hold the `T` it owns.
+// MyBox3<T> holds a MyUnique<T>: both a pointer AND a PhantomData<T>; the
+// latter communicates its ownership relationship with `T`.
+//
+// See dropck_pairs.rs for the relevant case, 
+// and compare it to `fn f3()` (and, for closures, `f4` and `f5`).
+// When you run the program
+// with `cargo run -- --unsound dropck`, the output will include:
+//
+// drop PrintOnDrop(mb2#2, PrintOnDrop(v2#1, 13, INVALID), Valid)
+//
+// (However, in the absence of #[may_dangle], the compiler will constrain
+// things in a manner that may indeed imply that PhantomData is unnecessary;
+// pnkfelix is not 100% sure of this claim yet, though. The `no-eyepatch`
+// feature puts it to the test: see eyepatch.rs.)
+
+#![feature(dropck_eyepatch)]
+#![cfg_attr(feature = "unsize", feature(arbitrary_self_types, coerce_unsized, dispatch_from_dyn, unsize))]
+#![cfg_attr(feature = "coroutines", feature(coroutines, coroutine_trait, stmt_expr_attributes))]
+// The demos below deliberately split `let` from initialization: declaration
+// order is what determines drop order, so we keep it explicit.
+#![allow(clippy::needless_late_init)]
+
+use std::alloc::{self, dealloc, Layout};
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+#[cfg(feature = "unsize")]
+use std::marker::Unsize;
+#[cfg(feature = "unsize")]
+use std::ops::{CoerceUnsized, DispatchFromDyn};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use my_unique::MyUnique;
+
+// What other programs get: see engine.rs.
+#[cfg(feature = "instrumented-alloc")]
+pub use alloc_tracker::report as report_allocations;
+pub use engine::{demos, run_demo, DemoResult, RunError};
+pub use json::Value;
+pub use layout_tests::{diff as layout_diff, report as layout_report};
+pub use main_thread::mark_main_thread;
+pub use observer::{subscribe, subscribe_here, DropObserver, Subscription};
+pub use recorder::Event;
+pub use trace::{load, DemoInfo, FromJson, LoadError, ToJson, Trace};
+pub use variance_tests::report as variance_report;
+
+mod aliasing;
+mod alloc_counter;
+#[cfg(feature = "instrumented-alloc")]
+mod alloc_tracker;
+mod arena;
+mod auto_trait_tests;
+#[cfg(feature = "async-demos")]
+mod async_drop_order;
+mod borrowing_future;
+mod boxed_closures;
+mod branded;
+#[cfg(feature = "c-abi")]
+mod c_abi;
+mod closure_capture;
+mod coercion_sites;
+mod const_drop;
+#[cfg(feature = "coroutines")]
+mod coroutine_drop;
+mod cross_thread_drops;
+mod decoder;
+mod derive_bounds;
+#[cfg(feature = "abort-demos")]
+mod double_panic;
+mod drop_queue;
+mod dropck_pairs;
+mod early_return;
+mod drop_strategies;
+mod encoder;
+mod engine;
+mod endian;
+mod eyepatch;
+mod family;
+pub mod fd_state;
+mod ffi_handle;
+mod ffi_owned;
+mod foreign_cstring;
+mod hash_map_drop;
+mod hrtb;
+mod impl_capture;
+mod in_place_drop;
+mod intrusive_list;
+mod into_raw;
+mod json;
+mod layout_tests;
+#[cfg(feature = "abort-demos")]
+mod lock_in_drop;
+mod main_thread;
+mod map_ref;
+mod mem_replace;
+pub mod mermaid;
+mod my_arc;
+mod my_cell_box;
+mod my_hash_map;
+mod my_iter;
+mod my_linked_list;
+mod my_small_vec;
+mod my_unique;
+mod my_vec;
+mod nll;
+pub mod normalize;
+mod observer;
+mod once_box;
+mod payload_matrix;
+mod phantom_errors;
+mod phantom_owner;
+mod pin_projection;
+mod rc_cycle;
+#[cfg(feature = "instrumented-alloc")]
+mod rc_leak;
+mod recorder;
+#[cfg(feature = "redteam")]
+mod redteam;
+pub mod registry;
+pub mod replay;
+mod scope_guard;
+mod scrutinee_temporaries;
+mod scoped_threads;
+mod send_sync;
+mod shadowing;
+mod slab;
+mod small_box;
+mod static_bound;
+mod tagged_box;
+mod temporaries;
+mod thread_local_drop;
+mod trace;
+mod traced;
+mod transaction;
+mod two_phase;
+mod uninit;
+mod unit_marker;
+#[cfg(feature = "unsize")]
+mod unsize;
+mod unused_param;
+mod unwind_safe;
+mod variance_tests;
+mod vec_drop_order;
+mod vec_dropck;
+mod visitor;
+mod weak_observers;
+mod zero_cost;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum State { INVALID, Valid }
+
+// How a PrintOnDrop shows its payload: ShowDebug, the default, with its
+// Debug impl, and Opaque, for payloads without one (closures, say), not
+// at all. A strategy is only a type, never a value, so PrintOnDrop holds
+// it as a PhantomData<fn() -> F>.
+trait Format<T> {
+    fn fmt(t: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+struct ShowDebug;
+
+impl<T: fmt::Debug> Format<T> for ShowDebug {
+    fn fmt(t: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        t.fmt(f)
+    }
+}
+
+struct Opaque;
+
+impl<T> Format<T> for Opaque {
+    fn fmt(_: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<no debug>")
+    }
+}
+
+// The id is unique across the whole program, so that output and recorded
+// events can tell apart values with the same name. Names are usually
+// literals, but may be built at run time (say, in a loop).
+struct PrintOnDrop<T, F: Format<T> = ShowDebug>(Cow<'static, str>, T, State, u64, PhantomData<fn() -> F>);
+
+impl<T: fmt::Debug> PrintOnDrop<T> {
+    fn new(name: impl Into<Cow<'static, str>>, t: T) -> Self {
+        PrintOnDrop::formatted(name, t)
+    }
+}
+
+impl<T> PrintOnDrop<T, Opaque> {
+    fn opaque(name: impl Into<Cow<'static, str>>, t: T) -> Self {
+        PrintOnDrop::formatted(name, t)
+    }
+}
+
+impl<T, F: Format<T>> PrintOnDrop<T, F> {
+    fn formatted(name: impl Into<Cow<'static, str>>, t: T) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        let p = PrintOnDrop(name.into(), t, State::Valid, NEXT_ID.fetch_add(1, Ordering::Relaxed), PhantomData);
+        observer::publish(|o| o.on_construct(&p.0, p.3));
+        p
+    }
+
+    fn name(&self) -> &str {
+        &self.0
+    }
+
+    fn id(&self) -> u64 {
+        self.3
+    }
+}
+
+impl<T, F: Format<T>> fmt::Debug for PrintOnDrop<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.2 == State::INVALID {
+            observer::publish(|o| o.on_invalid_access(&self.0, self.3));
+        }
+        write!(f, "PrintOnDrop({}#{}, ", self.0, self.3)?;
+        F::fmt(&self.1, f)?;
+        write!(f, ", {:?})", self.2)
+    }
+}
+
+// Shorthand for PrintOnDrop::new, which is most of what demos are made of:
+//
+//     print_on_drop!("sb_v", 13)        // PrintOnDrop::new("sb_v", 13)
+//     print_on_drop!(borrowing &v)      // PrintOnDrop::new("&v", &v)
+//     print_on_drop!(opaque "sb_f", f)  // PrintOnDrop::opaque("sb_f", f)
+//     print_on_drop!(v1 = 13)           // v1 = PrintOnDrop::new("v1", 13)
+//     print_on_drop!(let v1 = 13)       // let v1 = PrintOnDrop::new("v1", 13);
+//
+// The last two name the value after the variable holding it.
+macro_rules! print_on_drop {
+    (let $v:ident = $t:expr) => {
+        let $v = $crate::PrintOnDrop::new(stringify!($v), $t);
+    };
+    ($v:ident = $t:expr) => {
+        $v = $crate::PrintOnDrop::new(stringify!($v), $t)
+    };
+    (borrowing $r:expr) => {
+        $crate::PrintOnDrop::new(stringify!($r), $r)
+    };
+    (opaque $name:expr, $t:expr) => {
+        $crate::PrintOnDrop::opaque($name, $t)
+    };
+    ($name:expr, $t:expr) => {
+        $crate::PrintOnDrop::new($name, $t)
+    };
+}
+use print_on_drop;
+
+// Runs `f` without PrintOnDrop printing anything (on any thread), for
+// demos that drop far too many for the output to be of use. Drops are
+// still recorded.
+fn quiet_drops<R>(f: impl FnOnce() -> R) -> R {
+    let muted = observer::Stdout::mute(true);
+    let r = f();
+    observer::Stdout::mute(muted);
+    r
+}
+
+impl<T, F: Format<T>> Drop for PrintOnDrop<T, F> {
+    fn drop(&mut self) {
+        observer::publish(|o| o.on_drop(&self.0, self.3, self.2, self));
+        // Recording must not allocate, so an owned name is moved into the
+        // event rather than cloned (a borrowed one costs nothing to copy,
+        // and stays for a dangling reference to print; see dropck_pairs.rs).
+        let name = match &self.0 {
+            Cow::Borrowed(name) => Cow::Borrowed(*name),
+            Cow::Owned(_) => mem::take(&mut self.0),
+        };
+        recorder::record(recorder::Event::Drop(name, self.3, self.2));
+        self.2 = State::INVALID;
+    }
+}
+
+struct MyBox1<T> {
+    #[allow(dead_code)] // only held so that it gets dropped
+    v: Box<T>,
+}
+
+impl<T> MyBox1<T> {
+    fn new(t: T) -> Self {
+        MyBox1 { v: Box::new(t) }
+    }
+}
+
+struct MyBox2<T> {
+    v: NonNull<T>,
+}
+
+impl<T> MyBox2<T> {
+    fn new(t: T) -> Self {
+        // As MyBox3::new_uninit: a zero-sized `T` isn't allocated.
+        let layout = Layout::new::<T>();
+        let p = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            let Some(p) = NonNull::new(unsafe { alloc::alloc(layout) }) else {
+                alloc::handle_alloc_error(layout);
+            };
+            p.cast()
+        };
+        unsafe { ptr::write(p.as_ptr(), t) };
+        MyBox2 { v: p }
+    }
+}
+
+// Eyepatched, unless built with the `no-eyepatch` feature (see
+// eyepatch.rs), which leaves MyBox2 and MyBox3 with plain Drop impls.
+#[cfg(not(feature = "no-eyepatch"))]
+unsafe impl<#[may_dangle] T> Drop for MyBox2<T> {
+    fn drop(&mut self) {
+        unsafe { self.free() }
+    }
+}
+
+#[cfg(feature = "no-eyepatch")]
+impl<T> Drop for MyBox2<T> {
+    fn drop(&mut self) {
+        unsafe { self.free() }
+    }
+}
+
+impl<T> MyBox2<T> {
+    // What Drop does, with the eyepatch or without.
+    unsafe fn free(&mut self) {
+        unsafe {
+            // We want this to be *legal*. This destructor is not 
+            // allowed to call methods on `T` (since it may be in
+            // an invalid state), but it should be allowed to drop
+            // instances of `T` as it deconstructs itself.
+            //
+            // (Note however that the compiler has no knowledge
+            //  that `MyBox2<T>` owns an instance of `T`.)
+            //
+            // Dropped where it lies, rather than `ptr::read` out onto the
+            // stack first (see in_place_drop.rs).
+            ptr::drop_in_place(self.v.as_ptr());
+            // A zero-sized value was never allocated.
+            if mem::size_of::<T>() != 0 {
+                dealloc(self.v.as_ptr() as *mut u8, Layout::new::<T>());
+            }
+        }
+    }
+}
+
+struct MyBox3<T: ?Sized> {
+    v: MyUnique<T>,
+}
+
+impl<T> MyBox3<T> {
+    fn new(t: T) -> Self {
+        // See uninit.rs.
+        let mut b = MyBox3::new_uninit();
+        b.write(t);
+        unsafe { b.assume_init() }
+    }
+
+    fn pin(t: T) -> Pin<MyBox3<T>> {
+        // Safety: the `T` is on the heap, so moving the MyBox3 doesn't move
+        // it; Deref and DerefMut only hand out references to it; and Drop
+        // drops it where it is, before the memory is freed.
+        unsafe { Pin::new_unchecked(MyBox3::new(t)) }
+    }
+}
+
+impl<T: ?Sized> Deref for MyBox3<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.v.as_ptr() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MyBox3<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.v.as_ptr() }
+    }
+}
+
+// And that's what it takes to make MyBox3<dyn Trait> work as well.
+#[cfg(feature = "unsize")]
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<MyBox3<U>> for MyBox3<T> {}
+#[cfg(feature = "unsize")]
+impl<T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<MyBox3<U>> for MyBox3<T> {}
+
+#[cfg(not(feature = "no-eyepatch"))]
+unsafe impl<#[may_dangle] T: ?Sized> Drop for MyBox3<T> {
+    fn drop(&mut self) {
+        unsafe { self.free() }
+    }
+}
+
+#[cfg(feature = "no-eyepatch")]
+impl<T: ?Sized> Drop for MyBox3<T> {
+    fn drop(&mut self) {
+        unsafe { self.free() }
+    }
+}
+
+impl<T: ?Sized> MyBox3<T> {
+    unsafe fn free(&mut self) {
+        unsafe {
+            // The size and alignment of a `dyn Trait` or slice come from
+            // the pointer's metadata, so this works for any `T`.
+            let layout = Layout::for_value(&*self.v.as_ptr());
+            // In place: `ptr::read`ing it out first would move a value that
+            // `pin` promised never moves.
+            ptr::drop_in_place(self.v.as_ptr());
+            // A zero-sized value was never allocated.
+            if layout.size() != 0 {
+                dealloc(self.v.as_ptr() as *mut u8, layout);
+            }
+        }
+    }
+}
+
+/// MyBox1, which owns a Box<T> and so gets dropck right for free.
+pub fn f1() {
+    // `let (v, _mb1);` and `let (_mb1, v)` won't compile due to dropck
+    let v1;
+    let _mb1;
+    print_on_drop!(v1 = 13);
+    _mb1 = MyBox1::new(print_on_drop!("mb1", &v1));
+}
+
+/// MyBox3, which gets it right thanks to the PhantomData in MyUnique.
+pub fn f3() {
+    let v3;
+    let _mb3; // `let (v, mb3);` won't compile due to dropck
+    print_on_drop!(v3 = 13);
+    _mb3 = MyBox3::new(print_on_drop!("mb3", &v3));
+}
+
+/// MyBox3 holding a closure instead: the payload's drop glue is the
+/// closure's, which drops what it captured, here a PrintOnDrop borrowing
+/// `v4`. So dropck treats it like f3's.
+pub fn f4() {
+    let v4;
+    let _mb4; // `let (_mb4, v4);` won't compile due to dropck
+    print_on_drop!(v4 = 13);
+    let p = print_on_drop!("mb4", &v4);
+    _mb4 = MyBox3::new(move || p.id());
+}
+
+/// But a closure that captures only a reference has no drop glue at all:
+/// nothing of `v5` can be touched when it goes, so either order compiles,
+/// even for MyBox3, and is sound. Without the eyepatch, it doesn't
+/// compile (see eyepatch.rs).
+#[cfg(not(feature = "no-eyepatch"))]
+pub fn f5() {
+    let _mb5;
+    let v5;
+    print_on_drop!(v5 = 13);
+    let r = &v5;
+    _mb5 = MyBox3::new(move || r.id());
+}
+
@@ -0,0 +1,104 @@
+// A MutexGuard is a value like any other, held until its scope ends, and
+// dropped in reverse declaration order with the locals around it. So a
+// lock can still be held while something declared after the guard is
+// dropped, and if that something's Drop waits on another thread that
+// needs the lock (here, by joining it), neither ever gets any further.
+//
+// The deadlock is real, so the demo has it happen in a child process
+// (see `registry::run_child`), which is killed when it doesn't finish in
+// time; what it printed before hanging shows where it got stuck. The two
+// fixes, declaring the guard last or dropping it by hand before the end
+// of the scope, run in this process.
+//
+// Behind the `abort-demos` feature, with double_panic.rs, since it also
+// needs this program's binary to run.
+
+use std::env;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::registry::{run_child, ChildExit};
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// Set in the environment of the child, which deadlocks.
+const CHILD: &str = "LOCK_IN_DROP_CHILD";
+
+static COUNT: Mutex<u32> = Mutex::new(0);
+
+// Joins its thread when dropped, as a scoped thread's handle would.
+struct JoinOnDrop(Option<JoinHandle<()>>);
+
+impl fmt::Debug for JoinOnDrop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JoinOnDrop")
+    }
+}
+
+impl Drop for JoinOnDrop {
+    fn drop(&mut self) {
+        if let Some(handle) = self.0.take() {
+            handle.join().unwrap();
+        }
+    }
+}
+
+// A thread that wants the lock, to count once it has it.
+fn worker() -> PrintOnDrop<JoinOnDrop> {
+    print_on_drop!("ld_worker", JoinOnDrop(Some(thread::spawn(|| *COUNT.lock().unwrap() += 1))))
+}
+
+fn guard() -> PrintOnDrop<MutexGuard<'static, u32>> {
+    print_on_drop!("ld_guard", COUNT.lock().unwrap())
+}
+
+// The guard declared first is dropped last: the worker is joined while
+// the lock is still held, and waits for it forever.
+fn deadlock() -> ! {
+    {
+        let _guard = guard();
+        let _worker = worker();
+    }
+    unreachable!("ld: the worker was joined");
+}
+
+// Declared last, the guard is dropped first, and the worker gets the lock.
+fn reordered() {
+    let _worker = worker();
+    let _guard = guard();
+}
+
+// Or keep the order, and let go of the lock before the end of the scope.
+fn dropped_early() {
+    let guard = guard();
+    let _worker = worker();
+    drop(guard);
+}
+
+crate::registry::demo! {
+    "A lock held while a later local's Drop needs it: deadlock, watched from a child process",
+    tags: ["threads", "drop-order"],
+}
+
+pub fn demo() {
+    if env::var_os(CHILD).is_some() {
+        deadlock();
+    }
+
+    recorder::take();
+    let before = *COUNT.lock().unwrap();
+    reordered();
+    assert_eq!(recorder::take_drops(), ["ld_guard", "ld_worker"]);
+    dropped_early();
+    assert_eq!(recorder::take_drops(), ["ld_guard", "ld_worker"]);
+    assert_eq!(*COUNT.lock().unwrap(), before + 2);
+
+    let child = run_child("lock_in_drop", &[(CHILD, "1")], Duration::from_secs(2)).expect("couldn't run a child process");
+    println!("lock_in_drop: child {}", child.exit);
+    assert!(matches!(child.exit, ChildExit::TimedOut));
+    // It got as far as dropping the worker, which it never finished; the
+    // guard was never dropped, so the lock was never let go.
+    assert!(child.stdout.contains("ld_worker#"));
+    assert!(!child.stdout.contains("ld_guard#"));
+}
@@ -1,149 +1,172 @@
-// Illustration of a case where PhantomData is providing necessary ownership
-// info to rustc.
-//
-// MyBox2<T> uses just a `*const T` to hold the `T` it owns.
-// MyBox3<T> has both a `*const T` AND a PhantomData<T>; the latter communicates
-// its ownership relationship with `T`.
-//
-// Skim down to `fn f2()` to see the relevant case, 
-// and compare it to `fn f3()`. When you run the program,
-// the output will include:
-//
-// drop PrintOnDrop(mb2b, PrintOnDrop("v2b", 13, INVALID), Valid)
-//
-// (However, in the absence of #[may_dangle], the compiler will constrain
-// things in a manner that may indeed imply that PhantomData is unnecessary;
-// pnkfelix is not 100% sure of this claim yet, though.)
-
-#![feature(dropck_eyepatch)]
-
-use std::alloc::{self, dealloc, Layout};
-use std::fmt;
-use std::marker::PhantomData;
-use std::mem;
-use std::ptr;
-
-#[derive(Copy, Clone, Debug)]
-enum State { INVALID, Valid }
-
-#[derive(Debug)]
-struct PrintOnDrop<T: fmt::Debug>(&'static str, T, State);
-
-impl<T: fmt::Debug> PrintOnDrop<T> {
-    fn new(name: &'static str, t: T) -> Self {
-        PrintOnDrop(name, t, State::Valid)
+// The whyPhantomData program: the demos, run from the command line. What
+// they are, and the types they use, are in the library (lib.rs).
+
+use std::process;
+
+use why_phantom_data::mermaid::Mermaid;
+use why_phantom_data::normalize;
+use why_phantom_data::replay::{self, Recording};
+use why_phantom_data::{f1, f3, f4, registry, subscribe};
+
+static RECORDING: Recording = Recording::new();
+static MERMAID: Mermaid = Mermaid::new();
+
+// Removes `flag` and the argument after it from `args`, returning the
+// latter.
+fn flag_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let i = args.iter().position(|a| a == flag)?;
+    if i + 1 == args.len() {
+        eprintln!("{} needs a file name", flag);
+        process::exit(2);
     }
+    args.remove(i);
+    Some(args.remove(i))
 }
 
-impl<T: fmt::Debug> Drop for PrintOnDrop<T> {
-    fn drop(&mut self) {
-        println!("drop PrintOnDrop({}, {:?}, {:?})",
-                 self.0,
-                 self.1,
-                 self.2);
-        self.2 = State::INVALID;
+// `cargo run` runs every (sound) demo; `cargo run -- <name or tag>...`
+// only the ones matching any of the arguments; `cargo run -- --list`
+// lists them, and `cargo run -- --explain <name or tag>...` explains
+// them. Unsound demos only run if named, or with `--unsound`, and each
+// in a child process; `--isolate` runs every demo like that (though what
+// a child did isn't recorded, or drawn, by this one). `--normalize` makes
+// what they print, and what `--record` saves, the same from run to run
+// (see normalize.rs).
+// `--record <file>` saves what they did, `--replay <file>` shows it again,
+// and `--replay <file> --diff` runs them again to compare (see replay.rs).
+// `--mermaid <file>` draws what they did, as markdown (see mermaid.rs).
+// `cargo run -- variance-report` runs nothing, and prints the variance
+// of each generic type instead (see variance_tests.rs), and `cargo run
+// -- layout-report` the layout of those built on MyUnique, in whichever
+// representation the repr-* features picked; `layout-report <file>`
+// compares it with one saved from another build (see layout_tests.rs).
+// What the representations do differently, `--record` from one build
+// and `--replay --diff` from another shows.
+fn main() {
+    // Safety: this is the main thread.
+    unsafe { why_phantom_data::mark_main_thread() };
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a == "variance-report") {
+        why_phantom_data::variance_report();
+        return;
     }
-}
-
-struct MyBox1<T> {
-    v: Box<T>,
-}
-
-impl<T> MyBox1<T> {
-    fn new(t: T) -> Self {
-        MyBox1 { v: Box::new(t) }
+    if args.first().is_some_and(|a| a == "layout-report") {
+        let Some(path) = args.get(1) else {
+            why_phantom_data::layout_report();
+            return;
+        };
+        let differences = why_phantom_data::layout_diff(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(2);
+        });
+        for d in &differences {
+            println!("diff: {}", d);
+        }
+        println!("diff: {} rows differ", differences.len());
+        if !differences.is_empty() {
+            process::exit(1);
+        }
+        return;
     }
-}
-
-struct MyBox2<T> {
-    v: *const T,
-}
-
-impl<T> MyBox2<T> {
-    fn new(t: T) -> Self {
-        unsafe {
-            let p = alloc::alloc(Layout::new::<T>());
-            let p = p as *mut T;
-            ptr::write(p, t);
-            MyBox2 { v: p }
+    let unsound = args.iter().any(|a| a == "--unsound");
+    args.retain(|a| a != "--unsound");
+    let record = flag_value(&mut args, "--record");
+    let replay = flag_value(&mut args, "--replay");
+    let mermaid = flag_value(&mut args, "--mermaid");
+    let isolate = args.iter().any(|a| a == "--isolate");
+    args.retain(|a| a != "--isolate");
+    let normalize = args.iter().any(|a| a == "--normalize");
+    args.retain(|a| a != "--normalize");
+    normalize::normalize_output(normalize);
+    let diff = args.iter().any(|a| a == "--diff");
+    args.retain(|a| a != "--diff");
+    if diff && replay.is_none() {
+        eprintln!("--diff needs a --replay <file> to compare with");
+        process::exit(2);
+    }
+    let saved = replay.map(|path| {
+        replay::load_file(&path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(2);
+        })
+    });
+    if let (Some(saved), false) = (&saved, diff) {
+        replay::render(saved);
+        return;
+    }
+    let demos = registry::all();
+    if args.iter().any(|a| a == "--list") {
+        for d in &demos {
+            println!("{:<18} {:<40} {}", d.name(), d.tags.join(","), d.description);
         }
+        return;
     }
-}
-
-unsafe impl<#[may_dangle] T> Drop for MyBox2<T> {
-    fn drop(&mut self) {
-        unsafe {
-            // We want this to be *legal*. This destructor is not 
-            // allowed to call methods on `T` (since it may be in
-            // an invalid state), but it should be allowed to drop
-            // instances of `T` as it deconstructs itself.
-            //
-            // (Note however that the compiler has no knowledge
-            //  that `MyBox2<T>` owns an instance of `T`.)
-            ptr::read(self.v);
-            dealloc(self.v as *mut u8, Layout::new::<T>());
+    // Diffing, the demos to run are the ones that were saved.
+    if let Some(saved) = &saved {
+        args.extend(saved.iter().map(|t| t.demo.clone()));
+    }
+    let explain = args.first().is_some_and(|a| a == "--explain");
+    let args = &args[explain as usize..];
+    let selected: Vec<_> = demos
+        .iter()
+        .filter(|d| args.is_empty() || args.iter().any(|a| d.matches(a)))
+        .filter(|d| !d.unsound || unsound || explain || args.iter().any(|a| a == d.name()))
+        .collect();
+    if selected.is_empty() {
+        eprintln!("no demo or tag matches {:?}; try --list", args);
+        process::exit(2);
+    }
+    if explain {
+        for d in selected {
+            d.explain();
+            println!();
         }
+        return;
     }
-}
-
-struct MyBox3<T> {
-    v: *const T,
-    _pd: PhantomData<T>,
-}
 
-impl<T> MyBox3<T> {
-    fn new(t: T) -> Self {
-        unsafe {
-            let p = alloc::alloc(Layout::new::<T>());
-            let p = p as *mut T;
-            ptr::write(p, t);
-            MyBox3 { v: p, _pd: Default::default() }
+    if !registry::in_child() {
+        f1();
+        f3();
+        f4();
+        #[cfg(not(feature = "no-eyepatch"))]
+        why_phantom_data::f5();
+    }
+    let _recording = (record.is_some() || saved.is_some()).then(|| subscribe(&RECORDING));
+    let _drawing = mermaid.is_some().then(|| subscribe(&MERMAID));
+    for d in selected {
+        RECORDING.begin(d.name());
+        MERMAID.begin(d.name());
+        if isolate && !registry::in_child() {
+            d.run_in_child();
+        } else {
+            d.run();
         }
     }
-}
-
-unsafe impl<#[may_dangle] T> Drop for MyBox3<T> {
-    fn drop(&mut self) {
-        unsafe {
-            ptr::read(self.v);
-            dealloc(self.v as *mut u8, Layout::new::<T>());
+    if let Some(path) = record {
+        if normalize {
+            RECORDING.normalize();
+        }
+        if let Err(e) = RECORDING.save(&path) {
+            eprintln!("{}", e);
+            process::exit(2);
         }
     }
-}
-
-fn f1() {
-    // `let (v, _mb1);` and `let (_mb1, v)` won't compile due to dropck
-    let v1;
-    let _mb1;
-    v1 = PrintOnDrop::new("v1", 13);
-    _mb1 = MyBox1::new(PrintOnDrop::new("mb1", &v1));
-}
-
-fn f2() {
-    {
-        let (v2a, _mb2a); // Sound, but not distinguished from below by rustc!
-        v2a = PrintOnDrop::new("v2a", 13);
-        _mb2a = MyBox2::new(PrintOnDrop::new("mb2a", &v2a));
+    if let Some(path) = mermaid {
+        if let Err(e) = MERMAID.save(&path) {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
     }
 
-    {
-        let (_mb2b, v2b); // Unsound!
-        v2b = PrintOnDrop::new("v2b", 13);
-        _mb2b = MyBox2::new(PrintOnDrop::new("mb2b", &v2b));
-        // namely, v2b dropped before _mb2b, but latter contains
-        // value that attempts to access v2b when being dropped.
+    if let Some(saved) = saved {
+        let differences = RECORDING.with_traces(|now| replay::diff(&saved, now));
+        for d in &differences {
+            println!("diff: {}", d);
+        }
+        println!("diff: {} of {} demos differ", differences.len(), saved.len());
+        if !differences.is_empty() {
+            process::exit(1);
+        }
     }
+    #[cfg(feature = "instrumented-alloc")]
+    why_phantom_data::report_allocations();
 }
-
-fn f3() {
-    let v3;
-    let _mb3; // `let (v, mb3);` won't compile due to dropck
-    v3 = PrintOnDrop::new("v3", 13);
-    _mb3 = MyBox3::new(PrintOnDrop::new("mb3", &v3));
-}
-
-fn main() {
-    f1();
-    f2();
-    f3();
-}
\ No newline at end of file
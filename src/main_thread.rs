@@ -0,0 +1,106 @@
+// Some resources (GUI toolkits, certain OS APIs) may only be touched from
+// the main thread. Rather than check at every call, we can require proof:
+// a MainThreadToken, which can only be obtained on the main thread and,
+// being !Send and !Sync, can never get to any other thread, nor be
+// borrowed from one.
+//
+// Which thread is the main one, std doesn't say (its name, "main", is
+// one any thread can be given), so the program says, once, as it starts:
+// `mark_main_thread`, which is unsafe because nothing can check it. The
+// command line does that first thing, as does py/ when loaded on
+// Python's main thread; a program embedding the demos should too. And
+// only one token exists at a time: a second `acquire` fails until the
+// first token is dropped.
+//
+// All of that comes from one phantom field. Raw pointers are neither Send
+// nor Sync, so PhantomData<*const ()> removes both auto traits without
+// adding any data: the token is a zero-sized proof.
+
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::thread::{self, ThreadId};
+
+use crate::auto_trait_tests::{assert_not_impl_send, assert_not_impl_sync};
+
+pub struct MainThreadToken {
+    _not_send: PhantomData<*const ()>,
+}
+
+static MAIN: OnceLock<ThreadId> = OnceLock::new();
+
+// Whether a token exists. Only the main thread ever sets or clears it.
+static HELD: AtomicBool = AtomicBool::new(false);
+
+/// Records the calling thread as the main one. Only the first call counts.
+///
+/// # Safety
+///
+/// It must be called from the program's main thread.
+pub unsafe fn mark_main_thread() {
+    MAIN.get_or_init(|| thread::current().id());
+}
+
+impl MainThreadToken {
+    /// Succeeds on the thread `mark_main_thread` was called on, unless a
+    /// token exists already.
+    pub fn acquire() -> Option<Self> {
+        let on_main = MAIN.get() == Some(&thread::current().id());
+        if on_main && !HELD.swap(true, Ordering::Relaxed) {
+            Some(MainThreadToken { _not_send: PhantomData })
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for MainThreadToken {
+    fn drop(&mut self) {
+        HELD.store(false, Ordering::Relaxed);
+    }
+}
+
+pub struct Window {
+    title: &'static str,
+}
+
+impl Window {
+    // Main-thread-only APIs just ask for a `&MainThreadToken`.
+    pub fn show(&self, _: &MainThreadToken) {
+        println!("Window: showing {:?}", self.title);
+    }
+}
+
+crate::registry::demo!("MainThreadToken: proof of being on the main thread", tags: ["auto-traits"]);
+
+pub fn demo() {
+    assert!(thread::spawn(|| MainThreadToken::acquire().is_none()).join().unwrap());
+    assert_eq!(size_of::<MainThreadToken>(), 0);
+    assert_not_impl_send!(MainThreadToken);
+    assert_not_impl_sync!(MainThreadToken);
+
+    // Run by a program that hasn't marked its main thread, or from
+    // another thread, there is no token to be had.
+    let Some(token) = MainThreadToken::acquire() else {
+        println!("Window: not on the main thread, so not shown");
+        return;
+    };
+    assert!(MainThreadToken::acquire().is_none(), "acquired twice");
+
+    let w = Window { title: "phantom" };
+    w.show(&token);
+
+    // Once it's dropped, there can be another.
+    drop(token);
+    let token = MainThreadToken::acquire().expect("not acquired again");
+    w.show(&token);
+
+    // The token can't reach another thread, so neither can a call to
+    // `show`:
+    //
+    // thread::scope(|s| {
+    //     s.spawn(|| w.show(&token)); // won't compile: `*const ()` cannot be shared between threads safely
+    // });
+    //
+    // thread::spawn(move || drop(token)); // won't compile: `*const ()` cannot be sent between threads safely
+}
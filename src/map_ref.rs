@@ -0,0 +1,118 @@
+// MapRef<'a, T, U, F>: a `map` over a MyVec's elements that keeps a raw
+// pointer to the vector and an index into it, as a cursor would, rather
+// than an iterator. Two PhantomDatas, one for each thing the fields don't
+// say on their own:
+//
+//   - PhantomData<&'a T>: the pointer has no lifetime, but what MapRef
+//     hands to `f` (and so, often, what it yields) are `&'a T`s borrowed
+//     from the vector, which must stay put, unchanged, for `'a`. This is
+//     MyIter's phantom (see my_iter.rs), for the same reason.
+//   - PhantomData<fn() -> U>: `U` only appears in the bound on `F`, and
+//     a type parameter that no field uses is an error (E0392). MapRef
+//     never holds a `U`, so the phantom is of a function returning one:
+//     covariant in `U`, and no part of MapRef's drop glue.
+//
+// Nor can `U` simply be left off MapRef and given to the impls alone: an
+// impl's type parameters must each appear in what it is for (E0207).
+
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use lesson::lesson;
+
+use crate::my_vec::MyVec;
+use crate::{print_on_drop, recorder};
+
+pub struct MapRef<'a, T, U, F> {
+    parent: NonNull<MyVec<T>>,
+    index: usize,
+    f: F,
+    _borrows: PhantomData<&'a T>,
+    _yields: PhantomData<fn() -> U>,
+}
+
+impl<'a, T, U, F: FnMut(&'a T) -> U> MapRef<'a, T, U, F> {
+    pub fn new(parent: &'a MyVec<T>, f: F) -> Self {
+        MapRef { parent: NonNull::from(parent), index: 0, f, _borrows: PhantomData, _yields: PhantomData }
+    }
+}
+
+impl<'a, T, U, F: FnMut(&'a T) -> U> Iterator for MapRef<'a, T, U, F> {
+    type Item = U;
+
+    fn next(&mut self) -> Option<U> {
+        // Safety: `new` borrowed the vector for `'a`, so it is still there,
+        // and hasn't been changed.
+        let parent: &'a MyVec<T> = unsafe { self.parent.as_ref() };
+        if self.index == parent.len() {
+            return None;
+        }
+        let t = unsafe { &*parent.as_ptr().add(self.index) };
+        self.index += 1;
+        Some((self.f)(t))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = unsafe { self.parent.as_ref() }.len() - self.index;
+        (left, Some(left))
+    }
+}
+
+// Covariant in `'a`, like the `&'a T` it stands for.
+fn shorten<'short, 'long: 'short, T, U, F>(m: MapRef<'long, T, U, F>) -> MapRef<'short, T, U, F> {
+    m
+}
+
+// Each phantom is needed:
+//
+// struct NoBorrow<'a, T, U, F> { parent: NonNull<MyVec<T>>, f: F, _yields: PhantomData<fn() -> U> } // won't compile: E0392, lifetime parameter `'a` is never used
+// struct NoOutput<'a, T, U, F> { parent: NonNull<MyVec<T>>, f: F, _borrows: PhantomData<&'a T> } // won't compile: E0392, type parameter `U` is never used
+
+/// A MapRef borrows the vector it maps over for `'a`, though all it keeps
+/// of it is a raw pointer: PhantomData<&'a T> makes that borrow real to
+/// the compiler, so the vector can't be dropped, or pushed to, while a
+/// MapRef (or anything it yielded that borrows the elements) is around.
+/// The items may outlive the MapRef, though: they borrow the vector, not
+/// the adapter.
+///
+/// Its output type `U` only appears in `F: FnMut(&'a T) -> U`, so it is
+/// given a PhantomData<fn() -> U> to be a parameter at all: a phantom that
+/// owns nothing, and is covariant.
+#[lesson(
+    title = "MapRef: a borrow-aware adapter with two PhantomDatas",
+    tags = ["lifetimes", "variance", "collections"],
+    notes = [
+        "Using the names after the vector is dropped won't compile: E0505",
+        "Pushing to the vector while a MapRef is live won't compile: E0502",
+        "Without either PhantomData, MapRef won't compile: E0392",
+    ],
+)]
+pub fn demo() {
+    let v = print_on_drop!("mapref_v", 13);
+    let mut vec = MyVec::new();
+    for name in ["mapref_0", "mapref_1", "mapref_2"] {
+        vec.push(print_on_drop!(name, &v));
+    }
+
+    // The names borrow the elements, and outlive the adapter.
+    let names: Vec<&str>;
+    {
+        let m = shorten(MapRef::new(&vec, |p| p.name()));
+        assert_eq!(m.size_hint(), (3, Some(3)));
+        names = m.collect();
+    }
+    assert_eq!(names, ["mapref_0", "mapref_1", "mapref_2"]);
+    //
+    // drop(vec); // won't compile: E0505, cannot move out of `vec` because it is borrowed
+    // println!("{:?}", names);
+
+    let mut ids = MapRef::new(&vec, |p| p.id());
+    let first = ids.next().unwrap();
+    //
+    // vec.push(print_on_drop!("mapref_3", &v)); // won't compile: E0502, cannot borrow `vec` as mutable because it is also borrowed as immutable
+    assert_eq!(ids.map(|id| id - first).collect::<Vec<_>>(), [1, 2]);
+
+    recorder::take();
+    drop(vec);
+    assert_eq!(recorder::take_drops(), ["mapref_0", "mapref_1", "mapref_2"]);
+}
@@ -0,0 +1,97 @@
+// mem::swap, mem::replace and mem::take: moving values in and out of a
+// place without dropping anything. None of them runs a destructor; they
+// only change which value is where, and so which is dropped when the
+// place is. That is also how they change drop order without anyone
+// noticing: swap two variables and they're dropped the other way round.
+
+use std::mem;
+
+use lesson::lesson;
+
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder, MyBox3, PrintOnDrop};
+
+fn swapped() {
+    let mut a = print_on_drop!("mr_a", 1);
+    let mut b = print_on_drop!("mr_b", 2);
+    mem::swap(&mut a, &mut b);
+    mark("mr_swapped");
+    // `b` is dropped first, as ever, but now holds "mr_a".
+}
+
+fn replaced() {
+    let mut slot = print_on_drop!("mr_old", 1);
+    let old = mem::replace(&mut slot, print_on_drop!("mr_new", 2));
+    mark("mr_replaced");
+    assert_eq!(old.name(), "mr_old");
+
+    // Assigning drops what was there on the spot; so does replacing it
+    // and throwing away what comes back.
+    let mut other = print_on_drop!("mr_assigned", 3);
+    assert_eq!(other.name(), "mr_assigned");
+    other = print_on_drop!("mr_reassigned", 4);
+    assert_eq!(other.name(), "mr_reassigned");
+    let _ = mem::replace(&mut other, print_on_drop!("mr_last", 5));
+    mark("mr_discarded");
+    // Then `other`, `old` and `slot`, in reverse order of declaration.
+}
+
+fn taken() {
+    let mut slot = Some(print_on_drop!("mr_taken", 1));
+    let taken = mem::take(&mut slot);
+    mark("mr_took");
+    assert!(slot.is_none());
+    drop(taken);
+    // `slot` is None, and drops nothing.
+    mark("mr_end");
+}
+
+fn in_a_box() {
+    // Through DerefMut, these work on what a MyBox3 holds, leaving the box
+    // where it is.
+    let mut b = MyBox3::new(print_on_drop!("mr_boxed", 1));
+    let old = mem::replace(&mut *b, print_on_drop!("mr_into_box", 2));
+    drop(b);
+    mark("mr_box_dropped");
+    drop(old);
+
+    let mut b = MyBox3::new(Some(print_on_drop!("mr_boxed_taken", 3)));
+    let taken: Option<PrintOnDrop<i32>> = mem::take(&mut *b);
+    drop(b);
+    mark("mr_box_dropped");
+    drop(taken);
+}
+
+/// `mem::swap`, `mem::replace` and `mem::take` move values between places
+/// without dropping any of them: each value is dropped later, with
+/// whichever place (or variable) it ended up in. Swapping two variables
+/// swaps the order their values are dropped in; `mem::replace` hands back
+/// the old value, to be dropped when whoever takes it is done with it;
+/// `mem::take` leaves a Default (None, for an Option) behind, which drops
+/// nothing.
+///
+/// Assignment is different: `x = y` drops the old `x` then and there, and
+/// so does `let _ = mem::replace(&mut x, y)`, since `_` binds nothing. All
+/// of them work through DerefMut as well, on the contents of a MyBox3,
+/// which is then dropped with whatever it holds by then.
+#[lesson(
+    title = "mem::swap, replace and take: moving values around, and their drops with them",
+    tags = ["drop-order"],
+)]
+pub fn demo() {
+    recorder::take();
+    swapped();
+    assert_eq!(recorder::take_drops(), ["mr_swapped", "mr_a", "mr_b"]);
+    replaced();
+    assert_eq!(
+        recorder::take_drops(),
+        ["mr_replaced", "mr_assigned", "mr_reassigned", "mr_discarded", "mr_last", "mr_old", "mr_new"]
+    );
+    taken();
+    assert_eq!(recorder::take_drops(), ["mr_took", "mr_taken", "mr_end"]);
+    in_a_box();
+    assert_eq!(
+        recorder::take_drops(),
+        ["mr_into_box", "mr_box_dropped", "mr_boxed", "mr_box_dropped", "mr_boxed_taken"]
+    );
+}
@@ -0,0 +1,231 @@
+// Demos as Mermaid sequence diagrams: `cargo run -- --mermaid drops.md`
+// writes a markdown file with a diagram per demo run, which GitHub (and
+// most slide tools) draw in place. Each PrintOnDrop is a participant,
+// created when it is made and destroyed when it is dropped, with the
+// demo itself as the one making and dropping them; scope markers (see
+// scope_guard.rs) are boxes around what happened inside them, and
+// guards, DropTrace drops and uses after drop are notes.
+//
+// Like a Recording (see replay.rs), a Mermaid hears every thread, adds
+// what it hears to the diagram of whichever demo `begin` was last told
+// of, and doesn't count its own allocations.
+
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::fs;
+use std::mem;
+use std::sync::Mutex;
+
+use crate::alloc_counter::{allocations, uncounted};
+use crate::observer::{self, DropObserver};
+use crate::recorder::{self, Event};
+use crate::scope_guard::{defer, scope};
+use crate::{print_on_drop, MyBox3, State};
+
+#[derive(Clone, Debug)]
+enum Step {
+    New(String, u64),
+    Recorded(Event),
+    UsedAfterDrop(String, u64),
+}
+
+struct Diagram {
+    demo: String,
+    steps: Vec<Step>,
+}
+
+pub struct Mermaid(Mutex<Vec<Diagram>>);
+
+impl Mermaid {
+    pub const fn new() -> Self {
+        Mermaid(Mutex::new(Vec::new()))
+    }
+
+    /// What is heard from now on goes in `demo`'s diagram.
+    pub fn begin(&self, demo: &str) {
+        uncounted(|| self.0.lock().unwrap().push(Diagram { demo: demo.to_string(), steps: Vec::new() }));
+    }
+
+    fn push(&self, step: impl FnOnce() -> Step) {
+        uncounted(|| {
+            if let Some(d) = self.0.lock().unwrap().last_mut() {
+                d.steps.push(step());
+            }
+        });
+    }
+
+    /// Every diagram so far, as a markdown document.
+    pub fn markdown(&self) -> String {
+        uncounted(|| {
+            let mut md = String::from("# Drop order, demo by demo\n");
+            for d in self.0.lock().unwrap().iter() {
+                write!(md, "\n## {}\n\n```mermaid\n{}```\n", d.demo, sequence_diagram(&d.demo, &d.steps)).unwrap();
+            }
+            md
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        uncounted(|| fs::write(path, self.markdown()).map_err(|e| format!("{}: {}", path, e)))
+    }
+}
+
+impl Default for Mermaid {
+    fn default() -> Self {
+        Mermaid::new()
+    }
+}
+
+impl DropObserver for Mermaid {
+    fn on_construct(&self, name: &str, id: u64) {
+        self.push(|| Step::New(name.to_string(), id));
+    }
+
+    fn on_invalid_access(&self, name: &str, id: u64) {
+        self.push(|| Step::UsedAfterDrop(name.to_string(), id));
+    }
+
+    fn on_record(&self, e: &Event) {
+        self.push(|| Step::Recorded(e.clone()));
+    }
+}
+
+impl Drop for Mermaid {
+    fn drop(&mut self) {
+        // Allocated uncounted, so freed uncounted.
+        let diagrams = mem::take(self.0.get_mut().unwrap());
+        uncounted(|| drop(diagrams));
+    }
+}
+
+// Names may be anything; in a diagram, `#` and `;` are Mermaid's own.
+fn label(name: &str) -> String {
+    let mut s = String::new();
+    for c in name.chars() {
+        match c {
+            '#' => s.push_str("#35;"),
+            ';' => s.push_str("#59;"),
+            c => s.push(c),
+        }
+    }
+    s
+}
+
+fn sequence_diagram(demo: &str, steps: &[Step]) -> String {
+    // Values dropped without having been seen made are declared up front.
+    let mut head = vec!["sequenceDiagram".to_string(), format!("    participant demo as {}", label(demo))];
+    let mut body = Vec::new();
+    // Participants still alive, by name and id (ids aren't always unique:
+    // a `const`'s values all have 0; see const_drop.rs).
+    let mut live: Vec<((&str, u64), String)> = Vec::new();
+    let mut next = 1;
+    let mut depth = 1;
+    let mut fresh = || {
+        next += 1;
+        format!("v{}", next - 1)
+    };
+    for step in steps {
+        let indent = "    ".repeat(depth);
+        match step {
+            Step::New(name, id) => {
+                let alias = fresh();
+                body.push(format!("{}create participant {} as {}", indent, alias, label(&format!("{}#{}", name, id))));
+                body.push(format!("{}demo->>{}: new", indent, alias));
+                live.push(((name, *id), alias));
+            }
+            Step::Recorded(Event::Drop(name, id, state)) => {
+                let alias = match live.iter().position(|(key, _)| *key == (&**name, *id)) {
+                    Some(i) => live.remove(i).1,
+                    None => {
+                        let alias = fresh();
+                        head.push(format!("    participant {} as {}", alias, label(&format!("{}#{}", name, id))));
+                        alias
+                    }
+                };
+                let invalid = if *state == State::INVALID { ", INVALID" } else { "" };
+                body.push(format!("{}destroy {}", indent, alias));
+                body.push(format!("{}demo-x{}: drop{}", indent, alias, invalid));
+            }
+            Step::Recorded(Event::Guard(name)) => body.push(format!("{}Note over demo: guard {}", indent, label(name))),
+            Step::Recorded(Event::Traced(name)) => body.push(format!("{}Note over demo: drop {}", indent, label(name))),
+            Step::Recorded(Event::Enter(name)) => {
+                body.push(format!("{}rect rgba(128, 128, 128, 0.1)", indent));
+                body.push(format!("{}    Note over demo: {}", indent, label(name)));
+                depth += 1;
+            }
+            // A scope entered before the diagram began has no box to end.
+            Step::Recorded(Event::Exit(_)) if depth == 1 => {}
+            Step::Recorded(Event::Exit(_)) => {
+                depth -= 1;
+                body.push(format!("{}end", "    ".repeat(depth)));
+            }
+            Step::UsedAfterDrop(name, id) => {
+                body.push(format!("{}Note over demo: {} used after it was dropped", indent, label(&format!("{}#{}", name, id))));
+            }
+        }
+    }
+    // And one entered but not left, likewise.
+    while depth > 1 {
+        depth -= 1;
+        body.push(format!("{}end", "    ".repeat(depth)));
+    }
+    head.extend(body);
+    head.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+fn scenario() -> (u64, u64) {
+    scope!("mm_scope");
+    let v = print_on_drop!("mm_v", 1);
+    let b = MyBox3::new(print_on_drop!("mm_b", &v));
+    defer!("mm_guard", {});
+    (v.id(), b.id())
+}
+
+crate::registry::demo!("Demos drawn as Mermaid sequence diagrams", tags: ["drop-order"]);
+
+pub fn demo() {
+    let mermaid = Mermaid::new();
+    let before = allocations();
+    let (v, b) = observer::with_observer(&mermaid, || {
+        mermaid.begin("mermaid");
+        scenario()
+    });
+    // The box's, and none of the Mermaid's.
+    assert_eq!(allocations() - before, 1);
+    recorder::take();
+
+    let md = mermaid.markdown();
+    println!("{}", md);
+    let expected = format!(
+        "# Drop order, demo by demo
+
+## mermaid
+
+```mermaid
+sequenceDiagram
+    participant demo as mermaid
+    rect rgba(128, 128, 128, 0.1)
+        Note over demo: mm_scope
+        create participant v1 as mm_v#35;{v}
+        demo->>v1: new
+        create participant v2 as mm_b#35;{b}
+        demo->>v2: new
+        Note over demo: guard mm_guard
+        destroy v2
+        demo-xv2: drop
+        destroy v1
+        demo-xv1: drop
+    end
+```
+"
+    );
+    assert_eq!(md, expected);
+
+    // Dropped without being seen made, a value is declared up front; and
+    // names are escaped.
+    let steps = [Step::Recorded(Event::Drop(Cow::Borrowed("mm_#;"), 0, State::Valid))];
+    assert_eq!(
+        sequence_diagram("mm", &steps),
+        "sequenceDiagram\n    participant demo as mm\n    participant v1 as mm_#35;#59;#35;0\n    destroy v1\n    demo-xv1: drop\n"
+    );
+}
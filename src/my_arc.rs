@@ -0,0 +1,434 @@
+// MyArc<T>: a minimal atomically reference-counted pointer, and MyWeak<T>,
+// its weak counterpart. The counts and the value share one heap
+// allocation, reached through a NonNull, so (as for MyBox3) a
+// PhantomData<ArcInner<T>> is what tells dropck that dropping a MyArc may
+// drop a `T`.
+//
+// Destruction is in two phases, as std's is. When the last MyArc goes,
+// the value is dropped, in place; the allocation stays, for the MyWeaks
+// still pointing at it to find the strong count zero, and upgrade to
+// nothing. It's freed when the last of them goes. To keep that to one
+// count, the MyArcs between them hold one weak reference, let go of by
+// whichever drops the value, so the weak count is the number of MyWeaks,
+// plus one while there are MyArcs. A MyWeak never drops a `T`, and has an
+// eyepatched Drop and no PhantomData<T>, which dropck takes at its word:
+// a MyWeak may outlive what its `T` borrows.
+//
+// The orderings are std's: a clone only needs the count to go up
+// (Relaxed), but the handle that takes it to zero must see every other
+// handle's last use of the value before dropping it (Release on each
+// decrement, an Acquire fence before the drop). Freeing the allocation
+// goes the same way.
+//
+// try_unwrap and get_mut, as std's, only work for the one handle left:
+// the first takes the value out, so that whoever gets it drops it, rather
+// than the last handle's Drop; the second lends it, mutably, and also
+// needs there to be no MyWeaks, which could otherwise upgrade meanwhile.
+// To check both counts at once, get_mut locks the weak one, by setting it
+// to usize::MAX, which `downgrade` waits out: otherwise another handle
+// could make a MyWeak and go away between the two loads.
+// make_mut always lends it, copy-on-write: a shared value is cloned
+// first, into a new allocation of this handle's own; one that only MyWeaks
+// point at as well is moved there instead, leaving them nothing to
+// upgrade to.
+//
+// The demo measures what that costs: N threads cloning and dropping
+// handles to one value, all hammering the same counter.
+
+use std::marker::PhantomData;
+use std::alloc::{dealloc, Layout};
+use std::hint;
+use std::mem;
+use std::ops::Deref;
+use std::process;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::alloc_counter::live_bytes;
+use crate::temporaries::mark;
+use crate::{quiet_drops, recorder, PrintOnDrop};
+
+struct ArcInner<T> {
+    strong: AtomicUsize,
+    weak: AtomicUsize,
+    // Dropped by hand, by the last MyArc, before the allocation is freed
+    // (without dropping it again). A plain `T`, not a ManuallyDrop<T>:
+    // that has no drop glue, and a PhantomData<ArcInner<T>> would no
+    // longer tell dropck anything.
+    data: T,
+}
+
+pub struct MyArc<T> {
+    ptr: NonNull<ArcInner<T>>,
+    _pd: PhantomData<ArcInner<T>>,
+}
+
+pub struct MyWeak<T> {
+    ptr: NonNull<ArcInner<T>>,
+}
+
+// Handles on different threads share the `T` (so it must be Sync), and
+// whichever is dropped last drops it, on its own thread (so it must be
+// Send). A MyWeak can be upgraded to a MyArc, so it needs the same.
+unsafe impl<T: Send + Sync> Send for MyArc<T> {}
+unsafe impl<T: Send + Sync> Sync for MyArc<T> {}
+unsafe impl<T: Send + Sync> Send for MyWeak<T> {}
+unsafe impl<T: Send + Sync> Sync for MyWeak<T> {}
+
+// Clones of both kinds: like std, give up well before a count could
+// overflow (leaking clones in a loop could otherwise make one wrap, and
+// lead to a use after free).
+fn increment(count: &AtomicUsize) {
+    if count.fetch_add(1, Ordering::Relaxed) > isize::MAX as usize {
+        process::abort();
+    }
+}
+
+impl<T> MyArc<T> {
+    pub fn new(data: T) -> Self {
+        let inner = Box::new(ArcInner {
+            strong: AtomicUsize::new(1),
+            weak: AtomicUsize::new(1),
+            data,
+        });
+        MyArc { ptr: NonNull::from(Box::leak(inner)), _pd: PhantomData }
+    }
+
+    pub fn count(this: &Self) -> usize {
+        this.inner().strong.load(Ordering::Relaxed)
+    }
+
+    /// How many MyWeaks there are (not counting the MyArcs' own).
+    pub fn weak_count(this: &Self) -> usize {
+        match this.inner().weak.load(Ordering::Relaxed) {
+            // Locked by get_mut, which only does so when there are none.
+            usize::MAX => 0,
+            n => n - 1,
+        }
+    }
+
+    pub fn downgrade(this: &Self) -> MyWeak<T> {
+        let weak = &this.inner().weak;
+        let mut n = weak.load(Ordering::Relaxed);
+        loop {
+            // Locked by get_mut, for as long as it takes to load one count.
+            if n == usize::MAX {
+                hint::spin_loop();
+                n = weak.load(Ordering::Relaxed);
+                continue;
+            }
+            if n > isize::MAX as usize {
+                process::abort();
+            }
+            // Acquire, to follow on from get_mut's unlocking.
+            match weak.compare_exchange_weak(n, n + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return MyWeak { ptr: this.ptr },
+                Err(now) => n = now,
+            }
+        }
+    }
+
+    /// The value, if this is the only handle to it; otherwise the handle,
+    /// unchanged.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        // Acquire, as for the last drop: whoever had the other handles is
+        // done with the value, and we're about to move it. Once the count
+        // is zero, no MyWeak can upgrade.
+        if this.inner().strong.compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(this);
+        }
+        let ptr = this.ptr;
+        mem::forget(this);
+        let data = unsafe { ptr::read(&(*ptr.as_ptr()).data) };
+        // The MyArcs' weak reference, which the last MyWeak (or this) frees.
+        drop(MyWeak { ptr });
+        Ok(data)
+    }
+
+    /// The value, mutably, if this is the only handle to it, and there
+    /// are no MyWeaks. Holding it `&mut` means no clone or MyWeak can be
+    /// made meanwhile.
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        // As std's: with the weak count locked, no MyWeak can be made, so
+        // a strong count of one means this handle is the only way in.
+        let inner = this.inner();
+        if inner.weak.compare_exchange(1, usize::MAX, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return None;
+        }
+        let unique = inner.strong.load(Ordering::Acquire) == 1;
+        inner.weak.store(1, Ordering::Release);
+        unique.then(|| unsafe { &mut (*this.ptr.as_ptr()).data })
+    }
+
+    /// The value, mutably: cloned first unless this is the only MyArc,
+    /// and moved out from under any MyWeaks.
+    pub fn make_mut(this: &mut Self) -> &mut T
+    where
+        T: Clone,
+    {
+        // Taking the count to zero, as try_unwrap does, stops MyWeaks from
+        // upgrading while we look at theirs.
+        let inner = this.inner();
+        if inner.strong.compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            // Shared: this handle lets go of the value, for a copy.
+            *this = MyArc::new(T::clone(this));
+        } else if inner.weak.load(Ordering::Relaxed) != 1 {
+            // Only MyWeaks besides: they find the count at zero, and keep
+            // the old allocation until they go.
+            let old = this.ptr;
+            let data = unsafe { ptr::read(&(*old.as_ptr()).data) };
+            unsafe { ptr::write(this, MyArc::new(data)) };
+            drop(MyWeak { ptr: old });
+        } else {
+            // Ours alone after all.
+            inner.strong.store(1, Ordering::Release);
+        }
+        unsafe { &mut (*this.ptr.as_ptr()).data }
+    }
+
+    fn inner(&self) -> &ArcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> Clone for MyArc<T> {
+    fn clone(&self) -> Self {
+        increment(&self.inner().strong);
+        MyArc { ptr: self.ptr, _pd: PhantomData }
+    }
+}
+
+impl<T> Deref for MyArc<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.inner().data
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for MyArc<T> {
+    fn drop(&mut self) {
+        if self.inner().strong.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        atomic::fence(Ordering::Acquire);
+        unsafe { ptr::drop_in_place(&mut (*self.ptr.as_ptr()).data) };
+        drop(MyWeak { ptr: self.ptr });
+    }
+}
+
+impl<T> MyWeak<T> {
+    /// A MyArc to the value, if it hasn't been dropped.
+    pub fn upgrade(&self) -> Option<MyArc<T>> {
+        // Only up from a count that isn't zero: at zero, the value is
+        // gone, or going, and mustn't be brought back.
+        let strong = &self.inner().strong;
+        let mut n = strong.load(Ordering::Relaxed);
+        loop {
+            if n == 0 {
+                return None;
+            }
+            if n > isize::MAX as usize {
+                process::abort();
+            }
+            match strong.compare_exchange_weak(n, n + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return Some(MyArc { ptr: self.ptr, _pd: PhantomData }),
+                Err(now) => n = now,
+            }
+        }
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.inner().strong.load(Ordering::Relaxed)
+    }
+
+    // Only the counts: the value may be gone.
+    fn inner(&self) -> &ArcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> Clone for MyWeak<T> {
+    fn clone(&self) -> Self {
+        increment(&self.inner().weak);
+        MyWeak { ptr: self.ptr }
+    }
+}
+
+// Frees the allocation, not the value, which is gone by then, so it
+// never touches a `T`.
+unsafe impl<#[may_dangle] T> Drop for MyWeak<T> {
+    fn drop(&mut self) {
+        if self.inner().weak.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        atomic::fence(Ordering::Acquire);
+        unsafe { dealloc(self.ptr.as_ptr().cast(), Layout::new::<ArcInner<T>>()) };
+    }
+}
+
+// A copy is named for what it was copied from, so that it shows which
+// one is dropped when.
+#[derive(Debug)]
+struct Doc(PrintOnDrop<u32>);
+
+impl Clone for Doc {
+    fn clone(&self) -> Self {
+        Doc(PrintOnDrop::new(format!("{}_copy", self.0.name()), self.0 .1))
+    }
+}
+
+// Counts how often it is dropped, on whatever thread that happens.
+#[derive(Debug)]
+struct Payload<'a> {
+    drops: &'a AtomicUsize,
+}
+
+impl Drop for Payload<'_> {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// Each of `threads` threads clones and drops `rounds` handles to one
+// value, starting from a handle of its own.
+fn clone_and_drop(threads: usize, rounds: usize) -> Duration {
+    let drops = AtomicUsize::new(0);
+    let arc = MyArc::new(PrintOnDrop::new("arc_bench", Payload { drops: &drops }));
+    let start = Instant::now();
+    thread::scope(|s| {
+        for _ in 0..threads {
+            let mine = arc.clone();
+            s.spawn(move || {
+                for _ in 0..rounds {
+                    drop(mine.clone());
+                }
+            });
+        }
+        // Let one of the threads drop the value.
+        drop(arc);
+    });
+    let elapsed = start.elapsed();
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+    elapsed
+}
+
+crate::registry::demo! {
+    "MyArc: an atomically reference-counted pointer",
+    tags: ["dropck", "threads", "bench"],
+}
+
+pub fn demo() {
+    let v = PrintOnDrop::new("arc_v", 13);
+    let a = MyArc::new(PrintOnDrop::new("arc_a", &v));
+    let b = a.clone();
+    println!("MyArc: {:?} count={}", *b, MyArc::count(&a));
+    drop(a);
+    assert_eq!(MyArc::count(&b), 1);
+    drop(b);
+
+    // Shared, neither works, and the handle is given back; the last one
+    // dropped drops the value, as ever.
+    recorder::take();
+    let mut a = MyArc::new(PrintOnDrop::new("arc_shared", 1));
+    let b = a.clone();
+    assert!(MyArc::get_mut(&mut a).is_none());
+    let a = MyArc::try_unwrap(a).unwrap_err();
+    drop(a);
+    mark("arc_a_dropped");
+    drop(b);
+    assert_eq!(recorder::take_drops(), ["arc_a_dropped", "arc_shared"]);
+
+    // Unique, get_mut lends the value, and try_unwrap hands it over, to
+    // be dropped by whoever has it, after the allocation is gone.
+    let mut a = MyArc::new(PrintOnDrop::new("arc_unique", 1));
+    MyArc::get_mut(&mut a).unwrap().1 += 1;
+    let v = MyArc::try_unwrap(a).ok().expect("the only handle");
+    mark("arc_unwrapped");
+    assert_eq!(v.1, 2);
+    drop(v);
+    assert_eq!(recorder::take_drops(), ["arc_unwrapped", "arc_unique"]);
+
+    // make_mut: alone, the value is lent as it is...
+    let mut a = MyArc::new(Doc(PrintOnDrop::new("arc_doc", 1)));
+    let before = a.ptr;
+    MyArc::make_mut(&mut a).0 .1 += 1;
+    assert_eq!(a.ptr, before);
+    // ...shared, it's cloned, and each handle drops its own...
+    let b = a.clone();
+    MyArc::make_mut(&mut a).0 .1 += 1;
+    assert_eq!((MyArc::count(&a), MyArc::count(&b)), (1, 1));
+    assert_eq!((a.0.name(), a.0 .1, b.0.name(), b.0 .1), ("arc_doc_copy", 3, "arc_doc", 2));
+    drop(b);
+    mark("arc_original_dropped");
+    drop(a);
+    assert_eq!(recorder::take_drops(), ["arc_doc", "arc_original_dropped", "arc_doc_copy"]);
+    // ...and with a MyWeak about, it's moved, not cloned.
+    let mut a = MyArc::new(Doc(PrintOnDrop::new("arc_moved", 1)));
+    let w = MyArc::downgrade(&a);
+    MyArc::make_mut(&mut a).0 .1 += 1;
+    assert!(w.upgrade().is_none());
+    assert_eq!((MyArc::weak_count(&a), a.0.name(), a.0 .1), (0, "arc_moved", 2));
+    drop(w);
+    assert!(recorder::take_drops().is_empty());
+    drop(a);
+    assert_eq!(recorder::take_drops(), ["arc_moved"]);
+
+    // A MyWeak doesn't keep the value, only the allocation: the last
+    // MyArc drops the value, and the last MyWeak frees the memory.
+    let before = live_bytes();
+    let mut a = MyArc::new(PrintOnDrop::new("arc_watched", 1));
+    let w = MyArc::downgrade(&a);
+    let w2 = w.clone();
+    assert_eq!((MyArc::count(&a), MyArc::weak_count(&a)), (1, 2));
+    assert!(MyArc::get_mut(&mut a).is_none());
+    let b = w.upgrade().unwrap();
+    assert_eq!(b.1, 1);
+    drop(b);
+    drop(a);
+    mark("arc_strong_gone");
+    assert_eq!(w.strong_count(), 0);
+    assert!(w.upgrade().is_none());
+    assert!(live_bytes() > before);
+    drop(w);
+    drop(w2);
+    assert_eq!(live_bytes(), before);
+    assert_eq!(recorder::take_drops(), ["arc_watched", "arc_strong_gone"]);
+
+    // Dropping a MyWeak never touches the value, so it may be dropped
+    // after what the value borrowed (if it isn't used after, which could
+    // upgrade it); a MyArc may not:
+    {
+        let _w;
+        {
+            let v = PrintOnDrop::new("arc_borrowed", 2);
+            let a = MyArc::new(PrintOnDrop::new("arc_borrowing", &v));
+            _w = MyArc::downgrade(&a);
+        }
+        mark("arc_weak_left");
+    }
+    assert_eq!(recorder::take_drops(), ["arc_borrowing", "arc_borrowed", "arc_weak_left"]);
+    //
+    // let b;
+    // {
+    //     let v = PrintOnDrop::new("arc_borrowed", 2);
+    //     let a = MyArc::new(PrintOnDrop::new("arc_borrowing", &v)); // won't compile: E0597, `v` does not live long enough
+    //     b = a.clone();
+    // }
+
+    const ROUNDS: usize = 100_000;
+    quiet_drops(|| {
+        for threads in [1, 2, 4, 8] {
+            let t = clone_and_drop(threads, ROUNDS);
+            println!("MyArc: {} threads x {} clone/drop in {:?} ({:?} per pair)",
+                     threads, ROUNDS, t, t / (threads * ROUNDS) as u32);
+        }
+    });
+
+    // As for MyBox3:
+    //
+    // let (_arc, v2); // won't compile: `v2` does not live long enough
+    // v2 = PrintOnDrop::new("v2", 13);
+    // _arc = MyArc::new(PrintOnDrop::new("arc_bad", &v2));
+}
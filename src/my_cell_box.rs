@@ -0,0 +1,129 @@
+// MyCellBox<T>: MyBox3, but with interior mutability: `set` replaces the
+// boxed value through a shared `&self`.
+//
+// That one change makes covariance unsound. MyBox3<&'static str> may
+// safely be used as a MyBox3<&'short str>, because nothing can be written
+// into it through a shared reference. If the same were true of MyCellBox,
+// we could view a MyCellBox<&'static str> as a MyCellBox<&'short str>,
+// `set` a short-lived string into it, and later read it back out as
+// `&'static str`, long after it was freed.
+//
+// So MyCellBox must be invariant in `T`, which it gets for free by storing
+// an UnsafeCell<T> (invariant, like every type that allows mutation
+// through `&`) and saying so in the PhantomData, just like Cell and
+// RefCell. PhantomData<UnsafeCell<T>> also makes it !Sync, as it should be.
+
+use std::alloc::{self, dealloc, Layout};
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ptr::{self, NonNull};
+
+use crate::variance_tests::assert_invariant;
+use crate::{recorder, MyBox3, PrintOnDrop};
+
+pub struct MyCellBox<T> {
+    v: NonNull<UnsafeCell<T>>,
+    _pd: PhantomData<UnsafeCell<T>>,
+}
+
+impl<T> MyCellBox<T> {
+    pub fn new(t: T) -> Self {
+        let layout = Layout::new::<UnsafeCell<T>>();
+        let p = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            let Some(p) = NonNull::new(unsafe { alloc::alloc(layout) }) else {
+                alloc::handle_alloc_error(layout);
+            };
+            p.cast()
+        };
+        unsafe { ptr::write(p.as_ptr(), UnsafeCell::new(t)) };
+        MyCellBox { v: p, _pd: PhantomData }
+    }
+
+    pub fn set(&self, t: T) {
+        // Safety: MyCellBox is !Sync and never hands out references to its
+        // contents, so nobody else is looking at them right now. The old
+        // value is swapped out before it is dropped, as Cell::set does: its
+        // Drop may reach this same box and `set` it again, which would
+        // drop it a second time if it were still in the slot.
+        let old = unsafe { ptr::replace(self.v.as_ref().get(), t) };
+        drop(old);
+    }
+
+    pub fn get(&self) -> T
+    where
+        T: Copy,
+    {
+        unsafe { *self.v.as_ref().get() }
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for MyCellBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.v.as_ptr());
+            // A zero-sized value was never allocated.
+            if size_of::<T>() != 0 {
+                dealloc(self.v.as_ptr() as *mut u8, Layout::new::<UnsafeCell<T>>());
+            }
+        }
+    }
+}
+
+// Compiles: MyBox3 is covariant in `T`.
+fn shorten_box3<'short>(b: MyBox3<&'static str>) -> MyBox3<&'short str> {
+    b
+}
+
+// Doesn't: MyCellBox is invariant in `T`.
+assert_invariant!(for<'a> MyCellBox<&'a str>);
+//
+// fn shorten_cell_box<'short>(b: MyCellBox<&'static str>) -> MyCellBox<&'short str> {
+//     b // won't compile: lifetime may not live long enough
+// }
+
+crate::registry::demo! {
+    "MyCellBox: a MyBox3 that can be set through `&self`",
+    tags: ["variance", "auto-traits"],
+}
+
+pub fn demo() {
+    let _b3 = shorten_box3(MyBox3::new("box3"));
+
+    let b = MyCellBox::new(1);
+    b.set(2);
+    println!("MyCellBox: {}", b.get());
+    assert_eq!(b.get(), 2);
+
+    // `set` drops the old value; Drop, the last one.
+    recorder::take();
+    let p = MyCellBox::new(PrintOnDrop::new("cellbox_1", 1));
+    p.set(PrintOnDrop::new("cellbox_2", 2));
+    assert_eq!(recorder::take_drops(), ["cellbox_1"]);
+    drop(p);
+    assert_eq!(recorder::take_drops(), ["cellbox_2"]);
+
+    // A zero-sized value is never allocated.
+    let unit = MyCellBox::new(());
+    unit.set(());
+    assert_eq!(unit.get(), ());
+
+    let s: MyCellBox<&'static str> = MyCellBox::new("static");
+    s.set("also static");
+    println!("MyCellBox: {}", s.get());
+
+    // The exploit that invariance rules out:
+    //
+    // let s: MyCellBox<&'static str> = MyCellBox::new("static");
+    // {
+    //     let short = String::from("short");
+    //     let view: &MyCellBox<&str> = &s;
+    //     view.set(&short); // won't compile: E0597, `short` does not live long enough
+    // }
+    // let dangling: &'static str = s.get();
+    //
+    // With `v: NonNull<T>` and PhantomData<T> (covariant, as in MyBox3), it
+    // compiles, and `dangling` points at freed memory.
+}
@@ -0,0 +1,202 @@
+// A (very) simplified open-addressing hash map, built the same way as
+// MyBox3: raw storage for the entries, a `#[may_dangle]` destructor, and a
+// PhantomData telling rustc that the map owns its keys and values.
+//
+// std's HashMap (hashbrown) does exactly this, and for the same reason as
+// Vec and Box: without the eyepatch, a map holding plain references could
+// never be declared in the same `let` as the data it borrows; without the
+// PhantomData, a map holding values whose *destructors* use those
+// references would be accepted in the unsound order (see `fn f2()`).
+
+use std::alloc::{self, dealloc, Layout};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::{self, NonNull};
+
+use crate::{recorder, PrintOnDrop};
+
+pub struct MyHashMap<K, V> {
+    // `cap` slots of `(K, V)`; slot `i` is initialized iff `occupied[i]`.
+    entries: NonNull<(K, V)>,
+    occupied: Vec<bool>,
+    len: usize,
+    _pd: PhantomData<(K, V)>,
+}
+
+impl<K: Hash + Eq, V> MyHashMap<K, V> {
+    pub fn new() -> Self {
+        MyHashMap::with_capacity(8)
+    }
+
+    fn with_capacity(cap: usize) -> Self {
+        let layout = Layout::array::<(K, V)>(cap).unwrap();
+        // Zero-sized entries take no memory, and allocating none is UB; a
+        // dangling pointer is as good, as in MyVec.
+        let entries = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            let Some(p) = NonNull::new(unsafe { alloc::alloc(layout) } as *mut (K, V)) else {
+                alloc::handle_alloc_error(layout);
+            };
+            p
+        };
+        MyHashMap { entries, occupied: vec![false; cap], len: 0, _pd: PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    fn cap(&self) -> usize {
+        self.occupied.len()
+    }
+
+    // Linear probing: the first slot that is either empty or holds `k`.
+    fn probe(&self, k: &K) -> usize {
+        let mut h = DefaultHasher::new();
+        k.hash(&mut h);
+        let mut i = h.finish() as usize % self.cap();
+        while self.occupied[i] && unsafe { &(*self.entries.as_ptr().add(i)).0 } != k {
+            i = (i + 1) % self.cap();
+        }
+        i
+    }
+
+    /// Inserts `v` under `k`, returning the previous value (if any).
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if (self.len + 1) * 4 > self.cap() * 3 {
+            self.grow();
+        }
+        let i = self.probe(&k);
+        unsafe {
+            let slot = self.entries.as_ptr().add(i);
+            if self.occupied[i] {
+                return Some(ptr::replace(&mut (*slot).1, v));
+            }
+            ptr::write(slot, (k, v));
+        }
+        self.occupied[i] = true;
+        self.len += 1;
+        None
+    }
+
+    pub fn get(&self, k: &K) -> Option<&V> {
+        let i = self.probe(k);
+        if self.occupied[i] {
+            unsafe { Some(&(*self.entries.as_ptr().add(i)).1) }
+        } else {
+            None
+        }
+    }
+
+    fn grow(&mut self) {
+        let mut bigger = MyHashMap::with_capacity(self.cap() * 2);
+        for i in 0..self.cap() {
+            if self.occupied[i] {
+                // Move the entry out; clearing the flag first means our own
+                // destructor won't drop it a second time.
+                self.occupied[i] = false;
+                let (k, v) = unsafe { ptr::read(self.entries.as_ptr().add(i)) };
+                bigger.insert(k, v);
+            }
+        }
+        self.len = 0;
+        std::mem::swap(self, &mut bigger);
+    }
+}
+
+unsafe impl<#[may_dangle] K, #[may_dangle] V> Drop for MyHashMap<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            // As in MyBox3: we may not *use* a `K` or `V` here, only drop
+            // them. The PhantomData is what makes dropck account for that.
+            // If neither has drop glue, there is nothing to drop at all.
+            let slots = if mem::needs_drop::<(K, V)>() { self.occupied.len() } else { 0 };
+            for i in 0..slots {
+                if self.occupied[i] {
+                    ptr::drop_in_place(self.entries.as_ptr().add(i));
+                }
+            }
+            let layout = Layout::array::<(K, V)>(self.occupied.len()).unwrap();
+            if layout.size() != 0 {
+                dealloc(self.entries.as_ptr() as *mut u8, layout);
+            }
+        }
+    }
+}
+
+fn f_std() {
+    // Values are plain references, which have no drop glue. This only
+    // compiles because HashMap's Drop is eyepatched: `v` and `map` share a
+    // scope, so `v` does not strictly outlive `map`.
+    let (v, mut map);
+    v = PrintOnDrop::new("hm_std", 13);
+    map = HashMap::new();
+    map.insert("k", &v);
+    println!("std HashMap: {:?}", map.get("k"));
+
+    // But once the values' destructors look through the references, dropck
+    // steps in, because HashMap owns (a PhantomData of) its values:
+    //
+    // let (_map, v); // won't compile: `v` does not live long enough
+    // v = PrintOnDrop::new("hm_std2", 13);
+    // _map = HashMap::from([("k", PrintOnDrop::new("in_map", &v))]);
+}
+
+fn f_mine() {
+    // Same as above, accepted for the same reason.
+    let (v, mut map);
+    v = PrintOnDrop::new("hm_mine", 13);
+    map = MyHashMap::new();
+    map.insert("k", &v);
+    println!("MyHashMap: {:?}", map.get(&"k"));
+
+    // Grow past the initial capacity; each displaced value is dropped
+    // exactly once, by `insert`'s caller or by our destructor.
+    let v2 = PrintOnDrop::new("hm_many", 14);
+    let mut many = MyHashMap::new();
+    let mut ids = Vec::new();
+    recorder::take();
+    for i in 0..10 {
+        let entry = PrintOnDrop::new(format!("hm_entry_{}", i), &v2);
+        ids.push(entry.id());
+        many.insert(i, entry);
+    }
+    let replacement = PrintOnDrop::new("hm_replaced", &v2);
+    let replaced = mem::replace(&mut ids[3], replacement.id());
+    drop(many.insert(3, replacement));
+    assert_eq!(recorder::take_drop_ids(), [replaced]);
+    println!("MyHashMap len = {}", many.len());
+    drop(many);
+    let mut dropped = recorder::take_drop_ids();
+    dropped.sort();
+    ids.sort();
+    assert_eq!(dropped, ids);
+
+    // Zero-sized entries, which are never allocated (nor freed).
+    let mut units = MyHashMap::new();
+    assert_eq!(units.insert((), ()), None);
+    assert_eq!(units.insert((), ()), Some(()));
+    assert_eq!((units.get(&()), units.len()), (Some(&()), 1));
+
+    // Without `_pd`, rustc would accept this and `hm_bad` would print a
+    // dropped `v3` on its way out (cf. MyBox2 in `fn f2()`):
+    //
+    // let (mut _map, v3); // won't compile: `v3` does not live long enough
+    // v3 = PrintOnDrop::new("v3", 13);
+    // _map = MyHashMap::new();
+    // _map.insert(0, PrintOnDrop::new("hm_bad", &v3));
+}
+
+crate::registry::demo! {
+    "MyHashMap: an eyepatched open-addressing map that owns its keys and values",
+    tags: ["dropck", "collections"],
+}
+
+pub fn demo() {
+    f_std();
+    f_mine();
+}
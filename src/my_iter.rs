@@ -0,0 +1,219 @@
+// MyIter<'a, T>: a shared iterator over contiguous storage, built like
+// core::slice::Iter from a pair of raw pointers, `ptr..end`; and its
+// counterpart MyIterMut<'a, T>, built like core::slice::IterMut.
+//
+// Raw pointers carry no lifetime, so without help the iterator could
+// outlive the container it points into. PhantomData<&'a T> says "this
+// behaves like a `&'a T`": the iterator borrows the container for `'a`,
+// and, like `&'a T`, it is covariant in both `'a` and `T`, and is
+// Send/Sync exactly when `&'a T` is.
+//
+// MyIterMut has the very same fields, and differs only in its phantom:
+// PhantomData<&'a mut T> makes it borrow the container *mutably*, and,
+// like `&'a mut T`, makes it invariant in `T`.
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::{self, NonNull};
+
+use crate::my_vec::MyVec;
+use crate::print_on_drop;
+
+pub struct MyIter<'a, T> {
+    ptr: NonNull<T>,
+    // For zero-sized `T` there is nothing to point at, so (like core) we
+    // use the address difference `end - ptr` as the remaining count.
+    end: *const T,
+    _pd: PhantomData<&'a T>,
+}
+
+impl<'a, T> MyIter<'a, T> {
+    /// Safety: `ptr` must point to `len` initialized values that stay
+    /// valid, and are not mutated, for `'a`.
+    pub unsafe fn new(ptr: NonNull<T>, len: usize) -> Self {
+        let end = if mem::size_of::<T>() == 0 {
+            ptr.as_ptr().wrapping_byte_add(len)
+        } else {
+            unsafe { ptr.as_ptr().add(len) }
+        };
+        MyIter { ptr, end, _pd: PhantomData }
+    }
+
+    fn remaining(&self) -> usize {
+        if mem::size_of::<T>() == 0 {
+            self.end.addr() - self.ptr.as_ptr().addr()
+        } else {
+            unsafe { self.end.offset_from(self.ptr.as_ptr()) as usize }
+        }
+    }
+}
+
+impl<'a, T> Iterator for MyIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if ptr::eq(self.ptr.as_ptr(), self.end) {
+            return None;
+        }
+        unsafe {
+            if mem::size_of::<T>() == 0 {
+                self.end = self.end.wrapping_byte_sub(1);
+                Some(NonNull::dangling().as_ref())
+            } else {
+                let t = self.ptr.as_ref();
+                self.ptr = self.ptr.add(1);
+                Some(t)
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+impl<T> DoubleEndedIterator for MyIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if ptr::eq(self.ptr.as_ptr(), self.end) {
+            return None;
+        }
+        unsafe {
+            if mem::size_of::<T>() == 0 {
+                self.end = self.end.wrapping_byte_sub(1);
+                Some(NonNull::dangling().as_ref())
+            } else {
+                self.end = self.end.sub(1);
+                Some(&*self.end)
+            }
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for MyIter<'_, T> {}
+
+pub struct MyIterMut<'a, T> {
+    ptr: NonNull<T>,
+    end: *mut T,
+    _pd: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> MyIterMut<'a, T> {
+    /// Safety: `ptr` must point to `len` initialized values that stay
+    /// valid, and are not otherwise accessed, for `'a`.
+    pub unsafe fn new(ptr: NonNull<T>, len: usize) -> Self {
+        let end = if mem::size_of::<T>() == 0 {
+            ptr.as_ptr().wrapping_byte_add(len)
+        } else {
+            unsafe { ptr.as_ptr().add(len) }
+        };
+        MyIterMut { ptr, end, _pd: PhantomData }
+    }
+}
+
+impl<'a, T> Iterator for MyIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    // Each element is handed out at most once, and MyIterMut is not
+    // Clone, so no two of the `&'a mut T`s we return alias.
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.ptr.as_ptr() == self.end {
+            return None;
+        }
+        unsafe {
+            if mem::size_of::<T>() == 0 {
+                self.end = self.end.wrapping_byte_sub(1);
+                Some(NonNull::dangling().as_mut())
+            } else {
+                let t = self.ptr.as_mut();
+                self.ptr = self.ptr.add(1);
+                Some(t)
+            }
+        }
+    }
+}
+
+// These only compile because MyIter is covariant in `'a` and in `T`, as
+// `&'a T` is. Swap the PhantomData for PhantomData<&'a mut T> (invariant
+// in `T`) and the second is rejected; for PhantomData<*mut T>, both.
+fn shorten_borrow<'short, 'long: 'short, T>(it: MyIter<'long, T>) -> MyIter<'short, T> {
+    it
+}
+
+fn shorten_payload<'short, 'long: 'short>(
+    it: MyIter<'short, &'long str>,
+) -> MyIter<'short, &'short str> {
+    it
+}
+
+// Unlike `shorten_payload`, this is rejected: if it were allowed, we could
+// store a `&'short str` through the returned iterator into a vector that
+// everyone else still believes holds `&'long str`s.
+//
+// fn shorten_payload_mut<'short, 'long: 'short>(
+//     it: MyIterMut<'short, &'long str>,
+// ) -> MyIterMut<'short, &'short str> {
+//     it // won't compile: lifetime may not live long enough
+// }
+
+crate::registry::demo! {
+    "MyIter and MyIterMut: slice iterators from a pair of raw pointers",
+    tags: ["variance", "lifetimes"],
+}
+
+pub fn demo() {
+    let v = print_on_drop!("iter_v", 13);
+    let mut vec = MyVec::new();
+    vec.push(print_on_drop!("iter_0", &v));
+    vec.push(print_on_drop!("iter_1", &v));
+    vec.push(print_on_drop!("iter_2", &v));
+
+    let mut it = vec.iter();
+    println!("MyIter: len={} first={:?} last={:?}",
+             it.len(),
+             it.next().map(|p| p.name()),
+             it.next_back().map(|p| p.name()));
+    let rest: Vec<_> = shorten_borrow(it).map(|p| p.name()).collect();
+    println!("MyIter: rest={:?}", rest);
+
+    let mut zsts = MyVec::new();
+    zsts.push(());
+    zsts.push(());
+    let s = String::from("local");
+    let mut strs = MyVec::new();
+    strs.push("static");
+    strs.push(&s[..]);
+    let n = shorten_payload(strs.iter()).count();
+    assert_eq!((n, zsts.iter().count(), zsts.iter().rev().count()), (2, 2, 2));
+
+    let mut it = strs.iter_mut();
+    let (first, second) = (it.next().unwrap(), it.next().unwrap());
+    std::mem::swap(first, second);
+    println!("MyIterMut: {:?}", strs.iter().collect::<Vec<_>>());
+
+    // The iterator can't outlive the vector it borrows:
+    //
+    // let _it;
+    // {
+    //     let mut short = MyVec::new();
+    //     short.push(1);
+    //     _it = short.iter(); // won't compile: `short` does not live long enough
+    // }
+    // println!("{:?}", _it.count());
+    //
+    // nor can the vector be modified while it is being iterated:
+    //
+    // let mut vec = MyVec::new();
+    // vec.push(1);
+    // for _ in vec.iter() {
+    //     vec.push(2); // won't compile: cannot borrow `vec` as mutable
+    // }
+    //
+    // and two mutable iterators can't hand out references to the same
+    // elements at the same time:
+    //
+    // let mut vec = MyVec::new();
+    // vec.push(1);
+    // let a = vec.iter_mut().next().unwrap();
+    // let b = vec.iter_mut().next().unwrap(); // won't compile: cannot borrow `vec` as mutable more than once
+    // *a += *b;
+}
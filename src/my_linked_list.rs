@@ -0,0 +1,192 @@
+// MyLinkedList<T>: an owning doubly-linked list in the style of std's
+// LinkedList, where each node is a separate heap allocation reached only
+// through raw pointers. As with MyBox3, the PhantomData<Box<Node<T>>> is
+// what tells dropck that dropping the list drops `T`s.
+//
+// CursorMut<'a, T> points at one node and can insert and remove around
+// it. It reaches the list through a raw pointer too, so on its own it
+// would not keep the list borrowed at all; its PhantomData<&'a mut
+// MyLinkedList<T>> makes it hold an exclusive borrow, which is what keeps
+// anyone from iterating over, or pushing onto, the list while a cursor
+// is rearranging it.
+
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use crate::PrintOnDrop;
+
+// A missing `prev`/`next` (or `head`/`tail`) marks an end of the list.
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+pub struct MyLinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _pd: PhantomData<Box<Node<T>>>,
+}
+
+impl<T> MyLinkedList<T> {
+    pub fn new() -> Self {
+        MyLinkedList { head: None, tail: None, len: 0, _pd: PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let node = NonNull::from(Box::leak(Box::new(Node { value, prev: None, next: None })));
+        unsafe { self.link_between(node, self.tail, None) };
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut cur = self.head;
+        std::iter::from_fn(move || {
+            let node = unsafe { cur?.as_ref() };
+            cur = node.next;
+            Some(&node.value)
+        })
+    }
+
+    /// A cursor at the first element (or at the "ghost" position, if the
+    /// list is empty).
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { current: self.head, list: NonNull::from(self), _pd: PhantomData }
+    }
+
+    // Links `node` in between `prev` and `next`.
+    unsafe fn link_between(&mut self, mut node: NonNull<Node<T>>, prev: Link<T>, next: Link<T>) {
+        unsafe {
+            node.as_mut().prev = prev;
+            node.as_mut().next = next;
+            match prev {
+                Some(mut p) => p.as_mut().next = Some(node),
+                None => self.head = Some(node),
+            }
+            match next {
+                Some(mut n) => n.as_mut().prev = Some(node),
+                None => self.tail = Some(node),
+            }
+        }
+        self.len += 1;
+    }
+
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> Box<Node<T>> {
+        unsafe {
+            let node = Box::from_raw(node.as_ptr());
+            match node.prev {
+                Some(mut p) => p.as_mut().next = node.next,
+                None => self.head = node.next,
+            }
+            match node.next {
+                Some(mut n) => n.as_mut().prev = node.prev,
+                None => self.tail = node.prev,
+            }
+            self.len -= 1;
+            node
+        }
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for MyLinkedList<T> {
+    fn drop(&mut self) {
+        let mut cur = self.head;
+        while let Some(node) = cur {
+            unsafe {
+                let node = Box::from_raw(node.as_ptr());
+                cur = node.next;
+            }
+        }
+    }
+}
+
+pub struct CursorMut<'a, T> {
+    // None means the "ghost" position past the tail (and before the head).
+    current: Link<T>,
+    list: NonNull<MyLinkedList<T>>,
+    _pd: PhantomData<&'a mut MyLinkedList<T>>,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.current.map(|mut n| &mut n.as_mut().value) }
+    }
+
+    /// Moves to the next element, wrapping through the ghost position.
+    pub fn move_next(&mut self) {
+        self.current = match self.current {
+            Some(node) => unsafe { node.as_ref().next },
+            None => unsafe { self.list.as_ref().head },
+        };
+    }
+
+    /// Inserts `value` after the current element (at the front, if the
+    /// cursor is at the ghost position). The cursor doesn't move.
+    pub fn insert_after(&mut self, value: T) {
+        let node = NonNull::from(Box::leak(Box::new(Node { value, prev: None, next: None })));
+        unsafe {
+            let list = self.list.as_mut();
+            let next = match self.current {
+                Some(cur) => cur.as_ref().next,
+                None => list.head,
+            };
+            list.link_between(node, self.current, next);
+        }
+    }
+
+    /// Removes the current element and moves to the next one.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let current = self.current?;
+        unsafe {
+            let node = self.list.as_mut().unlink(current);
+            self.current = node.next;
+            Some(node.value)
+        }
+    }
+}
+
+crate::registry::demo! {
+    "MyLinkedList: an owning doubly-linked list of heap nodes",
+    tags: ["dropck", "collections"],
+    drops: ["ll_c", "ll_a", "ll_b", "ll_v"],
+}
+
+pub fn demo() {
+    let v = PrintOnDrop::new("ll_v", 13);
+    let mut list = MyLinkedList::new();
+    list.push_back(PrintOnDrop::new("ll_a", &v));
+    list.push_back(PrintOnDrop::new("ll_c", &v));
+
+    let mut cursor = list.cursor_front_mut();
+    cursor.insert_after(PrintOnDrop::new("ll_b", &v));
+    cursor.move_next();
+    cursor.move_next();
+    drop(cursor.remove_current()); // ll_c
+    assert!(cursor.current().is_none());
+    cursor.move_next();
+    println!("CursorMut: back at {:?}", cursor.current().map(|p| p.name()));
+
+    let names: Vec<_> = list.iter().map(|p| p.name()).collect();
+    println!("MyLinkedList: len={} {:?}", list.len(), names);
+
+    // While the cursor is alive, the list is mutably borrowed:
+    //
+    // let mut list = MyLinkedList::new();
+    // list.push_back(1);
+    // let mut cursor = list.cursor_front_mut();
+    // for x in list.iter() { println!("{}", x); } // won't compile: cannot borrow `list` as immutable
+    // cursor.remove_current();
+    //
+    // and, as usual, the list owns its elements:
+    //
+    // let (mut _list, v2); // won't compile: `v2` does not live long enough
+    // v2 = PrintOnDrop::new("v2", 13);
+    // _list = MyLinkedList::new();
+    // _list.push_back(PrintOnDrop::new("ll_bad", &v2));
+}
@@ -0,0 +1,177 @@
+// MySmallVec<T, N>: the first `N` elements live inline, after which
+// everything moves to a heap buffer that doubles in size as needed.
+//
+// The inline storage is `[MaybeUninit<T>; N]`, and MaybeUninit never drops
+// its contents, so as far as dropck is concerned it owns no `T` at all; the
+// heap buffer is a raw pointer. So, once more, it is PhantomData<T> that
+// tells rustc what our destructor will do.
+
+use std::alloc::{self, dealloc, Layout};
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::mem::{self, MaybeUninit};
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::slice;
+
+use crate::print_on_drop;
+
+pub struct MySmallVec<T, const N: usize> {
+    inline: [MaybeUninit<T>; N],
+    // None while the elements are still in `inline`.
+    heap: Option<NonNull<T>>,
+    cap: usize,
+    len: usize,
+    _pd: PhantomData<T>,
+}
+
+impl<T, const N: usize> MySmallVec<T, N> {
+    pub fn new() -> Self {
+        MySmallVec {
+            inline: [const { MaybeUninit::uninit() }; N],
+            heap: None,
+            // Zero-sized elements never need a heap buffer.
+            cap: if mem::size_of::<T>() == 0 { usize::MAX } else { N },
+            len: 0,
+            _pd: PhantomData,
+        }
+    }
+
+    pub fn is_spilled(&self) -> bool {
+        self.heap.is_some()
+    }
+
+    fn as_ptr(&self) -> *const T {
+        match self.heap {
+            Some(p) => p.as_ptr(),
+            None => self.inline.as_ptr() as *const T,
+        }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut T {
+        match self.heap {
+            Some(p) => p.as_ptr(),
+            None => self.inline.as_mut_ptr() as *mut T,
+        }
+    }
+
+    pub fn push(&mut self, t: T) {
+        if self.len == self.cap {
+            self.grow();
+        }
+        unsafe {
+            ptr::write(self.as_mut_ptr().add(self.len), t);
+        }
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        unsafe { Some(ptr::read(self.as_ptr().add(self.len))) }
+    }
+
+    // Doubles the capacity, moving from inline to heap storage on the
+    // first call. Only reached for non-zero-sized `T`.
+    fn grow(&mut self) {
+        let new_cap = (self.cap * 2).max(4);
+        let new_layout = Layout::array::<T>(new_cap).unwrap();
+        unsafe {
+            let Some(p) = NonNull::new(alloc::alloc(new_layout) as *mut T) else {
+                alloc::handle_alloc_error(new_layout);
+            };
+            // A bitwise move: the old slots are treated as uninitialized
+            // from here on, so nothing is dropped twice.
+            ptr::copy_nonoverlapping(self.as_ptr(), p.as_ptr(), self.len);
+            if let Some(old) = self.heap {
+                dealloc(old.as_ptr() as *mut u8, Layout::array::<T>(self.cap).unwrap());
+            }
+            self.heap = Some(p);
+        }
+        self.cap = new_cap;
+    }
+}
+
+impl<T, const N: usize> Deref for MySmallVec<T, N> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+}
+
+unsafe impl<#[may_dangle] T, const N: usize> Drop for MySmallVec<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.as_mut_ptr(), self.len));
+            if let Some(heap) = self.heap {
+                dealloc(heap.as_ptr() as *mut u8, Layout::array::<T>(self.cap).unwrap());
+            }
+        }
+    }
+}
+
+// Counts its own drops, so we can check that every element pushed is
+// dropped exactly once, whichever representation it ends up in.
+struct DropCounter<'a>(&'a Cell<usize>);
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn check_drop_counts<const N: usize>() {
+    for pushes in 0..4 * N + 3 {
+        for pops in 0..=pushes {
+            let drops = Cell::new(0);
+            let mut v = MySmallVec::<_, N>::new();
+            for _ in 0..pushes {
+                v.push(DropCounter(&drops));
+            }
+            for _ in 0..pops {
+                drop(v.pop());
+            }
+            assert_eq!(drops.get(), pops);
+            assert_eq!(v.len(), pushes - pops);
+            drop(v);
+            assert_eq!(drops.get(), pushes);
+        }
+    }
+}
+
+crate::registry::demo! {
+    "MySmallVec: `N` elements inline, then a growing heap buffer",
+    tags: ["dropck", "collections"],
+    drops: ["sv_2", "sv_0", "sv_1", "sv_v"],
+}
+
+pub fn demo() {
+    let v = print_on_drop!("sv_v", 13);
+    let mut sv = MySmallVec::<_, 2>::new();
+    sv.push(print_on_drop!("sv_0", &v));
+    sv.push(print_on_drop!("sv_1", &v));
+    println!("MySmallVec: len={} spilled={}", sv.len(), sv.is_spilled());
+    sv.push(print_on_drop!("sv_2", &v));
+    println!("MySmallVec: len={} spilled={}", sv.len(), sv.is_spilled());
+    drop(sv.pop());
+
+    check_drop_counts::<0>();
+    check_drop_counts::<1>();
+    check_drop_counts::<3>();
+
+    // Zero-sized elements always "fit", even with no inline slots.
+    let mut z = MySmallVec::<(), 0>::new();
+    for _ in 0..100 {
+        z.push(());
+    }
+    assert_eq!((z.len(), z.is_spilled()), (100, false));
+
+    // As for MyBox3:
+    //
+    // let (mut _sv, v2); // won't compile: `v2` does not live long enough
+    // print_on_drop!(v2 = 13);
+    // _sv = MySmallVec::<_, 2>::new();
+    // _sv.push(print_on_drop!("sv_bad", &v2));
+}
@@ -0,0 +1,158 @@
+// MyUnique<T> is our version of std's internal `Unique<T>`, which Box and
+// Vec keep their pointers in. It bundles up everything MyBox3 and MyVec
+// want from their pointer field, one property per ingredient:
+//
+// - NonNull<T>: never null, so Option<MyBox3<T>> is pointer-sized (the
+//   null value is a niche for `None`). And, unlike `*mut T`, covariant in
+//   `T`, which is what an owner of a `T` should be.
+// - PhantomData<T>: "owns a `T`", for dropck (see dropck_pairs.rs).
+//   It also makes our auto traits depend on `T`'s...
+// - unsafe impl Send/Sync: ...except that NonNull<T> is neither, and
+//   would drag us down with it. Owning a `T` through a unique pointer is
+//   no different from owning it inline, so we forward `T`'s.
+//
+// It doesn't know how to allocate or free; that's left to its users.
+//
+// That's the default representation, `repr-unique-emulation`. The other
+// two take an ingredient out, to see what it was for (`cargo run
+// --features repr-... -- layout-report`, and see main.rs):
+//
+// - `repr-const-ptr`: a `*const T` for the NonNull, still covariant, but
+//   with no niche, so an Option<MyBox3<T>> or Option<MyVec<T>> takes
+//   another word. It is never null all the same: it's only ever made from
+//   a NonNull.
+// - `repr-nonnull`: the NonNull without the PhantomData<T>. The layout is
+//   the same, but dropck no longer knows a MyUnique<T> owns a `T`, so
+//   MyBox3's and MyVec's eyepatches become unsound: f3's declarations
+//   compile in either order, and the wrong one is a use after free.
+//
+// The Send and Sync impls are the same for all three. But without the
+// PhantomData<T>, a MyBox3 holding a `&mut T` is UnwindSafe, as a NonNull
+// is for any `T` that is RefUnwindSafe.
+
+use std::cell::Cell;
+#[cfg(not(repr = "nonnull"))]
+use std::marker::PhantomData;
+#[cfg(feature = "unsize")]
+use std::marker::Unsize;
+use std::mem;
+#[cfg(feature = "unsize")]
+use std::ops::{CoerceUnsized, DispatchFromDyn};
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use crate::auto_trait_tests::{assert_impl_send, assert_impl_sync, assert_not_impl_send, assert_not_impl_sync};
+use crate::variance_tests::assert_covariant;
+
+/// The pointer a MyUnique<T> holds, in this build's representation.
+#[cfg(repr = "const-ptr")]
+pub type Raw<T> = *const T;
+#[cfg(not(repr = "const-ptr"))]
+pub type Raw<T> = NonNull<T>;
+
+/// Which representation this build has, as its feature names it.
+#[cfg(repr = "const-ptr")]
+pub const REPR: &str = "repr-const-ptr";
+#[cfg(repr = "nonnull")]
+pub const REPR: &str = "repr-nonnull";
+#[cfg(repr = "unique-emulation")]
+pub const REPR: &str = "repr-unique-emulation";
+
+/// Whether Option can use a MyUnique's null as `None`.
+pub const NICHE: bool = cfg!(not(repr = "const-ptr"));
+
+/// Whether dropck takes dropping a MyUnique<T> as dropping a `T`.
+pub const OWNS: bool = cfg!(not(repr = "nonnull"));
+
+pub struct MyUnique<T: ?Sized> {
+    ptr: Raw<T>,
+    #[cfg(not(repr = "nonnull"))]
+    _pd: PhantomData<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for MyUnique<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for MyUnique<T> {}
+
+impl<T: ?Sized> MyUnique<T> {
+    pub fn new(ptr: NonNull<T>) -> Self {
+        #[cfg(repr = "const-ptr")]
+        let ptr = ptr.as_ptr().cast_const();
+        MyUnique {
+            ptr,
+            #[cfg(not(repr = "nonnull"))]
+            _pd: PhantomData,
+        }
+    }
+
+    #[cfg(repr = "const-ptr")]
+    pub fn as_ptr(self) -> *mut T {
+        self.ptr.cast_mut()
+    }
+
+    #[cfg(not(repr = "const-ptr"))]
+    pub fn as_ptr(self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    #[cfg(repr = "const-ptr")]
+    pub fn as_non_null(self) -> NonNull<T> {
+        // Safety: it was made from a NonNull.
+        unsafe { NonNull::new_unchecked(self.ptr.cast_mut()) }
+    }
+
+    #[cfg(not(repr = "const-ptr"))]
+    pub fn as_non_null(self) -> NonNull<T> {
+        self.ptr
+    }
+}
+
+impl<T> MyUnique<T> {
+    /// A well-aligned placeholder, for empty and zero-sized allocations.
+    pub fn dangling() -> Self {
+        MyUnique::new(NonNull::dangling())
+    }
+}
+
+// Like NonNull, but unlike a derive, without requiring `T: Copy`.
+impl<T: ?Sized> Clone for MyUnique<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for MyUnique<T> {}
+
+// With these, MyUnique<T> converts to MyUnique<U> wherever `*mut T`
+// does to `*mut U`: `[T; N]` to `[T]`, a concrete type to a `dyn Trait`.
+#[cfg(feature = "unsize")]
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<MyUnique<U>> for MyUnique<T> {}
+#[cfg(feature = "unsize")]
+impl<T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<MyUnique<U>> for MyUnique<T> {}
+
+// NonNull: covariance. With `*mut T`, this wouldn't compile (with
+// `*const T`, it does).
+assert_covariant!(for<'a> MyUnique<&'a str>);
+
+crate::registry::demo! {
+    "MyUnique: the pointer field of MyBox3 and MyVec",
+    tags: ["dropck", "variance", "auto-traits"],
+}
+
+pub fn demo() {
+    // NonNull: the niche.
+    assert_eq!(mem::size_of::<Option<MyUnique<u64>>>() == mem::size_of::<*mut u64>(), NICHE);
+    assert_ne!(mem::size_of::<Option<*mut u64>>(), mem::size_of::<*mut u64>());
+
+    // The unsafe impls: Send and Sync exactly when `T` is.
+    assert_impl_send!(MyUnique<i32>, MyUnique<Cell<i32>>);
+    assert_impl_sync!(MyUnique<i32>);
+    assert_not_impl_sync!(MyUnique<Cell<i32>>);
+    assert_not_impl_send!(MyUnique<Rc<i32>>);
+    // Without them, NonNull would make it neither:
+    assert_not_impl_send!(NonNull<i32>);
+    assert_not_impl_sync!(NonNull<i32>);
+
+    // PhantomData<T>: ownership, tested by f3 in lib.rs, which only
+    // compiles in one declaration order (but for repr-nonnull).
+    println!("MyUnique ({}): {} bytes, or {} as an Option", REPR, mem::size_of::<MyUnique<u64>>(), mem::size_of::<Option<MyUnique<u64>>>());
+}
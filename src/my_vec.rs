@@ -0,0 +1,432 @@
+// MyVec<T>: MyBox3 generalized to `len` values in one allocation, laid out
+// like std's Vec (pointer, capacity, length). Its Drop is eyepatched and
+// its pointer is a MyUnique<T>, for the same reasons as MyBox3.
+//
+// Capacity at least doubles whenever it has to grow, so a run of pushes
+// costs amortized O(1) each, as with Vec, and it grows in place through
+// realloc where the allocator allows. `f_growth` checks how many
+// allocations that takes, and times MyVec against Vec: none of the
+// PhantomData in here exists at run time, so the two should be on par.
+//
+// The destructor skips the element-dropping step entirely when `T` has no
+// drop glue; see `f_needs_drop` for which types do.
+//
+// `drain` shows a different lesson: safe code may leak (`mem::forget`) any
+// value at any time, so a type that temporarily breaks its invariants must
+// stay sound even if the value that would restore them is never dropped.
+
+use std::alloc::{self, dealloc, Layout};
+use std::marker::PhantomData;
+use std::mem;
+use std::hint::black_box;
+use std::ops::{Bound, RangeBounds};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::alloc_counter::allocations;
+use crate::my_iter::{MyIter, MyIterMut};
+use crate::my_unique::MyUnique;
+use crate::unwind_safe::quietly;
+use crate::{recorder, PrintOnDrop};
+
+pub struct MyVec<T> {
+    ptr: MyUnique<T>,
+    cap: usize,
+    len: usize,
+}
+
+impl<T> MyVec<T> {
+    pub fn new() -> Self {
+        // Zero-sized values never need an allocation.
+        let cap = if mem::size_of::<T>() == 0 { usize::MAX } else { 0 };
+        MyVec { ptr: MyUnique::dangling(), cap, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn push(&mut self, t: T) {
+        if self.len == self.cap {
+            self.reserve(1);
+        }
+        unsafe {
+            ptr::write(self.ptr.as_ptr().add(self.len), t);
+        }
+        self.len += 1;
+    }
+
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr.as_ptr()
+    }
+
+    pub fn iter(&self) -> MyIter<'_, T> {
+        unsafe { MyIter::new(self.ptr.as_non_null(), self.len) }
+    }
+
+    pub fn iter_mut(&mut self) -> MyIterMut<'_, T> {
+        unsafe { MyIterMut::new(self.ptr.as_non_null(), self.len) }
+    }
+
+    /// Removes the elements in `range`, handing them out through the
+    /// returned iterator. Whatever the iterator didn't hand out is dropped
+    /// along with it, and the elements after `range` are moved down.
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i + 1,
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => self.len,
+        };
+        assert!(start <= end && end <= self.len, "drain range out of bounds");
+        let tail_len = self.len - end;
+        // Pre-poison: until the Drain is dropped, the vector only admits to
+        // its first `start` elements. If the Drain is leaked, the drained
+        // range and the tail are leaked with it, but nothing is dropped
+        // twice or read after being moved out.
+        self.len = start;
+        Drain { vec: NonNull::from(&mut *self), next: start, end, tail_len, _pd: PhantomData }
+    }
+
+    /// Drops the elements from `len` on, in order, keeping the capacity.
+    /// Does nothing if there aren't that many.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail = ptr::slice_from_raw_parts_mut(unsafe { self.ptr.as_ptr().add(len) }, self.len - len);
+        // Shorten first: should one of the destructors panic, the rest of
+        // the tail is still dropped (drop_in_place sees to that), and the
+        // vector no longer claims any of it.
+        self.len = len;
+        unsafe { ptr::drop_in_place(tail) }
+    }
+
+    /// Drops every element, in order, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Makes room for at least `additional` more elements. If that means
+    /// reallocating, the capacity at least doubles.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        if needed > self.cap {
+            self.set_capacity(needed.max(self.cap * 2).max(4));
+        }
+    }
+
+    /// Gives back any capacity beyond `len`.
+    pub fn shrink_to_fit(&mut self) {
+        if mem::size_of::<T>() > 0 && self.cap > self.len {
+            self.set_capacity(self.len);
+        }
+    }
+
+    // Resizes the allocation to exactly `new_cap` (no less than `len`)
+    // slots, or to none at all if that is zero. Only reached for
+    // non-zero-sized `T`.
+    //
+    // realloc moves the elements for us if it has to, but needs to be
+    // told the layout the block was allocated with, which is a function
+    // of the *old* capacity: so `self.cap` may only be updated once the
+    // memory has actually changed hands.
+    fn set_capacity(&mut self, new_cap: usize) {
+        let old_layout = Layout::array::<T>(self.cap).unwrap();
+        // Fails if `new_cap` elements would come to more than isize::MAX
+        // bytes, which is before anything has been touched.
+        let new_layout = Layout::array::<T>(new_cap).expect("capacity overflow");
+        let p = unsafe {
+            if new_cap == 0 {
+                dealloc(self.ptr.as_ptr() as *mut u8, old_layout);
+                NonNull::dangling()
+            } else {
+                let p = if self.cap == 0 {
+                    alloc::alloc(new_layout)
+                } else {
+                    alloc::realloc(self.ptr.as_ptr() as *mut u8, old_layout, new_layout.size())
+                };
+                // On failure the old block is left as it was, but there
+                // is no way to go on without the new one.
+                let Some(p) = NonNull::new(p as *mut T) else {
+                    alloc::handle_alloc_error(new_layout);
+                };
+                p
+            }
+        };
+        self.ptr = MyUnique::new(p);
+        self.cap = new_cap;
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for MyVec<T> {
+    fn drop(&mut self) {
+        unsafe {
+            // For a slice, drop_in_place is itself a loop over the
+            // elements; for a `T` without drop glue we needn't even start.
+            if mem::needs_drop::<T>() {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+            }
+            if self.cap > 0 && mem::size_of::<T>() > 0 {
+                dealloc(self.ptr.as_ptr() as *mut u8, Layout::array::<T>(self.cap).unwrap());
+            }
+        }
+    }
+}
+
+pub struct Drain<'a, T> {
+    vec: NonNull<MyVec<T>>,
+    // Elements `next..end` are still to be handed out; `tail_len` more
+    // follow them, to be moved down when we are done.
+    next: usize,
+    end: usize,
+    tail_len: usize,
+    // We hold the vector's `&'a mut` in all but name.
+    _pd: PhantomData<&'a mut MyVec<T>>,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        unsafe { Some(ptr::read(self.vec.as_ref().ptr.as_ptr().add(self.next - 1))) }
+    }
+}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            let vec = self.vec.as_mut();
+            let start = vec.len;
+            if mem::needs_drop::<T>() {
+                let rest = ptr::slice_from_raw_parts_mut(vec.ptr.as_ptr().add(self.next), self.end - self.next);
+                ptr::drop_in_place(rest);
+            }
+            ptr::copy(vec.ptr.as_ptr().add(self.end), vec.ptr.as_ptr().add(start), self.tail_len);
+            vec.len = start + self.tail_len;
+        }
+    }
+}
+
+fn f_drain() {
+    let v = PrintOnDrop::new("vec_v", 13);
+    let mut vec = MyVec::new();
+    for i in 0..5 {
+        vec.push(PrintOnDrop::new(format!("dr_{}", i), &v));
+    }
+
+    // Take one of the three drained elements; the other two are dropped
+    // with the Drain.
+    recorder::take();
+    let mut drain = vec.drain(1..4);
+    let taken = drain.next().unwrap();
+    drop(drain);
+    assert_eq!(recorder::take_drops(), ["dr_2", "dr_3"]);
+    drop(taken);
+    assert_eq!(recorder::take_drops(), ["dr_1"]);
+    let left: Vec<_> = vec.iter().map(|p| p.name()).collect();
+    assert_eq!(left, ["dr_0", "dr_4"]);
+
+    // Forgetting a Drain leaks everything from the drained range on, but
+    // is otherwise harmless: the vector simply got shorter.
+    let mut drain = vec.drain(..1);
+    drop(drain.next());
+    mem::forget(drain);
+    println!("MyVec after a forgotten drain: len={}", vec.len());
+    assert_eq!(vec.len(), 0);
+    vec.push(PrintOnDrop::new("dr_5", &v));
+    drop(vec);
+    assert_eq!(recorder::take_drops(), ["dr_0", "dr_5"]);
+
+    // And, like the iterators, a Drain borrows the vector mutably:
+    //
+    // let mut vec = MyVec::new();
+    // vec.push(1);
+    // let mut drain = vec.drain(..);
+    // vec.push(2); // won't compile: cannot borrow `vec` as mutable more than once
+    // drain.next();
+}
+
+// Not a rigorous benchmark, just enough to see that MyVec keeps up with
+// Vec on pushes.
+fn time_my_vec(n: u64) -> Duration {
+    let start = Instant::now();
+    let mut vec = MyVec::new();
+    for i in 0..n {
+        vec.push(black_box(i));
+    }
+    let elapsed = start.elapsed();
+    assert_eq!(vec.iter().sum::<u64>(), n * (n - 1) / 2);
+    elapsed
+}
+
+fn time_std_vec(n: u64) -> Duration {
+    let start = Instant::now();
+    let mut vec = Vec::new();
+    for i in 0..n {
+        vec.push(black_box(i));
+    }
+    let elapsed = start.elapsed();
+    assert_eq!(vec.iter().sum::<u64>(), n * (n - 1) / 2);
+    elapsed
+}
+
+fn f_growth() {
+    // Capacities 4, 8, ..., 1024: nine allocations for a thousand pushes.
+    let before = allocations();
+    let mut vec = MyVec::new();
+    for i in 0..1000u64 {
+        vec.push(i);
+    }
+    assert_eq!(allocations() - before, 9);
+    assert_eq!(vec.capacity(), 1024);
+
+    // One reallocation makes room for all of these...
+    let before = allocations();
+    vec.reserve(5000);
+    for i in 1000..6000 {
+        vec.push(i);
+    }
+    assert_eq!(allocations() - before, 1);
+    assert_eq!((vec.len(), vec.capacity()), (6000, 6000));
+
+    // ...and one more gives back what's left over.
+    vec.reserve(1);
+    assert_eq!(vec.capacity(), 12000);
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 6000);
+    assert_eq!(vec.iter().sum::<u64>(), 6000 * 5999 / 2);
+    vec.drain(..).for_each(drop);
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 0);
+
+    const N: u64 = 1_000_000;
+    println!("MyVec: {} pushes in {:?} (Vec: {:?})", N, time_my_vec(N), time_std_vec(N));
+}
+
+// Adds itself up as it is dropped: each drop costs next to nothing, but
+// it has to be called once per element.
+struct Counted(u64);
+
+static COUNTED_SUM: AtomicU64 = AtomicU64::new(0);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        COUNTED_SUM.fetch_add(self.0, Ordering::Relaxed);
+    }
+}
+
+fn time_drop<T>(vec: MyVec<T>) -> Duration {
+    let start = Instant::now();
+    drop(vec);
+    start.elapsed()
+}
+
+fn f_realloc() {
+    let before = allocations();
+    let mut vec = MyVec::new();
+    for i in 0..100u64 {
+        vec.push(i);
+    }
+    vec.shrink_to_fit();
+    vec.reserve(1000);
+    // One alloc and five reallocs (4 -> 128), one to shrink and one to
+    // grow again, with the values carried along every time.
+    assert_eq!(allocations() - before, 8);
+    assert!(vec.iter().copied().eq(0..100));
+
+    // A capacity whose byte size doesn't fit in an isize is refused up
+    // front, leaving the vector as it was...
+    let mut big = MyVec::<u64>::new();
+    big.push(1);
+    let r = quietly(|| panic::catch_unwind(AssertUnwindSafe(|| big.reserve(usize::MAX / 8))));
+    assert!(r.is_err());
+    // ...as is one whose element count doesn't even fit in a usize.
+    let r = quietly(|| panic::catch_unwind(AssertUnwindSafe(|| big.reserve(usize::MAX))));
+    assert!(r.is_err());
+    big.push(2);
+    assert!(big.iter().copied().eq([1, 2]));
+    // (An allocator saying no to a size that *is* legal can't be shown
+    // here: handle_alloc_error aborts the process.)
+
+    // Zero-sized elements never touch the allocator, however many there
+    // are, and can't be shrunk below the "infinite" capacity they start with.
+    let before = allocations();
+    let mut zsts = MyVec::new();
+    for _ in 0..1000 {
+        zsts.push(());
+    }
+    zsts.reserve(usize::MAX - 1000);
+    zsts.shrink_to_fit();
+    assert_eq!((zsts.len(), zsts.capacity()), (1000, usize::MAX));
+    assert_eq!(allocations() - before, 0);
+    let r = quietly(|| panic::catch_unwind(AssertUnwindSafe(|| zsts.reserve(usize::MAX))));
+    assert!(r.is_err());
+}
+
+fn f_needs_drop() {
+    // A type has drop glue if it implements Drop, or if anything it holds
+    // by value does...
+    assert!(!mem::needs_drop::<u64>());
+    assert!(!mem::needs_drop::<(u64, &String)>());
+    assert!(mem::needs_drop::<(u64, String)>());
+    assert!(mem::needs_drop::<PrintOnDrop<u64>>());
+    // ...whatever it holds it for. A MyVec<u64> has a Drop impl, so it
+    // needs dropping even though no `u64` ever does.
+    assert!(mem::needs_drop::<MyVec<u64>>());
+    // And PhantomData<T> never has any, whatever `T` is: it tells dropck
+    // that we *may* drop a `T`, but the actual dropping is up to our own
+    // Drop impl.
+    assert!(!mem::needs_drop::<PhantomData<String>>());
+
+    const N: u64 = 1_000_000;
+    let plain: MyVec<u64> = (0..N).fold(MyVec::new(), |mut v, i| { v.push(i); v });
+    let counted = (0..N).fold(MyVec::new(), |mut v, i| { v.push(Counted(i)); v });
+    // What an earlier run added doesn't count.
+    let before = COUNTED_SUM.load(Ordering::Relaxed);
+    let (t_plain, t_counted) = (time_drop(plain), time_drop(counted));
+    assert_eq!(COUNTED_SUM.load(Ordering::Relaxed) - before, N * (N - 1) / 2);
+    println!("MyVec: dropping {} u64s took {:?} (with drop glue: {:?})", N, t_plain, t_counted);
+}
+
+fn f_basic() {
+    let v = PrintOnDrop::new("vec_v", 13);
+    let mut vec = MyVec::new();
+    vec.push(PrintOnDrop::new("vec_0", &v));
+    vec.push(PrintOnDrop::new("vec_1", &v));
+    println!("MyVec: len={}", vec.len());
+
+    // As for MyBox3:
+    //
+    // let (mut _vec, v2); // won't compile: `v2` does not live long enough
+    // v2 = PrintOnDrop::new("v2", 13);
+    // _vec = MyVec::new();
+    // _vec.push(PrintOnDrop::new("vec_bad", &v2));
+}
+
+crate::registry::demo! {
+    "MyVec: growth, drain, realloc and needs_drop",
+    tags: ["dropck", "collections", "alloc"],
+}
+
+pub fn demo() {
+    f_basic();
+    f_drain();
+    f_growth();
+    f_realloc();
+    f_needs_drop();
+}
@@ -0,0 +1,121 @@
+// A borrow and the value holding it end at different times. Since NLL
+// ("non-lexical lifetimes"), a borrow lasts only as long as it is used:
+// a reference last used halfway through a block no longer borrows in the
+// other half, and what it borrowed can be changed there. But when values
+// are dropped hasn't changed: still at the end of their scope, in reverse
+// order of declaration, however long before that they were last used.
+//
+// The two meet when what holds the borrow has drop glue. Its drop, at
+// the end of the scope, is a use of the borrow, so the borrow lasts to
+// the end of the scope, just as it did before NLL. A plain reference has
+// no drop glue, and no such last use. The f-functions in lib.rs don't
+// tell these apart: there, every borrow is held by a PrintOnDrop, so it
+// lasts until that is dropped, which is what dropck is about.
+
+use lesson::lesson;
+
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// A plain reference's borrow ends at its last use, and `v` can be changed
+// after it; `v` is still only dropped at the end of the block.
+fn reference_ends_early() -> u32 {
+    let mut v = print_on_drop!("nll_v", 1);
+    let r = &v;
+    let seen = r.1;
+    mark("nll_last_use");
+    v.1 += 1;
+    mark("nll_changed");
+    seen + v.1
+}
+
+// Held by a PrintOnDrop, the borrow lasts until that is dropped, at the
+// end of the block; to change `v` before then, drop it first.
+fn holder_keeps_it() -> u32 {
+    let mut v = print_on_drop!("nll_v", 1);
+    let holder = print_on_drop!("nll_holder", &v);
+    let seen = holder.1 .1;
+    mark("nll_last_use");
+    //
+    // v.1 += 1; // won't compile: E0506, cannot assign to `v.1` because it is borrowed
+    drop(holder);
+    v.1 += 1;
+    mark("nll_changed");
+    seen + v.1
+}
+
+// Borrows of two values, neither nested in the other: that of `a` ends
+// while that of `b` is live, and `a` is changed in between. Lexically,
+// both would have lasted to the end of the block.
+fn interleaved() -> u32 {
+    let mut a = print_on_drop!("nll_a", 1);
+    let mut b = print_on_drop!("nll_b", 10);
+    let ra = &a;
+    let rb = &mut b;
+    rb.1 += ra.1;
+    a.1 += 1;
+    rb.1 += 1;
+    mark("nll_rb_done");
+    let ra = &mut a;
+    ra.1 += b.1;
+    a.1
+}
+
+// Still rejected, though it would be sound: a borrow returned from one
+// branch lasts as long as the result, in every branch, so the vector is
+// still borrowed when the other pushes to it (Polonius would accept it).
+//
+// fn first_or_push(v: &mut Vec<PrintOnDrop<u32>>) -> &PrintOnDrop<u32> {
+//     if let Some(p) = v.first() {
+//         return p;
+//     }
+//     v.push(print_on_drop!("nll_pushed", 0)); // won't compile: E0502, cannot borrow `*v` as mutable because it is also borrowed as immutable
+//     &v[0]
+// }
+//
+// What compiles borrows again, once it knows which branch it is in.
+fn first_or_push(v: &mut Vec<PrintOnDrop<u32>>) -> &PrintOnDrop<u32> {
+    if v.is_empty() {
+        v.push(print_on_drop!("nll_pushed", 0));
+    }
+    &v[0]
+}
+
+/// NLL ends a borrow at its last use, not at the end of the scope: after
+/// a reference to `v` has been used for the last time, `v` can be changed,
+/// or borrowed mutably, even while the reference is still in scope. The
+/// same goes for two borrows that overlap without either being nested in
+/// the other, each ending when it is done with.
+///
+/// Drops didn't move: every value here is still dropped at the end of its
+/// scope. So a borrow held by something with drop glue (a PrintOnDrop,
+/// say) lasts until it is dropped, since dropping it uses the borrow, and
+/// it takes an explicit `drop` to end it sooner. And a borrow returned
+/// out of one branch of a function lasts, in all of them, as long as the
+/// value returned.
+#[lesson(
+    title = "Non-lexical lifetimes: where borrows end, and where drops still happen",
+    tags = ["lifetimes", "drop-order"],
+    notes = [
+        "Changing a value while a PrintOnDrop holding a borrow of it is in scope won't compile: E0506",
+        "Pushing to a vector after returning a borrow of it from another branch won't compile: E0502",
+    ],
+)]
+pub fn demo() {
+    recorder::take();
+    assert_eq!(reference_ends_early(), 3);
+    assert_eq!(recorder::take_drops(), ["nll_last_use", "nll_changed", "nll_v"]);
+
+    assert_eq!(holder_keeps_it(), 3);
+    assert_eq!(recorder::take_drops(), ["nll_last_use", "nll_holder", "nll_changed", "nll_v"]);
+
+    assert_eq!(interleaved(), 14);
+    assert_eq!(recorder::take_drops(), ["nll_rb_done", "nll_b", "nll_a"]);
+
+    let mut v = Vec::new();
+    assert_eq!(first_or_push(&mut v).name(), "nll_pushed");
+    assert_eq!(first_or_push(&mut v).id(), v[0].id());
+    assert_eq!(v.len(), 1);
+    drop(v);
+    assert_eq!(recorder::take_drops(), ["nll_pushed"]);
+}
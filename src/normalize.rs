@@ -0,0 +1,181 @@
+// What changes from one run to the next, though the demos don't: the ids
+// PrintOnDrops are given (which depend on how many were made before, on
+// which thread), addresses, how long things took, and the order a std
+// HashMap drops its entries in. Normalized, two runs of the same demos
+// come out the same, on any machine, so that their traces and output can
+// be kept as golden files, and diffed.
+//
+// For a trace, that means renumbering ids from 1, in the order they first
+// appear, and, for a demo whose drops may come in any order (see
+// `drops_any_order` in registry.rs), sorting each run of drops. For text,
+// ids are renumbered the same way, and addresses and durations redacted;
+// a demo's own output has its runs of drop lines sorted likewise.
+// `--normalize` has both done to what `--record` saves, and to what
+// demos run in child processes print; `--replay --diff` always compares
+// traces normalized.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::recorder::Event;
+use crate::registry::{self, Demo};
+use crate::trace::Trace;
+use crate::State;
+
+static OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether what demos run in child processes print is normalized before
+/// it's passed on.
+pub fn normalize_output(on: bool) {
+    OUTPUT.store(on, Ordering::Relaxed);
+}
+
+pub(crate) fn output_normalized() -> bool {
+    OUTPUT.load(Ordering::Relaxed)
+}
+
+// Numbers ids from 1, in the order they are first asked about.
+#[derive(Default)]
+struct Renumber(HashMap<u64, u64>);
+
+impl Renumber {
+    fn id(&mut self, id: u64) -> u64 {
+        let next = self.0.len() as u64 + 1;
+        *self.0.entry(id).or_insert(next)
+    }
+}
+
+fn any_order(demo: &Demo) -> bool {
+    demo.expected_drops.is_some_and(|e| !e.in_order())
+}
+
+/// `t`, with its ids renumbered, and (if its demo's drops may come in any
+/// order) each run of drops sorted.
+pub fn trace(t: &Trace) -> Trace {
+    let mut events = t.events.clone();
+    if registry::all().iter().any(|d| d.name() == t.demo && any_order(d)) {
+        for run in events.chunk_by_mut(|a, b| matches!((a, b), (Event::Drop(..), Event::Drop(..)))) {
+            run.sort_by_key(|e| match e {
+                Event::Drop(name, _, state) => (name.clone(), *state == State::INVALID),
+                _ => (Cow::Borrowed(""), false),
+            });
+        }
+    }
+    let mut ids = Renumber::default();
+    for e in &mut events {
+        if let Event::Drop(_, id, _) = e {
+            *id = ids.id(*id);
+        }
+    }
+    Trace { events, ..t.clone() }
+}
+
+// Whether `c` could be part of a name, or of a number.
+fn word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+/// `s`, with ids (as in `name#12`) renumbered, and addresses (`0x7ff3...`)
+/// and durations (`12.5ms`, as a Duration's Debug has them) redacted.
+pub fn text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut ids = Renumber::default();
+    let mut prev = ' ';
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        let digits = |t: &str| t.find(|c: char| !c.is_ascii_digit()).unwrap_or(t.len());
+        // An id follows a name; `#` before a `;` is a Mermaid escape, and
+        // more digits than a u64 holds aren't an id either.
+        if c == '#' && word(prev) {
+            let n = digits(&rest[1..]);
+            let id = (n > 0 && !rest[1 + n..].starts_with(';')).then(|| rest[1..1 + n].parse().ok());
+            if let Some(Some(id)) = id {
+                out.push('#');
+                out.push_str(&ids.id(id).to_string());
+                (prev, rest) = ('0', &rest[1 + n..]);
+                continue;
+            }
+        }
+        if !word(prev) && rest.starts_with("0x") {
+            let n = rest[2..].find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(rest.len() - 2);
+            if n > 0 {
+                out.push_str("0x<addr>");
+                (prev, rest) = ('0', &rest[2 + n..]);
+                continue;
+            }
+        }
+        if !word(prev) && c.is_ascii_digit() {
+            let mut n = digits(rest);
+            if rest[n..].starts_with('.') {
+                n += 1 + digits(&rest[n + 1..]);
+            }
+            let unit = ["ns", "µs", "us", "ms", "s"].into_iter().find(|u| rest[n..].starts_with(u));
+            if let Some(unit) = unit {
+                let end = n + unit.len();
+                if !rest[end..].starts_with(word) {
+                    out.push_str("<time>");
+                    (prev, rest) = ('s', &rest[end..]);
+                    continue;
+                }
+            }
+        }
+        out.push(c);
+        (prev, rest) = (c, &rest[c.len_utf8()..]);
+    }
+    out
+}
+
+/// What `demo` printed, normalized as `text` does, and with each run of
+/// drop lines sorted if its drops may come in any order.
+pub fn output(demo: &Demo, s: &str) -> String {
+    if !any_order(demo) {
+        return text(s);
+    }
+    let mut lines: Vec<&str> = s.split_inclusive('\n').collect();
+    for run in lines.chunk_by_mut(|a, b| a.trim_start().starts_with("drop ") && b.trim_start().starts_with("drop ")) {
+        run.sort_unstable();
+    }
+    text(&lines.concat())
+}
+
+crate::registry::demo!("Normalized traces and output, for golden files and diffs", tags: ["drop-order"]);
+
+pub fn demo() {
+    let drop = |name: &'static str, id| Event::Drop(Cow::Borrowed(name), id, State::Valid);
+    let t = Trace { demo: "nz".into(), lost: 0, events: vec![drop("nz_b", 57), Event::Guard("nz_g".into()), drop("nz_a", 12), drop("nz_b", 57)] };
+    assert_eq!(trace(&t).events, [drop("nz_b", 1), Event::Guard("nz_g".into()), drop("nz_a", 2), drop("nz_b", 1)]);
+
+    // A HashMap's drops, in whatever order: sorted, for hash_map_drop's
+    // trace, but only there.
+    let hashed = |names: [&'static str; 3], ids: [u64; 3]| Trace {
+        demo: "hash_map_drop".into(),
+        lost: 0,
+        events: vec![Event::Enter("nz".into()), drop(names[0], ids[0]), drop(names[1], ids[1]), drop(names[2], ids[2])],
+    };
+    let one = hashed(["hm_2", "hm_0", "hm_1"], [3, 1, 2]);
+    let other = hashed(["hm_1", "hm_2", "hm_0"], [9, 10, 8]);
+    assert_ne!(one, other);
+    assert_eq!(trace(&one), trace(&other));
+    assert_eq!(trace(&one).drops(), ["hm_0", "hm_1", "hm_2"]);
+    assert_ne!(trace(&Trace { demo: "nz".into(), ..one.clone() }), trace(&Trace { demo: "nz".into(), ..other }));
+
+    let printed = "drop PrintOnDrop(mb#41, PrintOnDrop(v#40, 13, INVALID), Valid) at 0x7ffd5e2a91c0\n\
+                   MyArc: 8 threads in 29.87ms (37ns per pair), 1.5s, 12µs in all; v#40 again\n\
+                   create participant v1 as mm_v#35;40 for 2 demos";
+    assert_eq!(
+        text(printed),
+        "drop PrintOnDrop(mb#1, PrintOnDrop(v#2, 13, INVALID), Valid) at 0x<addr>\n\
+         MyArc: 8 threads in <time> (<time> per pair), <time>, <time> in all; v#2 again\n\
+         create participant v1 as mm_v#35;40 for 2 demos"
+    );
+    // Twice over, nothing changes.
+    assert_eq!(text(&text(printed)), text(printed));
+    assert_eq!(text("big#123456789012345678901234567890"), "big#123456789012345678901234567890");
+
+    let hash_map_drop = registry::all().into_iter().find(|d| d.name() == "hash_map_drop").unwrap();
+    let printed = "{ hm\n  drop PrintOnDrop(hm_1#8, 1, Valid)\n  drop PrintOnDrop(hm_0#7, 0, Valid)\n} hm\n";
+    let sorted = "{ hm\n  drop PrintOnDrop(hm_0#1, 0, Valid)\n  drop PrintOnDrop(hm_1#2, 1, Valid)\n} hm\n";
+    assert_eq!(output(hash_map_drop, printed), sorted);
+    assert_eq!(output(&DEMO, printed), text(printed));
+}
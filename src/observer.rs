@@ -0,0 +1,275 @@
+// What PrintOnDrop does besides recording (which it always does, for the
+// demos' own checks) is up to DropObservers: told when a PrintOnDrop is
+// made, when it is dropped, and when one is looked at after it was
+// dropped, which is what unsound code (see dropck_pairs.rs) ends up doing.
+// They are also told of every event recorded, whoever recorded it.
+//
+// Any number may listen at once (up to a fixed few, so that telling them
+// never allocates): `subscribe` adds one for events from every thread,
+// `subscribe_here` for events from the current thread only, which keeps
+// apart what concurrent demos' threads do. Either returns a Subscription
+// that unsubscribes when dropped. Stdout, subscribed from the start,
+// prints each drop, as PrintOnDrop always has, indented by how many scope
+// markers (see scope_guard.rs) it is inside of on its thread, and warns
+// on stderr about use after drop; `quiet_drops` mutes it for a while.
+// Observers are `&'static`s: unit structs, or statics with state of their
+// own, as in the demo below. `with_observer` lends one, for the current thread's
+// events, for only as long as a closure runs, which is how the library's
+// `run_demo` takes an observer that isn't `'static` (see engine.rs).
+
+use std::cell::Cell;
+use std::fmt;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::thread;
+
+use crate::recorder::Event;
+use crate::{quiet_drops, PrintOnDrop, State};
+
+pub trait DropObserver: Sync {
+    fn on_construct(&self, _name: &str, _id: u64) {}
+
+    /// `value` is the PrintOnDrop being dropped, as its Debug shows it.
+    fn on_drop(&self, _name: &str, _id: u64, _state: State, _value: &dyn fmt::Debug) {}
+
+    fn on_invalid_access(&self, _name: &str, _id: u64) {}
+
+    /// Whatever `recorder::record` is given, on whichever thread.
+    fn on_record(&self, _event: &Event) {}
+}
+
+pub struct Stdout;
+
+static MUTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // How many scopes deep Stdout is, on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+fn indent() -> usize {
+    2 * DEPTH.try_with(Cell::get).unwrap_or(0)
+}
+
+impl Stdout {
+    /// Mutes (or unmutes) Stdout, returning whether it was muted.
+    pub fn mute(muted: bool) -> bool {
+        MUTED.swap(muted, Ordering::Relaxed)
+    }
+}
+
+impl DropObserver for Stdout {
+    fn on_drop(&self, _: &str, _: u64, _: State, value: &dyn fmt::Debug) {
+        if !MUTED.load(Ordering::Relaxed) {
+            println!("{:2$}drop {:?}", "", value, indent());
+        }
+    }
+
+    fn on_record(&self, event: &Event) {
+        let muted = MUTED.load(Ordering::Relaxed);
+        match event {
+            Event::Enter(name) => {
+                if !muted {
+                    println!("{:2$}{{ {}", "", name, indent());
+                }
+                let _ = DEPTH.try_with(|d| d.set(d.get() + 1));
+            }
+            Event::Exit(name) => {
+                let _ = DEPTH.try_with(|d| d.set(d.get().saturating_sub(1)));
+                if !muted {
+                    println!("{:2$}}} {}", "", name, indent());
+                }
+            }
+            Event::Drop(..) | Event::Guard(_) | Event::Traced(_) => {}
+        }
+    }
+
+    fn on_invalid_access(&self, name: &str, id: u64) {
+        eprintln!("warning: {}#{} used after it was dropped", name, id);
+    }
+}
+
+type Slots<const N: usize> = [Option<&'static dyn DropObserver>; N];
+
+const GLOBAL_SLOTS: usize = 8;
+const LOCAL_SLOTS: usize = 4;
+
+static GLOBAL: RwLock<Slots<GLOBAL_SLOTS>> = RwLock::new({
+    let mut slots: Slots<GLOBAL_SLOTS> = [None; GLOBAL_SLOTS];
+    slots[0] = Some(&Stdout);
+    slots
+});
+
+thread_local! {
+    static LOCAL: Cell<Slots<LOCAL_SLOTS>> = const { Cell::new([None; LOCAL_SLOTS]) };
+    // What `with_observer` lends, for no longer than its call.
+    static LENT: Cell<Option<*const dyn DropObserver>> = const { Cell::new(None) };
+}
+
+/// Tells every observer of the current thread's events. The slots are
+/// copied out first, so an observer may itself show (and so observe)
+/// values, or subscribe others.
+pub fn publish(f: impl Fn(&dyn DropObserver)) {
+    let global = *GLOBAL.read().unwrap();
+    // During thread teardown the local slots may already be gone.
+    let local = LOCAL.try_with(Cell::get).unwrap_or([None; LOCAL_SLOTS]);
+    for o in global.into_iter().chain(local).flatten() {
+        f(o);
+    }
+    if let Some(o) = LENT.try_with(Cell::get).ok().flatten() {
+        // Safety: see with_observer.
+        f(unsafe { &*o });
+    }
+}
+
+/// Runs `f` with `o` told of the current thread's events too, as if
+/// subscribed here, but only until `f` returns (or panics).
+pub fn with_observer<R>(o: &dyn DropObserver, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<*const dyn DropObserver>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            LENT.set(self.0);
+        }
+    }
+
+    // Safety: only the lifetime is changed. The pointer is only used on
+    // this thread, by `publish`, while `f` runs: whatever was lent before
+    // is put back on the way out, however `f` leaves.
+    let o: *const (dyn DropObserver + 'static) = unsafe { mem::transmute(o) };
+    let _restore = Restore(LENT.replace(Some(o)));
+    f()
+}
+
+#[must_use = "dropping a Subscription unsubscribes"]
+pub struct Subscription {
+    slot: usize,
+    // For a thread's own subscriber, which thread; it can only be
+    // unsubscribed there.
+    local: Option<thread::ThreadId>,
+}
+
+/// Subscribes `o` to events from every thread.
+pub fn subscribe(o: &'static dyn DropObserver) -> Subscription {
+    let mut global = GLOBAL.write().unwrap();
+    let slot = free_slot(&global).expect("observer: too many subscribers");
+    global[slot] = Some(o);
+    Subscription { slot, local: None }
+}
+
+/// Subscribes `o` to events from the current thread only.
+pub fn subscribe_here(o: &'static dyn DropObserver) -> Subscription {
+    let mut local = LOCAL.get();
+    let slot = free_slot(&local).expect("observer: too many subscribers on this thread");
+    local[slot] = Some(o);
+    LOCAL.set(local);
+    Subscription { slot, local: Some(thread::current().id()) }
+}
+
+fn free_slot<const N: usize>(slots: &Slots<N>) -> Option<usize> {
+    slots.iter().position(Option::is_none)
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        match self.local {
+            None => GLOBAL.write().unwrap()[self.slot] = None,
+            Some(id) => {
+                assert_eq!(id, thread::current().id(), "observer: unsubscribed on another thread");
+                let mut local = LOCAL.get();
+                local[self.slot] = None;
+                LOCAL.set(local);
+            }
+        }
+    }
+}
+
+// A test harness's observer: counts, and says nothing.
+struct Counting {
+    constructed: AtomicU64,
+    dropped: AtomicU64,
+    invalid: AtomicU64,
+}
+
+impl DropObserver for Counting {
+    fn on_construct(&self, _: &str, _: u64) {
+        self.constructed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_drop(&self, _: &str, _: u64, _: State, _: &dyn fmt::Debug) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_invalid_access(&self, _: &str, _: u64) {
+        self.invalid.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+static COUNTING: Counting = Counting {
+    constructed: AtomicU64::new(0),
+    dropped: AtomicU64::new(0),
+    invalid: AtomicU64::new(0),
+};
+
+static MAIN_THREAD: Counting = Counting {
+    constructed: AtomicU64::new(0),
+    dropped: AtomicU64::new(0),
+    invalid: AtomicU64::new(0),
+};
+
+static SPAWNED: Counting = Counting {
+    constructed: AtomicU64::new(0),
+    dropped: AtomicU64::new(0),
+    invalid: AtomicU64::new(0),
+};
+
+impl Counting {
+    fn counts(&self) -> [u64; 3] {
+        [&self.constructed, &self.dropped, &self.invalid].map(|c| c.load(Ordering::Relaxed))
+    }
+
+    // Back to nothing, for the demo to run again.
+    fn reset(&self) {
+        for c in [&self.constructed, &self.dropped, &self.invalid] {
+            c.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+crate::registry::demo!("DropObservers: who is told what PrintOnDrop does", tags: ["drop-order"]);
+
+pub fn demo() {
+    // Everything goes to COUNTING and Stdout alike; each thread's own
+    // events also to its own counter.
+    for counting in [&COUNTING, &MAIN_THREAD, &SPAWNED] {
+        counting.reset();
+    }
+    let all = subscribe(&COUNTING);
+    let here = subscribe_here(&MAIN_THREAD);
+    {
+        let v = PrintOnDrop::new("obs_v", 13);
+        let _b = crate::MyBox3::new(PrintOnDrop::new("obs_b", &v));
+        thread::scope(|s| {
+            s.spawn(|| {
+                let _there = subscribe_here(&SPAWNED);
+                drop(PrintOnDrop::new("obs_thread", &v));
+            });
+        });
+    }
+    drop((all, here));
+    println!(
+        "DropObservers: all threads {:?}, main thread {:?}, spawned thread {:?} (constructed, dropped, used after drop)",
+        COUNTING.counts(),
+        MAIN_THREAD.counts(),
+        SPAWNED.counts()
+    );
+    assert_eq!((COUNTING.counts(), MAIN_THREAD.counts(), SPAWNED.counts()), ([3, 3, 0], [2, 2, 0], [1, 1, 0]));
+
+    // Unsubscribed, they hear no more.
+    drop(PrintOnDrop::new("obs_after", 0));
+    assert_eq!(COUNTING.counts(), [3, 3, 0]);
+
+    // quiet_drops mutes Stdout, and only Stdout.
+    quiet_drops(|| drop(PrintOnDrop::new("obs_quiet", 0)));
+}
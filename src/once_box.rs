@@ -0,0 +1,193 @@
+// OnceBox<T> is a MyBox that starts out empty (`None`) and is
+// filled in at most once, on first use, through a shared reference.
+//
+// Whether it owns a `T` is now only known at run time, but the PhantomData
+// <Box<T>> has to describe the worst case: *might* own a `T`, so dropck
+// must assume that Drop may drop one. Drop itself then checks, and
+// frees only if something was ever put there.
+//
+// Values are never replaced once set, so the `&T`s handed out by
+// `get_or_init` stay valid for as long as the OnceBox is borrowed.
+//
+// SyncOnceBox<T> is the threaded variant: an AtomicPtr, with racing
+// initializers settled by a compare-exchange. Every racer builds its own
+// value; one wins, the losers free theirs and use the winner's.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::{recorder, PrintOnDrop};
+
+pub struct OnceBox<T> {
+    ptr: Cell<Option<NonNull<T>>>,
+    _pd: PhantomData<Box<T>>,
+}
+
+// NonNull is !Send, but moving a OnceBox moves (at most) one `T`. It
+// stays !Sync (thanks to the Cell): `get_or_init` on a shared OnceBox
+// would race.
+unsafe impl<T: Send> Send for OnceBox<T> {}
+
+impl<T> OnceBox<T> {
+    pub fn new() -> Self {
+        OnceBox { ptr: Cell::new(None), _pd: PhantomData }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        self.ptr.get().map(|p| unsafe { p.as_ref() })
+    }
+
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        if let Some(p) = self.ptr.get() {
+            return unsafe { p.as_ref() };
+        }
+        let p = NonNull::from(Box::leak(Box::new(f())));
+        // `f` could have called `get_or_init` itself; someone may hold a
+        // `&T` to that value already, so we can't replace it.
+        if self.ptr.get().is_some() {
+            drop(unsafe { Box::from_raw(p.as_ptr()) });
+            panic!("OnceBox: reentrant init");
+        }
+        self.ptr.set(Some(p));
+        unsafe { p.as_ref() }
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for OnceBox<T> {
+    fn drop(&mut self) {
+        if let Some(p) = self.ptr.get() {
+            drop(unsafe { Box::from_raw(p.as_ptr()) });
+        }
+    }
+}
+
+pub struct SyncOnceBox<T> {
+    ptr: AtomicPtr<T>,
+    // AtomicPtr<T> is Send and Sync whatever `T` is. Box<T> brings back
+    // "Send if T: Send, Sync if T: Sync"; sharing a SyncOnceBox also lets
+    // any thread *create* the `T` that the owner will later drop, which
+    // is moving it between threads, so Mutex<T> adds "Sync only if T:
+    // Send" on top. Together: the same bounds as std's OnceLock.
+    _pd: PhantomData<(Box<T>, Mutex<T>)>,
+}
+
+impl<T> SyncOnceBox<T> {
+    pub fn new() -> Self {
+        SyncOnceBox { ptr: AtomicPtr::new(ptr::null_mut()), _pd: PhantomData }
+    }
+
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        let mut p = self.ptr.load(Ordering::Acquire);
+        if p.is_null() {
+            let new = Box::into_raw(Box::new(f()));
+            // Release publishes the value to whoever loads the pointer;
+            // Acquire, on failure, lets us see the winner's.
+            match self.ptr.compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => p = new,
+                Err(winner) => {
+                    drop(unsafe { Box::from_raw(new) });
+                    p = winner;
+                }
+            }
+        }
+        unsafe { &*p }
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for SyncOnceBox<T> {
+    fn drop(&mut self) {
+        let p = *self.ptr.get_mut();
+        if !p.is_null() {
+            drop(unsafe { Box::from_raw(p) });
+        }
+    }
+}
+
+fn f_once() {
+    recorder::take();
+    let never: OnceBox<PrintOnDrop<i32>> = OnceBox::new();
+    assert!(never.get().is_none());
+    drop(never);
+    assert_eq!(recorder::take_drops(), [] as [&str; 0]);
+
+    let b = OnceBox::new();
+    let first = b.get_or_init(|| PrintOnDrop::new("oncebox_1", 1));
+    // Already set: the closure isn't even called.
+    let again = b.get_or_init(|| unreachable!());
+    assert!(ptr::eq(first, again));
+    println!("OnceBox: initialized once, {:?}", b.get().map(|p| p.1));
+    drop(b);
+    assert_eq!(recorder::take_drops(), ["oncebox_1"]);
+
+    // Holding a reference that dies before the OnceBox does: fine, since
+    // Drop is eyepatched and `&String` has nothing to drop.
+    let b;
+    let s = String::from("borrowed");
+    b = OnceBox::new();
+    println!("OnceBox: {}", b.get_or_init(|| &s));
+
+    // A `T` with a Drop of its own, though, might use the reference:
+    //
+    // let b;
+    // let s = String::from("borrowed");
+    // b = OnceBox::new();
+    // b.get_or_init(|| PrintOnDrop::new("oncebox_2", &s)); // won't compile: E0597, `s` does not live long enough
+}
+
+fn f_sync_once() {
+    static BUILT: AtomicUsize = AtomicUsize::new(0);
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted(usize);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let b = SyncOnceBox::new();
+    let seen: Vec<usize> = thread::scope(|s| {
+        let hs: Vec<_> = (0..4)
+            .map(|i| {
+                let b = &b;
+                s.spawn(move || {
+                    b.get_or_init(|| {
+                        BUILT.fetch_add(1, Ordering::Relaxed);
+                        Counted(i)
+                    })
+                    .0
+                })
+            })
+            .collect();
+        hs.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    // Several threads may have built a value, but they all ended up with
+    // the same one, and every loser's was freed.
+    assert!(seen.iter().all(|&i| i == seen[0]));
+    let built = BUILT.load(Ordering::Relaxed);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), built - 1);
+    drop(b);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), built);
+    println!("SyncOnceBox: {} racers built {}, all saw {}", seen.len(), built, seen[0]);
+
+    // OnceBox can't be shared between threads at all:
+    //
+    // let b = OnceBox::new();
+    // thread::scope(|s| {
+    //     s.spawn(|| b.get_or_init(|| 1)); // won't compile: E0277, `Cell<Option<NonNull<i32>>>` cannot be shared between threads safely
+    // });
+}
+
+crate::registry::demo! {
+    "OnceBox: filled in at most once, through a shared reference",
+    tags: ["auto-traits", "threads"],
+}
+
+pub fn demo() {
+    f_once();
+    f_sync_once();
+}
@@ -0,0 +1,116 @@
+// MyBox3 and MyVec are generic, and their drop code has branches on `T`:
+// no element loop without drop glue (`needs_drop`), no allocation for a
+// zero-sized `T`. So each kind of payload takes a different path through
+// the same code, and each path gets checked here, one row of the matrix
+// per payload: plain Copy data, a type with a Drop impl, a zero-sized
+// type with one, a reference, and a PrintOnDrop holding a reference.
+// What each row checks is the same: how many drops every way of getting
+// rid of the values runs, and how many allocations it took.
+//
+// Dropck is per instantiation too, and is checked at compile time, by the
+// functions at the bottom compiling (or, commented out, not).
+
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::alloc_counter::allocations;
+use crate::my_vec::MyVec;
+use crate::{print_on_drop, recorder, MyBox3};
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+// Two bytes, and a Drop impl.
+#[derive(Debug)]
+struct Counted(#[allow(dead_code)] u16);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// No bytes, and a Drop impl all the same.
+struct ZeroSized;
+
+impl Drop for ZeroSized {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// Drops since the last call, counted or recorded.
+fn dropped() -> usize {
+    DROPS.swap(0, Ordering::Relaxed) + recorder::take_drops().len()
+}
+
+// Runs one row: `make` makes a payload, each of which drops `glue` things
+// (0 or 1) when it goes; `allocates` is whether a box of one allocates.
+fn row<T>(what: &str, make: impl Fn() -> T, glue: usize, allocates: bool) {
+    assert_eq!(mem::needs_drop::<T>(), glue > 0, "{}: needs_drop", what);
+    dropped();
+
+    // A box, dropped; and one emptied with into_inner first.
+    let before = allocations();
+    drop(MyBox3::new(make()));
+    assert_eq!(allocations() - before, allocates as usize, "{}: MyBox3 allocations", what);
+    assert_eq!(dropped(), glue, "{}: MyBox3 drops", what);
+    let t = MyBox3::into_inner(MyBox3::new(make()));
+    assert_eq!(dropped(), 0, "{}: MyBox3::into_inner drops", what);
+    drop(t);
+    assert_eq!(dropped(), glue, "{}: the value out of the box", what);
+
+    // A vector of four, one allocation's worth: truncated to two, drained
+    // of one, cleared, and filled again before being dropped.
+    let before = allocations();
+    let mut v = MyVec::new();
+    (0..4).for_each(|_| v.push(make()));
+    assert_eq!(allocations() - before, allocates as usize, "{}: MyVec allocations", what);
+    v.truncate(2);
+    assert_eq!(dropped(), 2 * glue, "{}: MyVec::truncate drops", what);
+    v.drain(..1).for_each(drop);
+    assert_eq!(dropped(), glue, "{}: MyVec::drain drops", what);
+    v.clear();
+    assert_eq!(dropped(), glue, "{}: MyVec::clear drops", what);
+    (0..4).for_each(|_| v.push(make()));
+    drop(v);
+    assert_eq!(dropped(), 4 * glue, "{}: MyVec drops", what);
+}
+
+/// Every payload is checked the same way: a MyBox3 of one, dropped or
+/// emptied; a MyVec of several, truncated, drained, cleared and dropped.
+/// Drop counts scale with the payload's drop glue, zero for Copy data and
+/// references, one per value for the rest; zero-sized payloads never
+/// allocate, whether or not they have a Drop impl.
+pub fn demo() {
+    let counted = Counted(7);
+    row("u64", || 13u64, 0, true);
+    row("Counted", || Counted(13), 1, true);
+    row("ZeroSized", || ZeroSized, 1, false);
+    row("()", || (), 0, false);
+    row("&Counted", || &counted, 0, true);
+    row("PrintOnDrop<&Counted>", || print_on_drop!("pm_ref", &counted), 1, true);
+    drop(counted);
+    assert_eq!(dropped(), 1);
+}
+
+crate::registry::demo!("MyBox3 and MyVec over every kind of payload", tags: ["collections", "dropck", "alloc"]);
+
+// Dropck, per payload: container first is fine for payloads that don't
+// borrow, and for bare references, which have no drop glue (so long as
+// MyBox3 has its eyepatch: see eyepatch.rs)...
+#[cfg(not(feature = "no-eyepatch"))]
+pub fn container_first_refs() {
+    let (_b, mut v, c);
+    c = Counted(1);
+    _b = MyBox3::new(&c);
+    v = MyVec::new();
+    v.push(&c);
+}
+
+// ...but not for a payload whose drop glue could reach the borrow:
+//
+// fn container_first_print_on_drop() {
+//     let (_b, c);
+//     c = Counted(1);
+//     _b = MyBox3::new(print_on_drop!("pm_ref", &c)); // won't compile: E0597, `c` does not live long enough
+// }
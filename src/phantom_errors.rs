@@ -0,0 +1,143 @@
+// Error types that remember, in their type, what was being made: a
+// ParseError<u8> from parsing a u8, a ConversionError<i64, u16> from
+// converting an i64 to a u16. The type parameters are only there for
+// the compiler and for the message, so they live in PhantomData, as
+// `fn() -> T`: the error doesn't own a `T` (and isn't dropped like one),
+// and is Send and Sync whatever `T` is.
+//
+// Derives would put their bounds on `T` and `From` (a ParseError<T> would
+// only be Clone if `T` were), so the impls are written out.
+
+use std::any::type_name;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use lesson::lesson;
+
+pub struct ParseError<T> {
+    input: String,
+    _parsing: PhantomData<fn() -> T>,
+}
+
+impl<T> ParseError<T> {
+    pub fn new(input: &str) -> Self {
+        ParseError { input: input.to_string(), _parsing: PhantomData }
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl<T> Clone for ParseError<T> {
+    fn clone(&self) -> Self {
+        ParseError::new(&self.input)
+    }
+}
+
+impl<T> PartialEq for ParseError<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.input == other.input
+    }
+}
+
+impl<T> fmt::Debug for ParseError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParseError<{}>({:?})", type_name::<T>(), self.input)
+    }
+}
+
+impl<T> fmt::Display for ParseError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "couldn't parse {:?} as a {}", self.input, type_name::<T>())
+    }
+}
+
+impl<T> Error for ParseError<T> {}
+
+/// Parses `s` as a `T`, with an error that says what `T` was.
+pub fn parse<T: FromStr>(s: &str) -> Result<T, ParseError<T>> {
+    s.parse().map_err(|_| ParseError::new(s))
+}
+
+pub struct ConversionError<From, To> {
+    value: String,
+    _converting: PhantomData<fn(From) -> To>,
+}
+
+impl<From, To> Clone for ConversionError<From, To> {
+    fn clone(&self) -> Self {
+        ConversionError { value: self.value.clone(), _converting: PhantomData }
+    }
+}
+
+impl<From, To> PartialEq for ConversionError<From, To> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<From, To> fmt::Debug for ConversionError<From, To> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ConversionError<{}, {}>({})", type_name::<From>(), type_name::<To>(), self.value)
+    }
+}
+
+impl<From, To> fmt::Display for ConversionError<From, To> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} doesn't fit in a {}", type_name::<From>(), self.value, type_name::<To>())
+    }
+}
+
+impl<From, To> Error for ConversionError<From, To> {}
+
+/// Converts `from` to a `To`, with an error that says both types.
+pub fn convert<From: fmt::Display + Copy, To: TryFrom<From>>(from: From) -> Result<To, ConversionError<From, To>> {
+    To::try_from(from).map_err(|_| ConversionError { value: from.to_string(), _converting: PhantomData })
+}
+
+fn is_send_sync<T: Send + Sync>() {}
+
+/// An error type can carry the types it is about in PhantomData, at no
+/// cost: a ParseError<u8> is a String and nothing else, but its Display
+/// (through `std::any::type_name`) says "couldn't parse "300" as a u8",
+/// and the compiler keeps a ParseError<u8> apart from a ParseError<u16>,
+/// so one can't be returned where the other was meant.
+///
+/// PhantomData<fn() -> T> rather than PhantomData<T>: the error never
+/// holds a `T`, so it shouldn't be dropped as if it did, and it stays
+/// Send and Sync even for a `T` that isn't (say, an `Rc`).
+#[lesson(
+    title = "PhantomData in error types: ParseError<T> and ConversionError<From, To>",
+    tags = ["typestate", "auto-traits"],
+    notes = ["A ParseError<u16> won't be accepted as a ParseError<u8>: E0308, mismatched types"],
+)]
+pub fn demo() {
+    let e = parse::<u8>("300").unwrap_err();
+    assert_eq!(e.to_string(), "couldn't parse \"300\" as a u8");
+    assert_eq!(format!("{:?}", e), "ParseError<u8>(\"300\")");
+    assert_eq!(e.input(), "300");
+    assert_eq!(parse::<u16>("300"), Ok(300));
+
+    let e = convert::<i64, u16>(-1).unwrap_err();
+    assert_eq!(e.to_string(), "i64 -1 doesn't fit in a u16");
+    assert_eq!(format!("{:?}", e.clone()), "ConversionError<i64, u16>(-1)");
+    assert_eq!(convert::<i64, u16>(65535), Ok(65535));
+
+    // As boxed errors, they still say what they were about.
+    let errors: Vec<Box<dyn Error>> = vec![Box::new(parse::<bool>("yes").unwrap_err()), Box::new(e)];
+    for e in &errors {
+        println!("phantom_errors: {}", e);
+    }
+
+    // Whatever the types, the errors are Send and Sync: the PhantomData
+    // is of a function pointer, which is both.
+    is_send_sync::<ParseError<std::rc::Rc<u8>>>();
+    is_send_sync::<ConversionError<*const u8, std::cell::Cell<u8>>>();
+
+    // And they are kept apart:
+    //
+    // let e: ParseError<u8> = parse::<u16>("x").unwrap_err(); // won't compile: E0308, mismatched types
+}
@@ -0,0 +1,176 @@
+// MyBox3, MyVec, MyArc and the rest all get the same three things right
+// by hand: a PhantomData<T> next to the raw pointer ("owns a `T`"), an
+// eyepatched Drop ("but only drops it"), and a check that the PhantomData
+// costs nothing. `phantom_owner!` writes them from the struct's real
+// fields and the destructor's body:
+//
+//     phantom_owner! {
+//         pub struct OwnedSlice<T> {
+//             ptr: NonNull<T>,
+//             len: usize,
+//         }
+//
+//         unsafe impl Drop {
+//             fn drop(&mut self) { ... }
+//         }
+//     }
+//
+// The `unsafe` is the eyepatch's promise, made by whoever writes the
+// body: it may drop the `T`s it owns, but not otherwise touch them, since
+// what they borrow may already be gone. The struct gets a `_owns` field
+// and an unsafe `from_raw_parts` taking the other fields, whose caller
+// promises that those fields really do own the `T`s.
+//
+// Only for a single type parameter, and (for #[may_dangle]) it needs
+// `#![feature(dropck_eyepatch)]` in the crate using it.
+
+use std::alloc::{self, Layout};
+use std::ptr::{self, NonNull};
+
+use lesson::lesson;
+
+use crate::{print_on_drop, recorder};
+
+macro_rules! phantom_owner {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident<$t:ident> {
+            $($fvis:vis $field:ident: $fty:ty),* $(,)?
+        }
+
+        unsafe impl Drop {
+            fn drop(&mut $self:ident) $body:block
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name<$t> {
+            $($fvis $field: $fty,)*
+            _owns: ::std::marker::PhantomData<$t>,
+        }
+
+        impl<$t> $name<$t> {
+            /// # Safety
+            ///
+            /// The fields must own the `T`s that the Drop impl drops.
+            $vis unsafe fn from_raw_parts($($field: $fty),*) -> Self {
+                $name { $($field,)* _owns: ::std::marker::PhantomData }
+            }
+        }
+
+        unsafe impl<#[may_dangle] $t> Drop for $name<$t> {
+            fn drop(&mut $self) $body
+        }
+
+        // The same struct without the PhantomData, which must be no
+        // smaller and no less aligned.
+        const _: () = {
+            #[allow(dead_code)]
+            struct Bare<$t> {
+                $($field: $fty,)*
+            }
+            assert!(size_of::<$name<u8>>() == size_of::<Bare<u8>>());
+            assert!(size_of::<$name<u64>>() == size_of::<Bare<u64>>());
+            assert!(align_of::<$name<u8>>() == align_of::<Bare<u8>>());
+            assert!(align_of::<$name<u64>>() == align_of::<Bare<u64>>());
+        };
+    };
+}
+
+phantom_owner! {
+    /// A fixed-length, heap-allocated `[T]`.
+    pub struct OwnedSlice<T> {
+        ptr: NonNull<T>,
+        len: usize,
+    }
+
+    unsafe impl Drop {
+        fn drop(&mut self) {
+            // Safety: only drops the `T`s, in place, then frees them.
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+                let layout = Layout::array::<T>(self.len).unwrap();
+                if layout.size() != 0 {
+                    alloc::dealloc(self.ptr.as_ptr().cast(), layout);
+                }
+            }
+        }
+    }
+}
+
+impl<T> OwnedSlice<T> {
+    pub fn new(items: impl ExactSizeIterator<Item = T>) -> Self {
+        let len = items.len();
+        let layout = Layout::array::<T>(len).expect("capacity overflow");
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            let Some(p) = NonNull::new(unsafe { alloc::alloc(layout) }) else {
+                alloc::handle_alloc_error(layout);
+            };
+            p.cast()
+        };
+        // Should the iterator panic part way, the `T`s written so far
+        // leak, and so does the allocation; that's safe.
+        let mut written = 0;
+        for t in items.take(len) {
+            unsafe { ptr.add(written).write(t) };
+            written += 1;
+        }
+        assert_eq!(written, len, "iterator was shorter than it said");
+        unsafe { OwnedSlice::from_raw_parts(ptr, len) }
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { &*ptr::slice_from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+fn covariant<'a>(s: OwnedSlice<&'static str>) -> OwnedSlice<&'a str> {
+    s
+}
+
+/// An OwnedSlice, written with phantom_owner!, holding PrintOnDrops that
+/// borrow from a local. Dropping it drops them, in order, in place.
+///
+/// The eyepatch lets an OwnedSlice<&str> outlive the String its elements
+/// point into, since dropping a `&str` does nothing with it. The
+/// PhantomData still tells dropck that the slice drops its elements, so
+/// the same with elements that use what they borrow when dropped (as
+/// PrintOnDrop does) is rejected, just as it is for MyBox3.
+#[lesson(
+    title = "phantom_owner!: PhantomData, eyepatch and size checks in one",
+    tags = ["dropck", "macros"],
+    notes = [
+        "`let (_words, text)` compiles: a `&str` may dangle when dropped",
+        "`let (_s, v2)` won't compile: a PrintOnDrop<&_> may not",
+    ],
+)]
+pub fn demo() {
+    let v = print_on_drop!("os_v", 13);
+    recorder::take();
+    let s = OwnedSlice::new((0..3).map(|i| print_on_drop!(format!("os_{}", i), &v)));
+    let names: Vec<_> = s.as_slice().iter().map(|p| p.name()).collect();
+    println!("OwnedSlice: {:?}", names);
+    drop(s);
+    assert_eq!(recorder::take_drops(), ["os_0", "os_1", "os_2"]);
+
+    // Thanks to the eyepatch, an OwnedSlice<&str> may be dropped after
+    // the String its elements point into, since dropping a `&str` does
+    // nothing with it...
+    let (_words, text);
+    text = String::from("phantom owner");
+    _words = OwnedSlice::new(text.split(' ').collect::<Vec<_>>().into_iter());
+
+    // ...but, because of the PhantomData, dropck still knows that it
+    // drops its elements, which for a PrintOnDrop<&_> does mean using
+    // what they borrow. So this is rejected, as it is for MyBox3:
+    //
+    // let (_s, v2);
+    // print_on_drop!(v2 = 13);
+    // _s = OwnedSlice::new(std::iter::once(print_on_drop!("os_bad", &v2))); // won't compile: `v2` does not live long enough
+
+    let words = covariant(OwnedSlice::new(["a", "b"].into_iter()));
+    assert_eq!(words.as_slice(), ["a", "b"]);
+    let units = OwnedSlice::<()>::new(std::iter::repeat_n((), 5));
+    assert_eq!(units.as_slice().len(), 5);
+}
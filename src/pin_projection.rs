@@ -0,0 +1,130 @@
+// Pin projection by hand. `Node` has two fields:
+//
+// - `anchor`, an `Anchor` that points into itself and so must never move
+//   once initialized. It contains a PhantomPinned, which makes it (and
+//   hence Node) !Unpin: once pinned, safe code can't get a `&mut` to it.
+//   Projection keeps it pinned: `Pin<&mut Node>` -> `Pin<&mut Anchor>`.
+//   That makes it a *structural* field.
+//
+// - `label`, an ordinary PrintOnDrop. Nothing cares where it lives, so
+//   projection hands out a plain `&mut`, and callers may move it out
+//   (say, with mem::replace). That makes it *non-structural*.
+//
+// Each field must be one or the other, consistently. Pin also makes a
+// promise about dropping: the memory of a pinned value isn't reused
+// until its destructor has run. Anchor's Drop checks that it really is
+// still where it was initialized.
+
+use std::marker::PhantomPinned;
+use std::mem;
+use std::pin::{pin, Pin};
+use std::ptr::{self, NonNull};
+
+use crate::{recorder, MyBox3, PrintOnDrop};
+
+pub struct Anchor {
+    data: String,
+    // Points at `data` once initialized.
+    this: Option<NonNull<String>>,
+    _pin: PhantomPinned,
+}
+
+impl Anchor {
+    fn new(data: &str) -> Self {
+        Anchor { data: data.to_string(), this: None, _pin: PhantomPinned }
+    }
+
+    fn init(self: Pin<&mut Self>) {
+        // Safety: we don't move out of the `&mut`.
+        let this = unsafe { self.get_unchecked_mut() };
+        this.this = Some(NonNull::from(&this.data));
+    }
+
+    fn get(self: Pin<&Self>) -> &str {
+        let this = self.this.expect("uninitialized Anchor");
+        // Safety: pinned, so `data` is still where `this` says.
+        unsafe { this.as_ref() }
+    }
+}
+
+impl Drop for Anchor {
+    fn drop(&mut self) {
+        // Drop takes `&mut self` even for pinned values: it must act as if
+        // `self` were pinned, which is why it mustn't move anything out.
+        assert!(self.this.is_none_or(|p| ptr::eq(p.as_ptr(), &self.data)), "Anchor moved after init");
+        println!("drop Anchor({:?})", self.data);
+    }
+}
+
+pub struct Node {
+    anchor: Anchor,
+    label: PrintOnDrop<i32>,
+}
+
+impl Node {
+    pub fn new(data: &str, label: &'static str) -> Self {
+        Node { anchor: Anchor::new(data), label: PrintOnDrop::new(label, 0) }
+    }
+
+    fn anchor(self: Pin<&mut Self>) -> Pin<&mut Anchor> {
+        // Safety: `anchor` is structural: we never hand out an unpinned
+        // `&mut` to it, and Node has no Drop impl that could move it.
+        unsafe { self.map_unchecked_mut(|n| &mut n.anchor) }
+    }
+
+    fn label(self: Pin<&mut Self>) -> &mut PrintOnDrop<i32> {
+        // Safety: `label` is not structural; nothing relies on its address.
+        unsafe { &mut self.get_unchecked_mut().label }
+    }
+}
+
+fn f_stack_pinned() {
+    let mut node = pin!(Node::new("on the stack", "pin_label_1"));
+    node.as_mut().anchor().init();
+    println!("Anchor: {:?}", node.as_mut().anchor().into_ref().get());
+
+    // Move a non-structural field out of a pinned value: fine.
+    let old = mem::replace(node.as_mut().label(), PrintOnDrop::new("pin_label_2", 1));
+    drop(old);
+
+    // Moving the structural field out doesn't compile:
+    //
+    // let a = mem::replace(&mut *node.as_mut().anchor(), Anchor::new("x")); // won't compile: cannot borrow data in dereference of `Pin<&mut Anchor>` as mutable
+}
+
+fn f_boxed() {
+    let mut node = Box::pin(Node::new("on the heap", "pin_label_3"));
+    node.as_mut().anchor().init();
+    // The Box may move; what it points to doesn't.
+    let mut moved = node;
+    println!("Anchor (box moved): {:?}", moved.as_mut().anchor().into_ref().get());
+}
+
+fn f_my_box3() {
+    let mut node = MyBox3::pin(Node::new("in a MyBox3", "pin_label_4"));
+    node.as_mut().anchor().init();
+    let mut moved = node;
+    println!("Anchor (MyBox3 moved): {:?}", moved.as_mut().anchor().into_ref().get());
+    // Dropping it is the real test: Anchor's Drop checks it wasn't moved.
+
+    // Getting a `&mut Node` (to move out of it) through the pin doesn't
+    // compile, and neither does unwrapping it:
+    //
+    // let mut other = MyBox3::pin(Node::new("other", "pin_label_5"));
+    // mem::swap(&mut *moved, &mut *other); // won't compile: E0596, cannot borrow data in dereference of `Pin<MyBox3<pin_projection::Node>>` as mutable
+    // let node = Pin::into_inner(moved); // won't compile: E0277, `PhantomPinned` cannot be unpinned
+}
+
+crate::registry::demo!("Pin projection by hand", tags: ["pin"]);
+
+pub fn demo() {
+    recorder::take();
+    f_stack_pinned();
+    // Fields drop in declaration order: anchor first (not recorded),
+    // then the label.
+    assert_eq!(recorder::take_drops(), ["pin_label_1", "pin_label_2"]);
+    f_boxed();
+    assert_eq!(recorder::take_drops(), ["pin_label_3"]);
+    f_my_box3();
+    assert_eq!(recorder::take_drops(), ["pin_label_4"]);
+}
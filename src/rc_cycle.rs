@@ -0,0 +1,80 @@
+// Everything so far has been about destructors running too *early*. Here
+// is the opposite: two `Rc`s pointing at each other keep each other's
+// count above zero forever, so neither destructor ever runs and the
+// memory is never freed. That is not unsafe (leaking is safe in Rust;
+// see `mem::forget`), which is exactly why unsafe code must not rely on
+// destructors running.
+//
+// Making one of the two edges a `Weak` breaks the cycle.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use crate::alloc_counter::live_bytes;
+use crate::{recorder, PrintOnDrop};
+
+pub(crate) struct Node {
+    _payload: PrintOnDrop<i32>,
+    pub(crate) next: RefCell<Option<Rc<Node>>>,
+    pub(crate) prev: RefCell<Weak<Node>>,
+}
+
+impl Node {
+    pub(crate) fn new(name: &'static str) -> Rc<Node> {
+        Rc::new(Node {
+            _payload: PrintOnDrop::new(name, 13),
+            next: RefCell::new(None),
+            prev: RefCell::new(Weak::new()),
+        })
+    }
+}
+
+// Runs `f`, then reports which values it dropped and how many bytes it
+// left allocated.
+fn observe(f: impl FnOnce()) -> (Vec<Cow<'static, str>>, usize) {
+    recorder::take();
+    let before = live_bytes();
+    f();
+    let leaked = live_bytes() - before;
+    (recorder::take_drops(), leaked)
+}
+
+fn f_strong_cycle() {
+    let (drops, leaked) = observe(|| {
+        let a = Node::new("rc_a");
+        let b = Node::new("rc_b");
+        *a.next.borrow_mut() = Some(b.clone());
+        *b.next.borrow_mut() = Some(a.clone());
+        println!("strong cycle: a={} b={}", Rc::strong_count(&a), Rc::strong_count(&b));
+        // Dropping `a` and `b` here only brings each count down to 1.
+    });
+    println!("strong cycle: dropped {:?}, leaked {} bytes", drops, leaked);
+    assert!(drops.is_empty());
+    assert_eq!(leaked, 2 * size_of::<Node>() + 4 * size_of::<usize>());
+}
+
+fn f_weak_back_edge() {
+    let (drops, leaked) = observe(|| {
+        let a = Node::new("rc_a");
+        let b = Node::new("rc_b");
+        *a.next.borrow_mut() = Some(b.clone());
+        *b.prev.borrow_mut() = Rc::downgrade(&a);
+        println!("weak back edge: a={} b={}", Rc::strong_count(&a), Rc::strong_count(&b));
+        // `b` goes first, but `a` still holds it; when `a` goes, its count
+        // reaches zero, and dropping it releases `b` in turn.
+    });
+    println!("weak back edge: dropped {:?}, leaked {} bytes", drops, leaked);
+    assert_eq!(drops, ["rc_a", "rc_b"]);
+    assert_eq!(leaked, 0);
+}
+
+crate::registry::demo! {
+    "Rc cycles: destructors that never run, and breaking them with Weak",
+    tags: ["drop-order"],
+}
+
+pub fn demo() {
+    f_strong_cycle();
+    f_weak_back_edge();
+}
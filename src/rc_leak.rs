@@ -0,0 +1,46 @@
+// rc_cycle.rs's leak, as the allocation tracker sees it. Each half of the
+// demo is attributed to a name of its own (`set_demo`), so the tracker can
+// say which blocks the strong cycle left behind, how big they are, and
+// that the Weak back edge left none; the end-of-run report (with
+// `instrumented-alloc`) lists both.
+
+use std::rc::Rc;
+
+use crate::alloc_tracker::{leaked, set_demo};
+use crate::rc_cycle::Node;
+use crate::recorder;
+
+fn strong_cycle() {
+    let a = Node::new("rl_a");
+    let b = Node::new("rl_b");
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.next.borrow_mut() = Some(a.clone());
+}
+
+fn weak_back_edge() {
+    let a = Node::new("rl_a");
+    let b = Node::new("rl_b");
+    *a.next.borrow_mut() = Some(b.clone());
+    *b.prev.borrow_mut() = Rc::downgrade(&a);
+}
+
+crate::registry::demo! {
+    "Rc cycles, as leaks in the allocation tracker's report",
+    tags: ["drop-order", "alloc"],
+}
+
+pub fn demo() {
+    recorder::take();
+    set_demo("rc_leak_strong");
+    strong_cycle();
+    set_demo("rc_leak_weak");
+    weak_back_edge();
+    set_demo("rc_leak");
+    assert_eq!(recorder::take_drops(), ["rl_a", "rl_b"]);
+
+    // Two blocks, an Rc's counts and a Node each, never freed.
+    let (blocks, bytes) = leaked("rc_leak_strong");
+    println!("rc_leak: strong cycle leaked {} blocks, {} bytes", blocks, bytes);
+    assert_eq!((blocks, bytes), (2, 2 * (size_of::<Node>() + 2 * size_of::<usize>())));
+    assert_eq!(leaked("rc_leak_weak"), (0, 0));
+}
@@ -0,0 +1,188 @@
+// Records what PrintOnDrop (and ScopeGuard, and DropTrace types) do, so
+// demos can check the order in which things were dropped (or that they
+// weren't dropped at all) rather than leaving that to whoever reads the
+// output. Scope markers (see scope_guard.rs) record where scopes begin
+// and end, for an observer to show the drops inside them as such.
+//
+// Events are kept per thread, in a ring allocated once, when the thread
+// first records something, and never grown; recording an event never
+// allocates, so it can't disturb demos that count allocations. What
+// happens when the ring is full is up to the Overflow policy.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::panic;
+
+use crate::alloc_counter::allocations;
+use crate::observer;
+use crate::unwind_safe::quietly;
+use crate::{PrintOnDrop, State};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A PrintOnDrop was dropped: its name, id and state.
+    Drop(Cow<'static, str>, u64, State),
+    /// A ScopeGuard ran its closure.
+    Guard(Cow<'static, str>),
+    /// A value of a `#[derive(DropTrace)]` type was dropped: its type.
+    Traced(Cow<'static, str>),
+    /// A scope marker was declared: its name.
+    Enter(Cow<'static, str>),
+    /// That scope marker was dropped, so its scope has ended.
+    Exit(Cow<'static, str>),
+}
+
+/// What `record` does with an event that doesn't fit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    /// Make room by forgetting the oldest event.
+    DropOldest,
+    /// Forget the new event.
+    DropNewest,
+    /// Panic (and so abort, if the event came from a destructor run while
+    /// unwinding).
+    Panic,
+}
+
+struct Ring {
+    events: VecDeque<Event>,
+    capacity: usize,
+    overflow: Overflow,
+    // Events forgotten since the last `take`.
+    lost: usize,
+}
+
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+thread_local! {
+    static RING: RefCell<Ring> = const {
+        RefCell::new(Ring {
+            events: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            overflow: Overflow::DropOldest,
+            lost: 0,
+        })
+    };
+}
+
+pub fn record(e: Event) {
+    observer::publish(|o| o.on_record(&e));
+    // During thread teardown the log may already be gone; such events are
+    // simply lost.
+    let _ = RING.try_with(|ring| {
+        let mut ring = ring.borrow_mut();
+        let ring = &mut *ring;
+        if ring.events.capacity() < ring.capacity {
+            ring.events.reserve_exact(ring.capacity);
+        }
+        if ring.events.len() == ring.capacity {
+            match ring.overflow {
+                Overflow::DropOldest => drop(ring.events.pop_front()),
+                Overflow::DropNewest => {
+                    ring.lost += 1;
+                    return;
+                }
+                Overflow::Panic => panic!("recorder: more than {} events", ring.capacity),
+            }
+            ring.lost += 1;
+        }
+        ring.events.push_back(e);
+    });
+}
+
+/// Sets how many events this thread keeps, reallocating the ring (and
+/// forgetting the oldest events, if they no longer fit) now rather than
+/// on some later `record`.
+pub fn set_capacity(capacity: usize) {
+    RING.with(|ring| {
+        let mut ring = ring.borrow_mut();
+        while ring.events.len() > capacity {
+            ring.events.pop_front();
+            ring.lost += 1;
+        }
+        let len = ring.events.len();
+        ring.events.shrink_to(capacity);
+        ring.events.reserve_exact(capacity - len);
+        ring.capacity = capacity;
+    });
+}
+
+pub fn set_overflow(overflow: Overflow) {
+    RING.with(|ring| ring.borrow_mut().overflow = overflow);
+}
+
+/// How many events have been forgotten since the last `take`.
+pub fn lost() -> usize {
+    RING.with(|ring| ring.borrow().lost)
+}
+
+/// Removes and returns everything recorded so far on this thread. The
+/// ring itself stays allocated.
+pub fn take() -> Vec<Event> {
+    RING.with(|ring| {
+        let mut ring = ring.borrow_mut();
+        ring.lost = 0;
+        ring.events.drain(..).collect()
+    })
+}
+
+/// The names of the values (and guards, and the types of DropTrace values)
+/// dropped since the last `take`, in drop order. Scopes are left out.
+pub fn take_drops() -> Vec<Cow<'static, str>> {
+    take()
+        .into_iter()
+        .filter_map(|e| match e {
+            Event::Drop(name, ..) | Event::Guard(name) | Event::Traced(name) => Some(name),
+            Event::Enter(_) | Event::Exit(_) => None,
+        })
+        .collect()
+}
+
+/// The ids of the PrintOnDrops dropped since the last `take`, in drop
+/// order.
+pub fn take_drop_ids() -> Vec<u64> {
+    take()
+        .into_iter()
+        .filter_map(|e| match e {
+            Event::Drop(_, id, _) => Some(id),
+            Event::Guard(_) | Event::Traced(_) | Event::Enter(_) | Event::Exit(_) => None,
+        })
+        .collect()
+}
+
+crate::registry::demo!("The drop recorder: capacity and overflow policies", tags: ["drop-order"]);
+
+pub fn demo() {
+    let names = |n: usize| -> Vec<String> { (0..n).map(|i| format!("rec_{}", i)).collect() };
+    let values: Vec<_> = names(5).into_iter().map(|name| PrintOnDrop::new(name, 13)).collect();
+
+    // Room for three: the first two drops are forgotten to make room for
+    // the last three, and none of it allocates.
+    set_capacity(3);
+    take();
+    let before = allocations();
+    drop(values);
+    assert_eq!(allocations() - before, 0);
+    assert_eq!(lost(), 2);
+    assert_eq!(take_drops(), ["rec_2", "rec_3", "rec_4"]);
+
+    // Or keep the first three instead.
+    set_overflow(Overflow::DropNewest);
+    drop(names(5).into_iter().map(|name| PrintOnDrop::new(name, 13)).collect::<Vec<_>>());
+    assert_eq!(lost(), 2);
+    assert_eq!(take_drops(), ["rec_0", "rec_1", "rec_2"]);
+
+    // Or insist that nothing be lost.
+    set_overflow(Overflow::Panic);
+    for name in ["rec_a", "rec_b", "rec_c"] {
+        record(Event::Guard(Cow::Borrowed(name)));
+    }
+    let r = quietly(|| panic::catch_unwind(|| record(Event::Guard(Cow::Borrowed("rec_d")))));
+    assert!(r.is_err());
+    assert_eq!(lost(), 0);
+    assert_eq!(take_drops(), ["rec_a", "rec_b", "rec_c"]);
+
+    set_overflow(Overflow::DropOldest);
+    set_capacity(DEFAULT_CAPACITY);
+}
@@ -0,0 +1,159 @@
+// The negative space: designs this crate teaches against, written out in
+// full. Each of them compiles, and passes every check the compiler makes,
+// and each has a use that is undefined behaviour, found by nothing short
+// of a run-time checker. They are what MyBox3, MyVec and MyCellBox are
+// careful not to be:
+//
+//   - Dangler: an eyepatched Drop without a PhantomData<T> (MyBox2).
+//   - Peeker: an eyepatch on a Drop that does look at its `T`.
+//   - CovariantCell: interior mutability, left covariant.
+//
+// Each `ub_*` case is a canary: run one at a time, picked by name, every
+// one must be reported, by Miri,
+//
+//     for ub in dangler peeker covariant_cell; do
+//         MIRIFLAGS=-Zmiri-env-forward=REDTEAM_UB REDTEAM_UB=$ub \
+//             cargo +nightly miri run --features redteam -- redteam && echo "$ub: NOT caught"
+//     done
+//
+// or by AddressSanitizer, each being a use after free (of the heap) or
+// after scope (of a stack slot):
+//
+//     RUSTFLAGS=-Zsanitizer=address REDTEAM_UB=$ub \
+//         cargo +nightly run --features redteam --target x86_64-unknown-linux-gnu -- redteam
+//
+// Without REDTEAM_UB, the demo only shows the correct use of each. Behind
+// the off-by-default `redteam` feature, so that none of this is built by
+// accident.
+
+use std::alloc::{self, dealloc, Layout};
+use std::env;
+use std::fmt::Debug;
+use std::ptr::{self, NonNull};
+
+use crate::{print_on_drop, recorder};
+
+// What MyBox2 gets wrong: it drops a `T`, but holds nothing that says it
+// owns one, so dropck lets the `T` borrow something that is already gone.
+pub struct Dangler<T> {
+    v: NonNull<T>,
+}
+
+impl<T> Dangler<T> {
+    pub fn new(t: T) -> Self {
+        unsafe {
+            let p = alloc::alloc(Layout::new::<T>()) as *mut T;
+            ptr::write(p, t);
+            Dangler { v: NonNull::new_unchecked(p) }
+        }
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for Dangler<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.v.as_ptr());
+            dealloc(self.v.as_ptr() as *mut u8, Layout::new::<T>());
+        }
+    }
+}
+
+// An eyepatch is a promise not to look at `T` in Drop; this one breaks
+// it, and a PhantomData couldn't help: `&String` has no drop glue, so
+// dropck has nothing to check.
+pub struct Peeker<T: Debug>(pub T);
+
+unsafe impl<#[may_dangle] T: Debug> Drop for Peeker<T> {
+    fn drop(&mut self) {
+        println!("redteam: peeking at {:?}", self.0);
+    }
+}
+
+// MyCellBox without the UnsafeCell: set through a shared reference, and
+// covariant, so a CovariantCell<&'static str> may be used as a
+// CovariantCell<&'short str>, have a short-lived reference put in it,
+// and still be read as a `&'static str`.
+pub struct CovariantCell<T> {
+    v: NonNull<T>,
+}
+
+impl<T: Copy> CovariantCell<T> {
+    pub fn new(t: T) -> Self {
+        CovariantCell { v: NonNull::from(Box::leak(Box::new(t))) }
+    }
+
+    pub fn set(&self, t: T) {
+        unsafe { *self.v.as_ptr() = t };
+    }
+
+    pub fn get(&self) -> T {
+        unsafe { *self.v.as_ptr() }
+    }
+}
+
+impl<T> Drop for CovariantCell<T> {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(self.v.as_ptr())) };
+    }
+}
+
+fn ub_dangler() {
+    let _d;
+    let s;
+    s = String::from("freed");
+    // The PrintOnDrop's Drop prints `&s`, after `s` has been freed.
+    _d = Dangler::new(print_on_drop!("rt_dangler", &s));
+}
+
+fn ok_dangler() {
+    let s = String::from("alive");
+    let _d = Dangler::new(print_on_drop!("rt_dangler", &s));
+}
+
+fn ub_peeker() {
+    let _p;
+    let s;
+    s = String::from("freed");
+    _p = Peeker(&s);
+}
+
+fn ok_peeker() {
+    let s = String::from("alive");
+    let _p = Peeker(&s);
+}
+
+fn ub_covariant_cell() {
+    let cell: CovariantCell<&'static str> = CovariantCell::new("static");
+    {
+        let shorter: &CovariantCell<&str> = &cell;
+        let s = String::from("freed");
+        shorter.set(&s);
+    }
+    let read: &'static str = cell.get();
+    println!("redteam: read {:?}", read);
+}
+
+fn ok_covariant_cell() {
+    let cell: CovariantCell<&'static str> = CovariantCell::new("static");
+    cell.set("also static");
+    assert_eq!(cell.get(), "also static");
+}
+
+crate::registry::demo!("Red team: sound-looking designs with UB, for Miri and ASan to catch", tags: ["unsafe", "dropck"]);
+
+pub fn demo() {
+    recorder::take();
+    ok_dangler();
+    ok_peeker();
+    ok_covariant_cell();
+    assert_eq!(recorder::take_drops(), ["rt_dangler"]);
+
+    if let Ok(which) = env::var("REDTEAM_UB") {
+        match which.as_str() {
+            "dangler" => ub_dangler(),
+            "peeker" => ub_peeker(),
+            "covariant_cell" => ub_covariant_cell(),
+            _ => panic!("REDTEAM_UB: no such case {:?}", which),
+        }
+    }
+}
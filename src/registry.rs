@@ -0,0 +1,408 @@
+// Every module with a demo describes it with `demo!`, next to its
+// `pub fn demo()`:
+//
+//     crate::registry::demo!("Slab: stable keys and reused slots", tags: ["dropck", "collections"]);
+//
+// which defines the module's DEMO. `all` lists them in the order they
+// run; main picks from it by name or tag (`cargo run -- dropck slab`), or
+// prints it (`cargo run -- --list`).
+//
+// A demo may also give the drops it leaves in the recorder, oldest first
+// (`drops: ["a", "b"]`), which `run` then checks, for demos that don't
+// already check their own. Where the order isn't up to the demo (a
+// HashMap's, say), `drops_any_order: [...]` checks only that the same
+// drops were left, each as many times as it's listed.
+//
+// Demos with more to say use `#[lesson(...)]` (from the lesson crate in
+// this workspace) on `demo` instead, which also attaches a Lesson: the
+// function's doc comment, and notes on what should and shouldn't compile.
+// `cargo run -- --explain <name>` prints it.
+//
+// An unsound demo (see dropck_pairs.rs) is only run when named on the
+// command line, or with `--unsound`, and then in a child process: the
+// program runs itself again, with DEMO_CHILD set, to run just that demo,
+// and reports how the child exited, so that whatever the UB does can't
+// take the rest of the run with it. `--isolate` does the same for every
+// demo. Demos that need to watch a process abort, or hang, use
+// `run_child` to the same end. It captures the child's output, kills it
+// if it runs past a timeout, and says what its exit status means: a
+// panic, an abort, a crash.
+
+use std::env;
+use std::fmt;
+use std::io::{self, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::recorder;
+use crate::trace::Trace;
+
+// Set in the environment of every child process started by `run_child`.
+const DEMO_CHILD: &str = "DEMO_CHILD";
+
+// How long an unsound (or isolated) demo gets to run in its child.
+const CHILD_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct Demo {
+    // The module's path; see `name`.
+    pub path: &'static str,
+    pub description: &'static str,
+    pub tags: &'static [&'static str],
+    pub expected_drops: Option<Expected>,
+    pub run: fn(),
+    pub lesson: Option<&'static Lesson>,
+    pub unsound: bool,
+}
+
+pub struct Lesson {
+    // The doc comment, a line at a time.
+    pub explanation: &'static [&'static str],
+    pub notes: &'static [&'static str],
+}
+
+impl Lesson {
+    /// The explanation, with its lines joined up into paragraphs.
+    pub fn paragraphs(&self) -> Vec<String> {
+        let mut paragraphs = vec![String::new()];
+        for line in self.explanation {
+            let line = line.strip_prefix(' ').unwrap_or(line);
+            let last = paragraphs.last_mut().unwrap();
+            if line.is_empty() {
+                if !last.is_empty() {
+                    paragraphs.push(String::new());
+                }
+            } else {
+                if !last.is_empty() {
+                    last.push(' ');
+                }
+                last.push_str(line);
+            }
+        }
+        paragraphs.retain(|p| !p.is_empty());
+        paragraphs
+    }
+}
+
+/// What a demo leaves in the recorder.
+#[derive(Clone, Copy, Debug)]
+pub enum Expected {
+    /// These drops, in this order.
+    InOrder(&'static [&'static str]),
+    /// These drops, in whatever order: the same as a multiset.
+    AnyOrder(&'static [&'static str]),
+}
+
+impl Expected {
+    pub fn drops(&self) -> &'static [&'static str] {
+        match *self {
+            Expected::InOrder(drops) | Expected::AnyOrder(drops) => drops,
+        }
+    }
+
+    pub fn in_order(&self) -> bool {
+        matches!(self, Expected::InOrder(_))
+    }
+
+    pub fn matches(&self, drops: &[&str]) -> bool {
+        match *self {
+            Expected::InOrder(expected) => drops == expected,
+            Expected::AnyOrder(expected) => {
+                let (mut drops, mut expected) = (drops.to_vec(), expected.to_vec());
+                drops.sort_unstable();
+                expected.sort_unstable();
+                drops == expected
+            }
+        }
+    }
+}
+
+impl Demo {
+    /// The module's name, which is also the demo's.
+    pub fn name(&self) -> &'static str {
+        self.path.rsplit("::").next().unwrap()
+    }
+
+    pub fn matches(&self, arg: &str) -> bool {
+        self.name() == arg || self.tags.contains(&arg)
+    }
+
+    pub fn explain(&self) {
+        println!("{}: {}", self.name(), self.description);
+        let Some(lesson) = self.lesson else {
+            return;
+        };
+        for p in lesson.paragraphs() {
+            println!("\n{}", p);
+        }
+        if !lesson.notes.is_empty() {
+            println!();
+            for note in lesson.notes {
+                println!("- {}", note);
+            }
+        }
+    }
+
+    pub fn run(&self) {
+        if self.unsound && !in_child() {
+            return self.run_in_child();
+        }
+        self.run_here();
+    }
+
+    /// Runs the demo in this process, sound or not, and returns what it
+    /// left in the recorder, having checked that against `expected_drops`.
+    pub fn run_here(&self) -> Trace {
+        #[cfg(feature = "instrumented-alloc")]
+        crate::alloc_tracker::set_demo(self.name());
+        recorder::take();
+        (self.run)();
+        let trace = Trace::take(self.name());
+        if let Some(expected) = self.expected_drops {
+            let drops = trace.drops();
+            assert!(expected.matches(&drops), "drops left by {}: {:?}, expected {:?}", self.name(), drops, expected);
+        }
+        trace
+    }
+
+    /// Runs the demo in a child process, passing on what it printed, and
+    /// says how that ended.
+    pub fn run_in_child(&self) {
+        let why = if self.unsound { "unsound" } else { "isolated" };
+        println!("{}: {}, running in a child process", self.name(), why);
+        match run_child(self.name(), &[], CHILD_TIMEOUT) {
+            Ok(child) if crate::normalize::output_normalized() => {
+                print!("{}", crate::normalize::output(self, &child.stdout));
+                eprint!("{}", crate::normalize::text(&child.stderr));
+                println!("{}: child {}", self.name(), child.exit);
+            }
+            Ok(child) => {
+                print!("{}", child.stdout);
+                eprint!("{}", child.stderr);
+                println!("{}: child {}", self.name(), child.exit);
+            }
+            Err(e) => println!("{}: couldn't start a child process: {}", self.name(), e),
+        }
+    }
+}
+
+/// Whether this is a child process started by `run_child`, which runs
+/// one demo and nothing else.
+pub fn in_child() -> bool {
+    env::var_os(DEMO_CHILD).is_some()
+}
+
+/// How a child process started by `run_child` ended.
+#[derive(Debug)]
+pub enum ChildExit {
+    Exited(ExitStatus),
+    /// Still running at the timeout, and killed.
+    TimedOut,
+}
+
+impl ChildExit {
+    /// The signal that ended the child, if one did (on Unix).
+    pub fn signal(&self) -> Option<i32> {
+        #[cfg(unix)]
+        if let ChildExit::Exited(status) = self {
+            use std::os::unix::process::ExitStatusExt;
+            return status.signal();
+        }
+        None
+    }
+}
+
+// What became of it: 101 is the status a panic on the main thread exits
+// with, and (on Unix) aborts and crashes are signals.
+impl fmt::Display for ChildExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            ChildExit::TimedOut => return write!(f, "timed out, and was killed"),
+            ChildExit::Exited(status) => status,
+        };
+        match (status.code(), self.signal()) {
+            (Some(0), _) => write!(f, "exited normally"),
+            (Some(101), _) => write!(f, "panicked (exit status 101)"),
+            (Some(code), _) => write!(f, "failed (exit status {})", code),
+            (None, Some(6)) => write!(f, "aborted (SIGABRT)"),
+            (None, Some(4)) => write!(f, "crashed (SIGILL)"),
+            (None, Some(7)) => write!(f, "crashed (SIGBUS)"),
+            (None, Some(11)) => write!(f, "crashed (SIGSEGV)"),
+            (None, Some(signal)) => write!(f, "killed by signal {}", signal),
+            (None, None) => write!(f, "{}", status),
+        }
+    }
+}
+
+pub struct ChildRun {
+    pub exit: ChildExit,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs this program again, on the demo `name` only and with `vars` set in
+/// its environment, and kills it if it hasn't exited within `timeout`.
+/// Its output is read as it comes, on threads of its own, so that a child
+/// with a lot to say doesn't stall on a full pipe.
+pub fn run_child(name: &str, vars: &[(&str, &str)], timeout: Duration) -> io::Result<ChildRun> {
+    let mut child = Command::new(env::current_exe()?)
+        .arg(name)
+        .env(DEMO_CHILD, "1")
+        .envs(vars.iter().copied())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    fn read_all(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            // What was read before an error is all there is to have.
+            let _ = pipe.read_to_end(&mut bytes);
+            String::from_utf8_lossy(&bytes).into_owned()
+        })
+    }
+    let stdout = read_all(child.stdout.take().unwrap());
+    let stderr = read_all(child.stderr.take().unwrap());
+    let deadline = Instant::now() + timeout;
+    let exit = loop {
+        if let Some(status) = child.try_wait()? {
+            break ChildExit::Exited(status);
+        }
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            break ChildExit::TimedOut;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    // Once it's gone, the pipes are closed, and the readers done.
+    Ok(ChildRun { exit, stdout: stdout.join().unwrap(), stderr: stderr.join().unwrap() })
+}
+
+macro_rules! demo {
+    ($description:literal, tags: [$($tag:literal),*] $(, $drops:ident: [$($drop:literal),*])? $(,)?) => {
+        pub const DEMO: $crate::registry::Demo = $crate::registry::Demo {
+            path: module_path!(),
+            description: $description,
+            tags: &[$($tag),*],
+            expected_drops: $crate::registry::demo!(@$($drops [$($drop),*])?),
+            run: demo,
+            lesson: None,
+            unsound: false,
+        };
+    };
+    (@) => { None };
+    (@drops [$($drop:literal),*]) => { Some($crate::registry::Expected::InOrder(&[$($drop),*])) };
+    (@drops_any_order [$($drop:literal),*]) => { Some($crate::registry::Expected::AnyOrder(&[$($drop),*])) };
+}
+pub(crate) use demo;
+
+pub fn all() -> Vec<&'static Demo> {
+    use crate::*;
+
+    let mut demos = vec![
+        &dropck_pairs::mybox2::VALUE_FIRST,
+        #[cfg(not(feature = "no-eyepatch"))]
+        &dropck_pairs::mybox2::CONTAINER_FIRST,
+        &dropck_pairs::mybox2_closure::VALUE_FIRST,
+        #[cfg(not(feature = "no-eyepatch"))]
+        &dropck_pairs::mybox2_closure::CONTAINER_FIRST,
+        &dropck_pairs::with_unit::VALUE_FIRST,
+        &dropck_pairs::with_unit::CONTAINER_FIRST,
+        &my_hash_map::DEMO,
+        &small_box::DEMO,
+        &my_small_vec::DEMO,
+        &rc_cycle::DEMO,
+        &slab::DEMO,
+        &intrusive_list::DEMO,
+        &my_vec::DEMO,
+        &my_iter::DEMO,
+        &my_linked_list::DEMO,
+        &scoped_threads::DEMO,
+        &scope_guard::DEMO,
+        &ffi_handle::DEMO,
+        &ffi_owned::DEMO,
+        &foreign_cstring::DEMO,
+        &transaction::DEMO,
+        &fd_state::DEMO,
+        &endian::DEMO,
+        &encoder::DEMO,
+        &family::DEMO,
+        &visitor::DEMO,
+        &branded::DEMO,
+        &send_sync::DEMO,
+        &borrowing_future::DEMO,
+    ];
+    #[cfg(feature = "async-demos")]
+    demos.push(&async_drop_order::DEMO);
+    #[cfg(feature = "coroutines")]
+    demos.push(&coroutine_drop::DEMO);
+    demos.extend([
+        &pin_projection::DEMO,
+        &main_thread::DEMO,
+        &my_cell_box::DEMO,
+        &once_box::DEMO,
+        &tagged_box::DEMO,
+        &aliasing::DEMO,
+        &variance_tests::DEMO,
+        &unwind_safe::DEMO,
+        &impl_capture::DEMO,
+        &my_unique::DEMO,
+    ]);
+    #[cfg(feature = "unsize")]
+    demos.push(&unsize::DEMO);
+    demos.extend([
+        &recorder::DEMO,
+        &drop_queue::DEMO,
+        &zero_cost::DEMO,
+        &my_arc::DEMO,
+        &uninit::DEMO,
+        &into_raw::DEMO,
+        &in_place_drop::DEMO,
+        &arena::DEMO,
+        &drop_strategies::DEMO,
+        &traced::DEMO,
+        &phantom_owner::DEMO,
+        &observer::DEMO,
+        &trace::DEMO,
+        &engine::DEMO,
+        &replay::DEMO,
+        &temporaries::DEMO,
+        &scrutinee_temporaries::DEMO,
+        &shadowing::DEMO,
+        &closure_capture::DEMO,
+        &boxed_closures::DEMO,
+        &thread_local_drop::DEMO,
+        &const_drop::DEMO,
+        &mem_replace::DEMO,
+        &vec_drop_order::DEMO,
+        &hash_map_drop::DEMO,
+        &early_return::DEMO,
+        &hrtb::DEMO,
+        &coercion_sites::DEMO,
+        &vec_dropck::DEMO,
+        &phantom_errors::DEMO,
+        &static_bound::DEMO,
+        &payload_matrix::DEMO,
+        &cross_thread_drops::DEMO,
+        &map_ref::DEMO,
+        &weak_observers::DEMO,
+        &mermaid::DEMO,
+        &unit_marker::DEMO,
+        &eyepatch::DEMO,
+        &nll::DEMO,
+        &two_phase::DEMO,
+        &derive_bounds::DEMO,
+        &unused_param::DEMO,
+        &decoder::DEMO,
+        &normalize::DEMO,
+    ]);
+    #[cfg(feature = "instrumented-alloc")]
+    demos.extend([&alloc_tracker::DEMO, &rc_leak::DEMO]);
+    #[cfg(feature = "c-abi")]
+    demos.push(&c_abi::DEMO);
+    #[cfg(feature = "abort-demos")]
+    demos.extend([&double_panic::DEMO, &lock_in_drop::DEMO]);
+    #[cfg(feature = "redteam")]
+    demos.push(&redteam::DEMO);
+    demos
+}
@@ -0,0 +1,196 @@
+// Record and replay: `cargo run -- --record out.trace` saves every event
+// each demo records, a demo's trace per line (see trace.rs);
+// `cargo run -- --replay out.trace` shows them again, through whatever
+// DropObservers are listening, without running anything; and
+// `--replay out.trace --diff` runs the demos in it again and reports
+// where what they do now differs from what they did then. Which is how
+// to check what a new compiler changed, without keeping the old one.
+//
+// A Recording hears everything recorded, on any thread, and adds it to
+// the trace of whichever demo `begin` was last told of. Its own
+// allocations aren't counted (see alloc_counter.rs), so demos that count
+// allocations, or bytes, behave just as they do unrecorded.
+//
+// Ids aren't compared: they depend on how many PrintOnDrops were made
+// before, and by which thread first. Nor is the order of drops that may
+// come in any order: traces are compared normalized (see normalize.rs).
+
+use std::fmt;
+use std::fs;
+use std::mem;
+use std::sync::Mutex;
+
+use crate::alloc_counter::{allocations, uncounted};
+use crate::normalize;
+use crate::observer::{self, DropObserver};
+use crate::recorder::{self, Event};
+use crate::scope_guard::defer;
+use crate::trace::{self, ToJson, Trace};
+use crate::{print_on_drop, State};
+
+pub struct Recording(Mutex<Vec<Trace>>);
+
+impl Recording {
+    pub const fn new() -> Self {
+        Recording(Mutex::new(Vec::new()))
+    }
+
+    /// What is recorded from now on is `demo`'s.
+    pub fn begin(&self, demo: &str) {
+        uncounted(|| self.0.lock().unwrap().push(Trace { demo: demo.to_string(), lost: 0, events: Vec::new() }));
+    }
+
+    /// Normalizes every trace so far, as `normalize::trace` does.
+    pub fn normalize(&self) {
+        uncounted(|| {
+            for t in self.0.lock().unwrap().iter_mut() {
+                *t = normalize::trace(t);
+            }
+        });
+    }
+
+    pub fn with_traces<R>(&self, f: impl FnOnce(&[Trace]) -> R) -> R {
+        f(&self.0.lock().unwrap())
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        uncounted(|| {
+            let text: String = self.with_traces(|t| t.iter().map(|t| format!("{}\n", t.to_json())).collect());
+            fs::write(path, text).map_err(|e| format!("{}: {}", path, e))
+        })
+    }
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Recording::new()
+    }
+}
+
+impl DropObserver for Recording {
+    fn on_record(&self, e: &Event) {
+        uncounted(|| {
+            if let Some(trace) = self.0.lock().unwrap().last_mut() {
+                trace.events.push(e.clone());
+            }
+        });
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        // Allocated uncounted, so freed uncounted.
+        let traces = mem::take(self.0.get_mut().unwrap());
+        uncounted(|| drop(traces));
+    }
+}
+
+pub fn load_file(path: &str) -> Result<Vec<Trace>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| trace::load(line).map_err(|e| format!("{}:{}: {}", path, i + 1, e)))
+        .collect()
+}
+
+// What a replayed drop shows its observers, for want of the value itself.
+struct Replayed<'a>(&'a str, u64, State);
+
+impl fmt::Debug for Replayed<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PrintOnDrop({}#{}, .., {:?})", self.0, self.1, self.2)
+    }
+}
+
+/// Tells every observer of the traces' events again, as they happened.
+pub fn render(traces: &[Trace]) {
+    for t in traces {
+        println!("{}: {} events, replayed", t.demo, t.events.len());
+        for e in &t.events {
+            observer::publish(|o| o.on_record(e));
+            match e {
+                Event::Drop(name, id, state) => {
+                    observer::publish(|o| o.on_drop(name, *id, *state, &Replayed(name, *id, *state)));
+                }
+                Event::Guard(name) => println!("guard {}", name),
+                Event::Traced(name) => println!("traced {}", name),
+                // Shown by Stdout, as they were the first time.
+                Event::Enter(_) | Event::Exit(_) => {}
+            }
+        }
+    }
+}
+
+// The same, but for the id.
+fn same(a: &Event, b: &Event) -> bool {
+    match (a, b) {
+        (Event::Drop(a, _, sa), Event::Drop(b, _, sb)) => a == b && sa == sb,
+        (Event::Guard(a), Event::Guard(b))
+        | (Event::Traced(a), Event::Traced(b))
+        | (Event::Enter(a), Event::Enter(b))
+        | (Event::Exit(a), Event::Exit(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Compares the traces of a run with those saved from an earlier one:
+/// for each demo whose trace differs, a line saying where.
+pub fn diff(saved: &[Trace], now: &[Trace]) -> Vec<String> {
+    let mut differences = Vec::new();
+    for s in saved {
+        let Some(n) = now.iter().find(|n| n.demo == s.demo) else {
+            differences.push(format!("{}: not run this time", s.demo));
+            continue;
+        };
+        let (s, n) = (&normalize::trace(s), &normalize::trace(n));
+        let first = s.events.iter().zip(&n.events).position(|(a, b)| !same(a, b));
+        if let Some(i) = first {
+            let (was, now) = (s.events[i].to_json(), n.events[i].to_json());
+            differences.push(format!("{}: event {} was {}, now {}", s.demo, i, was, now));
+        } else if s.events.len() != n.events.len() {
+            differences.push(format!("{}: {} events, now {}", s.demo, s.events.len(), n.events.len()));
+        }
+    }
+    for n in now.iter().filter(|n| !saved.iter().any(|s| s.demo == n.demo)) {
+        differences.push(format!("{}: not saved", n.demo));
+    }
+    differences
+}
+
+fn scenario(swap: bool) {
+    let (_a, _b);
+    _a = print_on_drop!("rp_a", 1);
+    defer!("rp_guard", {});
+    _b = print_on_drop!(if swap { "rp_c" } else { "rp_b" }, 2);
+}
+
+crate::registry::demo!("Record and replay: traces saved, played back and diffed", tags: ["drop-order"]);
+
+pub fn demo() {
+    let recording = Recording::new();
+    let before = allocations();
+    observer::with_observer(&recording, || {
+        recording.begin("rp_first");
+        scenario(false);
+        recording.begin("rp_second");
+        scenario(false);
+        recording.begin("rp_third");
+        scenario(true);
+    });
+    // The Recording's own allocations don't count.
+    assert_eq!(allocations() - before, 0);
+    recorder::take();
+
+    recording.with_traces(|t| {
+        let names = |t: &Trace| t.events.iter().map(|e| e.to_json().to_string()).collect::<Vec<_>>();
+        println!("Recording: {:?}", names(&t[0]));
+        assert_eq!(t[0].events.len(), 3);
+        // Same names, different ids: the same.
+        assert!(diff(&t[..1], &[Trace { demo: "rp_first".into(), ..t[1].clone() }]).is_empty());
+        let swapped = diff(&t[..1], &[Trace { demo: "rp_first".into(), ..t[2].clone() }]);
+        assert!(swapped[0].starts_with("rp_first: event 1 was {\"Drop\":[\"rp_b\","), "{:?}", swapped);
+        assert_eq!(diff(&t[..2], &t[..1]), ["rp_second: not run this time"]);
+        render(&t[..1]);
+    });
+}
@@ -0,0 +1,133 @@
+// ScopeGuard runs a closure when it is dropped, which makes "do this on
+// the way out, however we leave" as simple as declaring a local. The
+// `defer!` macro declares one for you.
+//
+// Guards are dropped like any other local, in reverse declaration order,
+// so they interleave with the locals around them. Each run is recorded,
+// so that the demo below can check exactly where.
+//
+// A Scope is a guard that only records: where it was declared, and where
+// it was dropped. Declared first in a block, with `scope!`, it is dropped
+// last, so everything else in the block is dropped between the two, and
+// Stdout (see observer.rs) indents those drops to show it.
+
+use std::borrow::Cow;
+use std::mem::ManuallyDrop;
+
+use crate::recorder::{self, Event};
+use crate::PrintOnDrop;
+
+pub struct ScopeGuard<F: FnOnce()> {
+    name: &'static str,
+    f: ManuallyDrop<F>,
+}
+
+impl<F: FnOnce()> ScopeGuard<F> {
+    pub fn new(name: &'static str, f: F) -> Self {
+        ScopeGuard { name, f: ManuallyDrop::new(f) }
+    }
+}
+
+impl<F: FnOnce()> Drop for ScopeGuard<F> {
+    fn drop(&mut self) {
+        recorder::record(Event::Guard(Cow::Borrowed(self.name)));
+        // Safety: `f` is never touched again.
+        let f = unsafe { ManuallyDrop::take(&mut self.f) };
+        f();
+    }
+}
+
+/// `defer!("name", body...)` runs `body` when the enclosing scope ends.
+macro_rules! defer {
+    ($name:literal, $($body:tt)*) => {
+        let _guard = $crate::scope_guard::ScopeGuard::new($name, || { $($body)* });
+    };
+}
+pub(crate) use defer;
+
+#[must_use = "a Scope ends when it is dropped"]
+pub struct Scope {
+    name: &'static str,
+}
+
+impl Scope {
+    pub fn enter(name: &'static str) -> Self {
+        recorder::record(Event::Enter(Cow::Borrowed(name)));
+        Scope { name }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        recorder::record(Event::Exit(Cow::Borrowed(self.name)));
+    }
+}
+
+/// `scope!("name")`, first in a block, marks the block as a scope.
+macro_rules! scope {
+    ($name:expr) => {
+        let _scope = $crate::scope_guard::Scope::enter($name);
+    };
+}
+pub(crate) use scope;
+
+fn f_interleaved() {
+    let _a = PrintOnDrop::new("sg_a", 1);
+    defer!("sg_guard1", println!("guard 1 runs"));
+    let b = PrintOnDrop::new("sg_b", 2);
+    // This guard borrows `b`, which is fine because `b` was declared
+    // first, and so is dropped after the guard has run.
+    defer!("sg_guard2", println!("guard 2 sees {:?}", b));
+}
+
+fn f_early_return(bail: bool) -> u32 {
+    defer!("sg_ret", println!("leaving f_early_return"));
+    let _c = PrintOnDrop::new("sg_c", 3);
+    if bail {
+        return 0;
+    }
+    let _d = PrintOnDrop::new("sg_d", 4);
+    1
+}
+
+fn f_nested() {
+    scope!("sg_outer");
+    let _e = PrintOnDrop::new("sg_e", 5);
+    {
+        scope!("sg_inner");
+        let _f = PrintOnDrop::new("sg_f", 6);
+    }
+    let _g = PrintOnDrop::new("sg_g", 7);
+}
+
+// The events as Stdout shows them, unindented.
+fn outline(events: Vec<Event>) -> Vec<String> {
+    events
+        .into_iter()
+        .map(|e| match e {
+            Event::Enter(name) => format!("{{ {}", name),
+            Event::Exit(name) => format!("}} {}", name),
+            Event::Drop(name, ..) | Event::Guard(name) | Event::Traced(name) => name.into_owned(),
+        })
+        .collect()
+}
+
+crate::registry::demo!("ScopeGuard and defer!: run a closure on the way out", tags: ["drop-order"]);
+
+pub fn demo() {
+    recorder::take();
+    f_interleaved();
+    assert_eq!(recorder::take_drops(), ["sg_guard2", "sg_b", "sg_guard1", "sg_a"]);
+
+    f_early_return(true);
+    assert_eq!(recorder::take_drops(), ["sg_c", "sg_ret"]);
+    f_early_return(false);
+    assert_eq!(recorder::take_drops(), ["sg_d", "sg_c", "sg_ret"]);
+
+    // Each scope's drops come between its Enter and its Exit.
+    f_nested();
+    assert_eq!(
+        outline(recorder::take()),
+        ["{ sg_outer", "{ sg_inner", "sg_f", "} sg_inner", "sg_g", "sg_e", "} sg_outer"]
+    );
+}
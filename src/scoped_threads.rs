@@ -0,0 +1,96 @@
+// A tiny `scope`, in the spirit of std::thread::scope: threads spawned
+// within it may borrow from the enclosing stack frame, because `scope`
+// joins all of them before returning.
+//
+// The whole thing hinges on the variance of `'scope`. Spawned closures
+// need only outlive `'scope`, and `scope` guarantees that `'scope` covers
+// everything up to the final join. If Scope<'scope, '_> were covariant in
+// `'scope`, code holding a `&'scope Scope<'scope, '_>` could shrink it to
+// some shorter lifetime (say, the body of the closure passed to `scope`)
+// and spawn threads borrowing locals that die before the join. Making the
+// phantom `&'scope mut &'scope ()`, which is invariant, rules that out.
+
+use std::marker::PhantomData;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+use crate::PrintOnDrop;
+
+pub struct Scope<'scope, 'env: 'scope> {
+    threads: Mutex<Vec<JoinHandle<()>>>,
+    _scope: PhantomData<&'scope mut &'scope ()>,
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    pub fn spawn<F: FnOnce() + Send + 'scope>(&'scope self, f: F) {
+        let f: Box<dyn FnOnce() + Send + 'scope> = Box::new(f);
+        // Safety: `scope` joins this thread before `'scope` ends, so `f`
+        // never actually outlives what it borrows.
+        let f: Box<dyn FnOnce() + Send + 'static> = unsafe { mem::transmute(f) };
+        let handle = thread::spawn(f);
+        self.threads.lock().unwrap().push(handle);
+    }
+}
+
+pub fn scope<'env, F, R>(f: F) -> R
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R,
+{
+    let scope = Scope { threads: Mutex::new(Vec::new()), _scope: PhantomData, _env: PhantomData };
+    // Even if `f` panics, we must not return (or unwind) past the threads
+    // it spawned while they may still be running.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+    let mut thread_panicked = false;
+    loop {
+        // Threads may spawn more threads, so keep going until none are left.
+        let handle = scope.threads.lock().unwrap().pop();
+        match handle {
+            Some(h) => thread_panicked |= h.join().is_err(),
+            None => break,
+        }
+    }
+    match result {
+        Err(e) => panic::resume_unwind(e),
+        Ok(_) if thread_panicked => panic!("a scoped thread panicked"),
+        Ok(r) => r,
+    }
+}
+
+crate::registry::demo! {
+    "A scope whose threads may borrow from the stack",
+    tags: ["lifetimes", "threads"],
+}
+
+pub fn demo() {
+    let v = PrintOnDrop::new("scope_v", 13);
+    let seen = Mutex::new(Vec::new());
+    let n = scope(|s| {
+        for i in 0..3 {
+            let (v, seen) = (&v, &seen);
+            s.spawn(move || {
+                seen.lock().unwrap().push(i);
+                let _p = PrintOnDrop::new("scope_thread", (i, v.1));
+            });
+        }
+        3
+    });
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort();
+    assert_eq!(seen.len(), n);
+    println!("scope: all {} threads joined, saw {:?}", n, seen);
+
+    // `'scope` is invariant, so it can't be shortened to the closure body,
+    // and threads can't borrow the body's locals:
+    //
+    // scope(|s| {
+    //     let local = PrintOnDrop::new("local", 13);
+    //     s.spawn(|| println!("{:?}", local)); // won't compile: closure may outlive `local`
+    // });
+    //
+    // With the covariant `PhantomData<&'scope ()>` in `_scope` instead,
+    // that compiles, `local` is dropped as the closure returns, and the
+    // thread (joined only later) may print it afterwards.
+}
@@ -0,0 +1,119 @@
+// Temporaries in the scrutinee of a `match`, `if let` or `let ... else`:
+// the value being matched on, and whatever was made to get at it. Unlike
+// those of an `if` condition, they aren't dropped when the matching is
+// done, but with the statement: after the arm that ran. For a lock guard,
+// that means the lock stays held through the arm, which is the classic
+// way to deadlock on a Mutex without seeing a second `lock()` anywhere.
+//
+// As in temporaries.rs, each case marks where things happen by dropping
+// PrintOnDrops of its own. Edition 2021 rules; 2024 changes `if let`'s
+// (see `if_let`).
+
+use std::sync::{Mutex, MutexGuard};
+
+use lesson::lesson;
+
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// What a scrutinee might call: a value to match on, made (and owned) by
+// a temporary.
+fn make(name: &'static str, n: i32) -> PrintOnDrop<Option<i32>> {
+    print_on_drop!(name, Some(n))
+}
+
+fn match_arm() {
+    match make("st_match", 1).1 {
+        Some(_) => mark("st_arm"),
+        None => unreachable!(),
+    }
+    mark("st_next");
+}
+
+fn if_condition() {
+    // A condition is a temporary scope of its own: gone before the block.
+    if make("st_if", 1).1.is_some() {
+        mark("st_then");
+    }
+}
+
+fn if_let() {
+    // In edition 2021, the scrutinee's temporaries outlive the `else`
+    // block too; from 2024, they go before it.
+    if let Some(0) = make("st_if_let", 1).1 {
+        unreachable!();
+    } else {
+        mark("st_else");
+    }
+    mark("st_next");
+}
+
+fn let_else() -> i32 {
+    // With the `let` statement: before what follows it, not after.
+    let Some(n) = make("st_let_else", 1).1 else {
+        unreachable!();
+    };
+    mark("st_next");
+    n
+}
+
+// A guard that says when it's dropped, and so when the lock is released.
+fn lock(m: &Mutex<Vec<i32>>) -> PrintOnDrop<MutexGuard<'_, Vec<i32>>> {
+    print_on_drop!("st_guard", m.lock().unwrap())
+}
+
+fn held_across_match(jobs: &Mutex<Vec<i32>>) {
+    match lock(jobs).1.pop() {
+        Some(_) => {
+            // Still locked: a `jobs.lock()` here would never return.
+            assert!(jobs.try_lock().is_err());
+            mark("st_job");
+        }
+        None => unreachable!(),
+    }
+}
+
+fn released_before_match(jobs: &Mutex<Vec<i32>>) {
+    let job = lock(jobs).1.pop();
+    match job {
+        Some(_) => {
+            assert!(jobs.try_lock().is_ok());
+            mark("st_job");
+        }
+        None => unreachable!(),
+    }
+}
+
+/// A `match`, `if let` or `let ... else` keeps the temporaries of its
+/// scrutinee until the end of the statement: in a `match`, until after
+/// the arm that ran, and in an `if let`, through the `else` block too. An
+/// `if` condition drops them before its block runs; `let ... else`, at the
+/// end of the `let`, before the statements after it.
+///
+/// With a lock guard as the temporary, the lock is held throughout the
+/// arm: `match jobs.lock().unwrap().pop() { Some(job) => run(job), .. }`
+/// runs every job with the queue locked, and deadlocks if a job locks it
+/// again. Binding the result first (`let job = ...; match job { .. }`)
+/// ends the guard's statement, and releases the lock, before the match.
+#[lesson(
+    title = "Scrutinee temporaries: match, if let and let-else, and the held lock",
+    tags = ["drop-order", "threads"],
+    notes = ["Edition 2024 drops an `if let` scrutinee's temporaries before `else`"],
+)]
+pub fn demo() {
+    recorder::take();
+    match_arm();
+    assert_eq!(recorder::take_drops(), ["st_arm", "st_match", "st_next"]);
+    if_condition();
+    assert_eq!(recorder::take_drops(), ["st_if", "st_then"]);
+    if_let();
+    assert_eq!(recorder::take_drops(), ["st_else", "st_if_let", "st_next"]);
+    assert_eq!(let_else(), 1);
+    assert_eq!(recorder::take_drops(), ["st_let_else", "st_next"]);
+
+    let jobs = Mutex::new(vec![1, 2]);
+    held_across_match(&jobs);
+    assert_eq!(recorder::take_drops(), ["st_job", "st_guard"]);
+    released_before_match(&jobs);
+    assert_eq!(recorder::take_drops(), ["st_guard", "st_job"]);
+}
@@ -0,0 +1,88 @@
+// Wrappers with the two "odd" auto-trait combinations, obtained by adding
+// a phantom field of a type that already has the combination we want:
+//
+// - `Cell<()>` is Send but not Sync (it may move to another thread, but
+//   two threads mustn't share one), so SendNotSync<T> uses PhantomData
+//   <Cell<()>>. Real examples: `Cell`, `RefCell`, an `mpsc::Receiver`.
+//
+// - `MutexGuard<'_, ()>` is Sync but not Send (some platforms require that
+//   a mutex be unlocked by the thread that locked it, yet sharing a guard
+//   only shares the data), so SyncNotSend<T> uses PhantomData
+//   <MutexGuard<'static, ()>>.
+//
+// Auto traits of a struct are the intersection of its fields', so adding
+// a field can only take traits away; these wrappers are Send/Sync only if
+// `T` is.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::MutexGuard;
+use std::thread;
+
+use crate::auto_trait_tests::{assert_impl_send, assert_impl_sync};
+
+pub struct SendNotSync<T> {
+    pub value: T,
+    _marker: PhantomData<Cell<()>>,
+}
+
+pub struct SyncNotSend<T> {
+    pub value: T,
+    _marker: PhantomData<MutexGuard<'static, ()>>,
+}
+
+impl<T> SendNotSync<T> {
+    pub fn new(value: T) -> Self {
+        SendNotSync { value, _marker: PhantomData }
+    }
+}
+
+impl<T> SyncNotSend<T> {
+    pub fn new(value: T) -> Self {
+        SyncNotSend { value, _marker: PhantomData }
+    }
+}
+
+crate::registry::demo!("Send-but-not-Sync and Sync-but-not-Send wrappers", tags: ["auto-traits"]);
+
+pub fn demo() {
+    assert_impl_send!(SendNotSync<u32>);
+    assert_impl_sync!(SyncNotSend<u32>);
+    // assert_impl_sync!(SendNotSync<u32>); // won't compile: E0277, `Cell<()>` cannot be shared between threads safely
+    // assert_impl_send!(SyncNotSend<u32>); // won't compile: E0277, `MutexGuard<'static, ()>` cannot be sent between threads safely
+
+    // A SendNotSync can be handed over to another thread...
+    let mut owned = SendNotSync::new(1);
+    let owned = thread::spawn(move || {
+        owned.value += 1;
+        owned
+    })
+    .join()
+    .unwrap();
+    println!("SendNotSync: moved to a thread and back, value = {}", owned.value);
+
+    // ...but not shared with one:
+    //
+    // thread::scope(|s| {
+    //     let r = &owned;
+    //     s.spawn(move || r.value); // won't compile: `Cell<()>` cannot be shared between threads safely
+    // });
+    //
+    // (Closures capture only the fields they use, so `|| owned.value`
+    // would borrow just the `u32`, and compile.)
+
+    // A SyncNotSend can be shared with other threads...
+    let shared = SyncNotSend::new(20);
+    let sum: u32 = thread::scope(|s| {
+        let hs: Vec<_> = (0..2).map(|_| s.spawn(|| shared.value)).collect();
+        hs.into_iter().map(|h| h.join().unwrap()).sum()
+    });
+    println!("SyncNotSend: shared with two threads, sum = {}", sum);
+
+    // ...but not given to one:
+    //
+    // thread::spawn(move || {
+    //     let whole = shared; // won't compile: `MutexGuard<'static, ()>` cannot be sent between threads safely
+    //     whole.value
+    // });
+}
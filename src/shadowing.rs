@@ -0,0 +1,71 @@
+// Shadowing isn't dropping: `let x = ...;` a second time makes a new
+// variable, and the old one, though it can no longer be named, lives on
+// to the end of its scope, where it is dropped after the new one, like
+// any local declared before it. Assigning to a variable is what drops its
+// old value, then and there.
+
+use lesson::lesson;
+
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder};
+
+fn shadowed() {
+    let x = print_on_drop!("sh_first", 1);
+    assert_eq!(x.name(), "sh_first");
+    let x = print_on_drop!("sh_second", 2);
+    mark("sh_shadowed");
+    assert_eq!(x.name(), "sh_second");
+}
+
+fn assigned() {
+    let mut x = print_on_drop!("sh_first", 1);
+    assert_eq!(x.name(), "sh_first");
+    mark("sh_before");
+    x = print_on_drop!("sh_second", 2);
+    mark("sh_assigned");
+    assert_eq!(x.name(), "sh_second");
+}
+
+fn in_a_block() {
+    let x = print_on_drop!("sh_outer", 1);
+    {
+        let x = print_on_drop!("sh_inner", 2);
+        assert_eq!(x.name(), "sh_inner");
+    }
+    // The outer one is back in view, and was there all along.
+    assert_eq!(x.name(), "sh_outer");
+    mark("sh_after_block");
+}
+
+fn moved() {
+    // Shadowed by itself, the value moves into the new variable, leaving
+    // the old one nothing to drop; likewise when the new one wraps it.
+    let x = print_on_drop!("sh_moved", 1);
+    let x = x;
+    mark("sh_rebound");
+    let x = Some(x);
+    mark("sh_wrapped");
+    assert!(x.is_some());
+}
+
+/// Shadowing a variable (`let x = ...;` again) doesn't drop the value in
+/// the old one: it's still there, only unnamed, and is dropped at the end
+/// of its scope, after the new one, in reverse order of declaration as
+/// usual. Shadowing in an inner block hides the outer variable until the
+/// block ends, and then it's back in view.
+///
+/// Assigning (`x = ...;`) is what drops the old value, at once. And
+/// `let x = x;` moves the value into the new variable, leaving the old
+/// one nothing to drop.
+#[lesson(title = "Shadowing: the old value lives on, unnamed, to the end of its scope", tags = ["drop-order"])]
+pub fn demo() {
+    recorder::take();
+    shadowed();
+    assert_eq!(recorder::take_drops(), ["sh_shadowed", "sh_second", "sh_first"]);
+    assigned();
+    assert_eq!(recorder::take_drops(), ["sh_before", "sh_first", "sh_assigned", "sh_second"]);
+    in_a_block();
+    assert_eq!(recorder::take_drops(), ["sh_inner", "sh_after_block", "sh_outer"]);
+    moved();
+    assert_eq!(recorder::take_drops(), ["sh_rebound", "sh_wrapped", "sh_moved"]);
+}
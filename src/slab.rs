@@ -0,0 +1,168 @@
+// A slab: values are stored in slots addressed by stable `usize` keys, and
+// the slot of a removed value goes on a free list to be reused by the next
+// insert.
+//
+// Each slot is a union of "occupied" (holds a `T`) and "vacant" (holds the
+// next free key), with the tag kept alongside it. A union field never gets
+// dropped implicitly, so, like MaybeUninit in MySmallVec, the slots tell
+// dropck nothing about our ownership of `T`, and neither does the raw
+// pointer to them. Do we need PhantomData<T>? Yes, for the same reason as
+// MyBox3: our destructor drops whatever `T`s are still occupying slots.
+
+use std::alloc::{self, dealloc, Layout};
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
+use std::ptr::{self, NonNull};
+
+use crate::PrintOnDrop;
+
+union Slot<T> {
+    value: ManuallyDrop<T>,
+    next_free: usize,
+}
+
+struct Entry<T> {
+    occupied: bool,
+    slot: Slot<T>,
+}
+
+pub struct Slab<T> {
+    entries: NonNull<Entry<T>>,
+    cap: usize,
+    // Entries `0..used` have been initialized (occupied or vacant);
+    // those beyond are untouched memory.
+    used: usize,
+    len: usize,
+    // Head of the free list threaded through vacant slots; `used` if empty.
+    next_free: usize,
+    _pd: PhantomData<T>,
+}
+
+impl<T> Slab<T> {
+    pub fn new() -> Self {
+        Slab { entries: NonNull::dangling(), cap: 0, used: 0, len: 0, next_free: 0, _pd: PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Stores `t` and returns the key under which it can be found.
+    pub fn insert(&mut self, t: T) -> usize {
+        let key = self.next_free;
+        unsafe {
+            if key == self.used {
+                if self.used == self.cap {
+                    self.grow();
+                }
+                self.used += 1;
+                self.next_free = self.used;
+            } else {
+                self.next_free = (*self.entries.as_ptr().add(key)).slot.next_free;
+            }
+            let value = ManuallyDrop::new(t);
+            ptr::write(self.entries.as_ptr().add(key), Entry { occupied: true, slot: Slot { value } });
+        }
+        self.len += 1;
+        key
+    }
+
+    pub fn get(&self, key: usize) -> Option<&T> {
+        if key < self.used {
+            let e = unsafe { &*self.entries.as_ptr().add(key) };
+            if e.occupied {
+                return Some(unsafe { &e.slot.value });
+            }
+        }
+        None
+    }
+
+    /// Removes and returns the value under `key`, making the key
+    /// available for reuse. Panics if `key` is vacant.
+    pub fn remove(&mut self, key: usize) -> T {
+        assert!(self.get(key).is_some(), "slab key {} is vacant", key);
+        unsafe {
+            let e = &mut *self.entries.as_ptr().add(key);
+            let t = ManuallyDrop::take(&mut e.slot.value);
+            e.occupied = false;
+            e.slot.next_free = self.next_free;
+            self.next_free = key;
+            self.len -= 1;
+            t
+        }
+    }
+
+    fn grow(&mut self) {
+        let new_cap = (self.cap * 2).max(4);
+        let new_layout = Layout::array::<Entry<T>>(new_cap).unwrap();
+        unsafe {
+            let p = if self.cap == 0 {
+                alloc::alloc(new_layout)
+            } else {
+                let old_layout = Layout::array::<Entry<T>>(self.cap).unwrap();
+                alloc::realloc(self.entries.as_ptr() as *mut u8, old_layout, new_layout.size())
+            };
+            let Some(p) = NonNull::new(p as *mut Entry<T>) else {
+                alloc::handle_alloc_error(new_layout);
+            };
+            self.entries = p;
+        }
+        self.cap = new_cap;
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for Slab<T> {
+    fn drop(&mut self) {
+        unsafe {
+            // Without drop glue there is nothing to look for.
+            let used = if mem::needs_drop::<T>() { self.used } else { 0 };
+            for key in 0..used {
+                let e = &mut *self.entries.as_ptr().add(key);
+                // Vacant slots hold a `usize`, which must not be dropped
+                // as if it were a `T`.
+                if e.occupied {
+                    ManuallyDrop::drop(&mut e.slot.value);
+                }
+            }
+            if self.cap > 0 {
+                dealloc(self.entries.as_ptr() as *mut u8, Layout::array::<Entry<T>>(self.cap).unwrap());
+            }
+        }
+    }
+}
+
+crate::registry::demo! {
+    "Slab: stable keys, reused slots, and which slots get dropped",
+    tags: ["dropck", "collections"],
+    drops: ["slab_b", "slab_c", "slab_a", "slab_d", "slab_v"],
+}
+
+pub fn demo() {
+    let v = PrintOnDrop::new("slab_v", 13);
+    let mut slab = Slab::new();
+    let a = slab.insert(PrintOnDrop::new("slab_a", &v));
+    let b = slab.insert(PrintOnDrop::new("slab_b", &v));
+    let c = slab.insert(PrintOnDrop::new("slab_c", &v));
+    drop(slab.remove(b));
+    assert!(slab.get(b).is_none());
+
+    // `b`'s key is handed out again.
+    let d = slab.insert(PrintOnDrop::new("slab_d", &v));
+    assert_eq!(d, b);
+    println!("Slab: len={} a={:?} c={:?} d={:?}",
+             slab.len(),
+             slab.get(a).map(|p| p.name()),
+             slab.get(c).map(|p| p.name()),
+             slab.get(d).map(|p| p.name()));
+
+    // Leave a vacant slot in the middle; only slab_a and slab_d are
+    // dropped along with the slab.
+    drop(slab.remove(c));
+
+    // As for MyBox3:
+    //
+    // let (mut _slab, v2); // won't compile: `v2` does not live long enough
+    // v2 = PrintOnDrop::new("v2", 13);
+    // _slab = Slab::new();
+    // _slab.insert(PrintOnDrop::new("slab_bad", &v2));
+}
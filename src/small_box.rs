@@ -0,0 +1,133 @@
+// SmallBox<T, N> keeps its `T` inline (in a buffer of `N` words) when it
+// fits, and spills it to the heap when it doesn't.
+//
+// Either way, nothing in the field types mentions `T` by value: the inline
+// buffer is just `MaybeUninit` words, and the heap pointer is a NonNull<T>.
+// So rustc knows as little about our ownership of `T` as it did for MyBox2,
+// in *both* representations, and the PhantomData<T> is again what fixes
+// that. The destructor then has to work out which representation it is
+// looking at before it can drop the value it owns.
+
+use std::alloc::{self, dealloc, Layout};
+use std::marker::PhantomData;
+use std::mem::{self, MaybeUninit};
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+
+use crate::print_on_drop;
+
+pub struct SmallBox<T, const N: usize> {
+    buf: MaybeUninit<[usize; N]>,
+    // None while the value lives in `buf`.
+    heap: Option<NonNull<T>>,
+    _pd: PhantomData<T>,
+}
+
+impl<T, const N: usize> SmallBox<T, N> {
+    const FITS: bool = mem::size_of::<T>() <= mem::size_of::<[usize; N]>()
+        && mem::align_of::<T>() <= mem::align_of::<[usize; N]>();
+
+    pub fn new(t: T) -> Self {
+        let mut b = SmallBox { buf: MaybeUninit::uninit(), heap: None, _pd: PhantomData };
+        unsafe {
+            if Self::FITS {
+                ptr::write(b.buf.as_mut_ptr() as *mut T, t);
+            } else {
+                // Doesn't fit, though it may still be zero-sized: a `T`
+                // aligned more strictly than `buf` doesn't fit either. That
+                // takes no memory, and allocating none is UB.
+                let layout = Layout::new::<T>();
+                let p = if layout.size() == 0 {
+                    NonNull::dangling()
+                } else {
+                    let Some(p) = NonNull::new(alloc::alloc(layout) as *mut T) else {
+                        alloc::handle_alloc_error(layout);
+                    };
+                    p
+                };
+                ptr::write(p.as_ptr(), t);
+                b.heap = Some(p);
+            }
+        }
+        b
+    }
+
+    pub fn is_inline(&self) -> bool {
+        self.heap.is_none()
+    }
+
+    // Must be recomputed on every access: an inline value moves whenever
+    // the SmallBox itself does, so we can't cache a pointer to it.
+    fn as_ptr(&self) -> *const T {
+        match self.heap {
+            Some(p) => p.as_ptr(),
+            None => self.buf.as_ptr() as *const T,
+        }
+    }
+}
+
+impl<T, const N: usize> Deref for SmallBox<T, N> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.as_ptr() }
+    }
+}
+
+unsafe impl<#[may_dangle] T, const N: usize> Drop for SmallBox<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            match self.heap {
+                Some(p) => {
+                    ptr::drop_in_place(p.as_ptr());
+                    // A zero-sized value was never allocated.
+                    if mem::size_of::<T>() != 0 {
+                        dealloc(p.as_ptr() as *mut u8, Layout::new::<T>());
+                    }
+                }
+                None => ptr::drop_in_place(self.buf.as_mut_ptr() as *mut T),
+            }
+        }
+    }
+}
+
+crate::registry::demo! {
+    "SmallBox: a `T` inline when it fits, on the heap when it doesn't",
+    tags: ["dropck", "collections"],
+    drops: ["sb_inline", "sb_heap", "sb_v"],
+}
+
+pub fn demo() {
+    let v = print_on_drop!("sb_v", 13);
+
+    // A PrintOnDrop<&_> is six words (a Cow name, the reference, the
+    // state and the id), so it fits in a SmallBox<_, 6>...
+    let small: SmallBox<_, 6> = SmallBox::new(print_on_drop!("sb_inline", &v));
+    // ...but not in a SmallBox<_, 2>.
+    let big: SmallBox<_, 2> = SmallBox::new(print_on_drop!("sb_heap", &v));
+    println!("SmallBox: {:?} inline={}", *small, small.is_inline());
+    println!("SmallBox: {:?} inline={}", *big, big.is_inline());
+    assert!(small.is_inline() && !big.is_inline());
+
+    // Moving an inline SmallBox moves the value along with it.
+    let moved = small;
+    println!("SmallBox (moved): {:?} inline={}", *moved, moved.is_inline());
+
+    // A zero-sized `T` aligned beyond `buf` doesn't fit either, and goes
+    // "on the heap", which for it is a dangling, suitably aligned pointer.
+    #[repr(align(64))]
+    struct Aligned;
+    let aligned: SmallBox<Aligned, 2> = SmallBox::new(Aligned);
+    assert!(!aligned.is_inline());
+    assert_eq!(&*aligned as *const Aligned as usize % 64, 0);
+
+    // Both representations are covered by the PhantomData, so both of
+    // these are rejected, just like for MyBox3:
+    //
+    // let (_sb, v2); // won't compile: `v2` does not live long enough
+    // print_on_drop!(v2 = 13);
+    // _sb = SmallBox::<_, 6>::new(print_on_drop!("sb_bad", &v2));
+    //
+    // let (_sb, v2); // won't compile: `v2` does not live long enough
+    // print_on_drop!(v2 = 13);
+    // _sb = SmallBox::<_, 2>::new(print_on_drop!("sb_bad", &v2));
+}
@@ -0,0 +1,137 @@
+// `T: 'static` says that a `T` holds no borrow shorter than the program,
+// not that it lives that long: an owned String is 'static, and dropped
+// like anything else. What makes a type fail the bound is a lifetime
+// parameter, and a phantom one counts as much as a real borrow: a
+// Session<'a> holding nothing but a PhantomData<&'a ()> can't be sent
+// to thread::spawn any more than a `&'a str` can.
+//
+// The other way round, a phantom `&'static T` makes `T: 'static` part of
+// the type itself: TypeKey<T> (a key by TypeId, which needs it anyway)
+// only exists for 'static `T`s, where a PhantomData<fn() -> T> accepts
+// any `T`, and is then only as 'static as `T` is.
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+use std::thread;
+
+use lesson::lesson;
+
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// Stands for a borrow of something that lives for `'a`, without holding
+// it (see branded.rs and ffi_handle.rs for real uses).
+struct Session<'a> {
+    _borrows: PhantomData<&'a ()>,
+}
+
+impl<'a> Session<'a> {
+    fn of<T>(_: &'a T) -> Self {
+        Session { _borrows: PhantomData }
+    }
+}
+
+struct TypeKey<T: 'static> {
+    id: TypeId,
+    _of: PhantomData<&'static T>,
+}
+
+impl<T: 'static> TypeKey<T> {
+    fn new() -> Self {
+        TypeKey { id: TypeId::of::<T>(), _of: PhantomData }
+    }
+}
+
+// Any `T` at all; but then only as 'static as `T` is.
+struct AnyKey<T> {
+    _of: PhantomData<fn() -> T>,
+}
+
+fn is_static<T: 'static>(_: &T) {}
+
+fn spawned() -> Vec<String> {
+    // Owned, so 'static, and moved into the thread, where it's dropped
+    // when the closure is done with it: nothing lives forever.
+    let owned = print_on_drop!("sb_owned", String::from("owned"));
+    thread::spawn(move || {
+        assert_eq!(owned.1, "owned");
+        drop(owned);
+        recorder::take_drops().into_iter().map(|d| d.into_owned()).collect()
+    })
+    .join()
+    .unwrap()
+}
+
+fn sessions() {
+    let local = print_on_drop!("sb_local", 0);
+    let session = Session::of(&local);
+    // Spawned, the session would outlive `local` as far as the compiler
+    // knows, though there's nothing in it:
+    //
+    // let other = Session::of(&local);
+    // thread::spawn(move || { let _moved = other; }); // won't compile: E0597, `local` does not live long enough
+    //
+    // A scoped thread only needs it to outlive the scope.
+    thread::scope(|s| {
+        s.spawn(move || {
+            let _moved = session;
+        });
+    });
+
+    // A session of a leaked value is 'static, and may go anywhere.
+    let leaked: &'static PrintOnDrop<i32> = Box::leak(Box::new(print_on_drop!("sb_leaked", 1)));
+    let session = Session::of(leaked);
+    thread::spawn(move || {
+        let _moved = session;
+    })
+    .join()
+    .unwrap();
+
+    // So may a reference to a constant, which is promoted to a static; but
+    // a PrintOnDrop has a destructor, and isn't promoted:
+    let promoted: &'static i32 = &5;
+    is_static(&Session::of(promoted));
+    //
+    // let not_promoted: &'static PrintOnDrop<i32> = &print_on_drop!("sb_temp", 5); // won't compile: E0716, temporary value dropped while borrowed
+}
+
+fn keys<'a>(s: &'a str) {
+    let k = TypeKey::<String>::new();
+    assert_eq!(k.id, TypeId::of::<String>());
+    is_static(&k);
+    //
+    // let k = TypeKey::<&'a str>::new(); // won't compile: lifetime may not live long enough
+
+    let any: AnyKey<&'a str> = AnyKey { _of: PhantomData };
+    let _ = (any, s);
+    //
+    // is_static(&AnyKey::<&'a str> { _of: PhantomData }); // won't compile: E0521, borrowed data escapes outside of function
+}
+
+/// A `T: 'static` bound, like thread::spawn's, rules out types with
+/// lifetimes in them, not values that will be dropped: an owned String is
+/// 'static, moves into the thread, and is dropped there like anything
+/// else. A lifetime parameter fails the bound whether or not anything is
+/// borrowed through it, so a zero-sized Session<'a> with a PhantomData<&'a
+/// ()> can no more be spawned than the reference it stands for; a scoped
+/// thread only asks that it outlive the scope. Made from a leaked value,
+/// or a promoted constant, it's a Session<'static>, and goes anywhere.
+///
+/// A phantom `&'static T` puts the bound in the type instead, and TypeKey
+/// (with a `T: 'static` it needs for TypeId anyway) can't be named for a
+/// borrowed `T` at all. A PhantomData<fn() -> T> takes any `T`, and
+/// passes the bound on: AnyKey<&'a str> is only 'static if `'a` is.
+#[lesson(
+    title = "'static bounds: phantom lifetimes, a phantom &'static T, and what 'static isn't",
+    tags = ["lifetimes", "threads", "auto-traits"],
+    notes = [
+        "Spawning a thread with a Session<'a> of a local won't compile: E0597",
+        "`TypeKey::<&'a str>` won't compile: `T: 'static` comes with the PhantomData<&'static T>",
+    ],
+)]
+pub fn demo() {
+    recorder::take();
+    assert_eq!(spawned(), ["sb_owned"]);
+    sessions();
+    assert_eq!(recorder::take_drops(), ["sb_local"]);
+    keys(&String::from("short"));
+}
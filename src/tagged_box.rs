@@ -0,0 +1,104 @@
+// TaggedBox<T> is a MyBox that also carries a small tag (here, up to
+// 3 bits) for free, in the low bits of its pointer: a `T` aligned to
+// 2^k bytes always has its address's k low bits clear.
+//
+// The field holding the tagged pointer is no longer a valid pointer to a
+// `T`, so it is stored as a NonNull<u8> (a set tag never makes it null),
+// and PhantomData<T> is, once again, all that tells rustc the TaggedBox
+// owns a `T`.
+//
+// The tag is packed with `ptr::map_addr`, never by casting to `usize` and
+// back: that keeps the pointer's provenance, so it is fine under strict
+// provenance (and Miri's `-Zmiri-strict-provenance`).
+
+use std::alloc::{self, dealloc, Layout};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::{self, NonNull};
+
+use crate::{recorder, PrintOnDrop};
+
+pub struct TaggedBox<T> {
+    tagged: NonNull<u8>,
+    _pd: PhantomData<T>,
+}
+
+impl<T> TaggedBox<T> {
+    const TAG_BITS: u32 = 3;
+    const TAG_MASK: usize = (1 << Self::TAG_BITS) - 1;
+
+    pub fn new(t: T, tag: usize) -> Self {
+        const { assert!(mem::size_of::<T>() != 0, "TaggedBox: zero-sized T") };
+        assert!(tag <= Self::TAG_MASK, "TaggedBox: tag {} doesn't fit in {} bits", tag, Self::TAG_BITS);
+        // Allocate with at least 8-byte alignment, whatever `T` asks for.
+        let layout = Self::layout();
+        unsafe {
+            let Some(p) = NonNull::new(alloc::alloc(layout)) else {
+                alloc::handle_alloc_error(layout);
+            };
+            assert_eq!(p.addr().get() & Self::TAG_MASK, 0, "TaggedBox: misaligned allocation");
+            ptr::write(p.as_ptr() as *mut T, t);
+            TaggedBox { tagged: p.map_addr(|a| a | tag), _pd: PhantomData }
+        }
+    }
+
+    fn layout() -> Layout {
+        Layout::new::<T>().align_to(1 << Self::TAG_BITS).unwrap()
+    }
+
+    fn ptr(&self) -> *mut T {
+        self.tagged.as_ptr().map_addr(|a| a & !Self::TAG_MASK) as *mut T
+    }
+
+    pub fn tag(&self) -> usize {
+        self.tagged.addr().get() & Self::TAG_MASK
+    }
+
+    pub fn set_tag(&mut self, tag: usize) {
+        assert!(tag <= Self::TAG_MASK, "TaggedBox: tag {} doesn't fit in {} bits", tag, Self::TAG_BITS);
+        // Safety: the untagged pointer is the allocation's, so not null.
+        let untagged = unsafe { NonNull::new_unchecked(self.ptr().cast::<u8>()) };
+        self.tagged = untagged.map_addr(|a| a | tag);
+    }
+
+    pub fn get(&self) -> &T {
+        unsafe { &*self.ptr() }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.ptr() }
+    }
+}
+
+unsafe impl<#[may_dangle] T> Drop for TaggedBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let p = self.ptr();
+            ptr::drop_in_place(p);
+            dealloc(p as *mut u8, Self::layout());
+        }
+    }
+}
+
+crate::registry::demo!("TaggedBox: a tag in the pointer's low bits", tags: ["layout"]);
+
+pub fn demo() {
+    recorder::take();
+    let mut b = TaggedBox::new(PrintOnDrop::new("tagged_1", 10u8), 5);
+    assert_eq!((b.tag(), b.get().1), (5, 10));
+    b.get_mut().1 += 1;
+    b.set_tag(2);
+    assert_eq!((b.tag(), b.get().1), (2, 11));
+    println!("TaggedBox: tag {}, value {}", b.tag(), b.get().1);
+
+    // Same size as a plain pointer.
+    assert_eq!(mem::size_of::<TaggedBox<u8>>(), mem::size_of::<*mut u8>());
+
+    drop(b);
+    assert_eq!(recorder::take_drops(), ["tagged_1"]);
+
+    // Zero-sized types can't be allocated, so are turned away at compile
+    // time:
+    //
+    // TaggedBox::new((), 0); // won't compile: E0080, evaluation panicked: TaggedBox: zero-sized T
+}
@@ -0,0 +1,111 @@
+// When the temporaries made in the middle of an expression are dropped:
+// at the end of the statement that made them, as a rule, in reverse order
+// of creation; except when `let` binds a reference to one directly, which
+// extends it to the end of the block. These are the rules of edition 2021,
+// which this crate is; 2024 changes the one for a block's tail expression
+// (see `tail`).
+//
+// Each case below makes its temporaries, then marks where the statement
+// ended by dropping a PrintOnDrop of its own, so that the recorder shows
+// which came first.
+
+use lesson::lesson;
+
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// Dropped on the spot, to show when a statement has ended (or anything
+// else has happened).
+pub fn mark(name: &'static str) {
+    drop(print_on_drop!(name, 0));
+}
+
+fn sum(a: &PrintOnDrop<i32>, b: &PrintOnDrop<i32>) -> u64 {
+    mark("tmp_call");
+    a.id() + b.id()
+}
+
+fn arguments() {
+    let _ = sum(&print_on_drop!("tmp_arg1", 1), &print_on_drop!("tmp_arg2", 2));
+    mark("tmp_next");
+}
+
+fn chain() {
+    let len = print_on_drop!("tmp_chain", 1).name().len();
+    mark("tmp_next");
+    assert_eq!(len, "tmp_chain".len());
+}
+
+fn extended() {
+    {
+        let r = &print_on_drop!("tmp_extended", 1);
+        // Through tuples, struct literals and arrays too...
+        let (t, _) = (&print_on_drop!("tmp_in_tuple", 2), 0);
+        mark("tmp_in_block");
+        assert!(r.id() < t.id());
+    }
+    mark("tmp_after_block");
+
+    // ...but not through a function call: the temporary goes at the
+    // semicolon, while `r` would still borrow it.
+    //
+    // let r = std::convert::identity(&print_on_drop!("tmp_not_extended", 1)); // won't compile: E0716, temporary value dropped while borrowed
+    // mark("tmp_next");
+    // r.id();
+}
+
+fn underscore() {
+    // `_` binds nothing, so the value is a temporary of the statement...
+    let _ = print_on_drop!("tmp_underscore", 1);
+    mark("tmp_next");
+    // ...where `_x` is a variable like any other.
+    let _x = print_on_drop!("tmp_underscore_x", 2);
+    mark("tmp_end");
+}
+
+fn tail() -> u64 {
+    // The temporary in the block's tail expression outlives the block's
+    // locals (until the end of the `let`, here). From edition 2024, it
+    // goes first.
+    let id = {
+        let _local = print_on_drop!("tmp_local", 1);
+        print_on_drop!("tmp_tail", 2).id()
+    };
+    mark("tmp_next");
+    id
+}
+
+/// Temporaries made while evaluating an expression, such as a
+/// `&PrintOnDrop::new(..)` passed as an argument, or the receiver of a
+/// method chain, are dropped at the end of the statement, in reverse order
+/// of creation: after the call they were made for has returned.
+///
+/// `let r = &temporary;` is the exception: the temporary lives as long as
+/// `r`, to the end of the block, and the same goes for one a tuple, struct
+/// literal or array in the `let` borrows. A function call in between
+/// breaks the chain, and with it the extension.
+///
+/// `let _ = value;` binds nothing, so the value is dropped at once, while
+/// `let _x = value;` keeps it to the end of the scope. A block's tail
+/// expression hands its temporaries to the enclosing statement, which in
+/// edition 2021 means they outlive the block's own locals.
+#[lesson(
+    title = "Temporaries: dropped at the end of the statement, unless extended",
+    tags = ["drop-order"],
+    notes = [
+        "`let r = identity(&temporary);` won't compile if `r` is used after: E0716",
+        "`let r = &temporary;` compiles: the temporary is extended",
+    ],
+)]
+pub fn demo() {
+    recorder::take();
+    arguments();
+    assert_eq!(recorder::take_drops(), ["tmp_call", "tmp_arg2", "tmp_arg1", "tmp_next"]);
+    chain();
+    assert_eq!(recorder::take_drops(), ["tmp_chain", "tmp_next"]);
+    extended();
+    assert_eq!(recorder::take_drops(), ["tmp_in_block", "tmp_in_tuple", "tmp_extended", "tmp_after_block"]);
+    underscore();
+    assert_eq!(recorder::take_drops(), ["tmp_underscore", "tmp_next", "tmp_end", "tmp_underscore_x"]);
+    tail();
+    assert_eq!(recorder::take_drops(), ["tmp_local", "tmp_tail", "tmp_next"]);
+}
@@ -0,0 +1,85 @@
+// The last of "when is it dropped": values that live as long as a thread,
+// or as the program. A `thread_local!` value is dropped when its thread
+// exits, if it was ever made at all; a `static` is never dropped, and
+// neither is anything leaked, at exit or otherwise.
+//
+// Destructors run at thread exit, after the thread's own recorder may be
+// gone, so the demo listens with a DropObserver subscribed for every
+// thread instead.
+
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::mem;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+use lesson::lesson;
+
+use crate::observer::{subscribe, DropObserver};
+use crate::temporaries::mark;
+use crate::{print_on_drop, PrintOnDrop, State};
+
+thread_local! {
+    static SLOT: RefCell<Option<PrintOnDrop<i32>>> = const { RefCell::new(None) };
+}
+
+// Set once: replacing it, as a second run of the demo would, would drop
+// the value before.
+static FOREVER: OnceLock<PrintOnDrop<i32>> = OnceLock::new();
+
+// The demo's drops, from whichever thread.
+struct Seen(Mutex<Vec<String>>);
+
+static SEEN: Seen = Seen(Mutex::new(Vec::new()));
+
+impl DropObserver for Seen {
+    fn on_drop(&self, name: &str, _: u64, _: State, _: &dyn Debug) {
+        if name.starts_with("tl_") {
+            self.0.lock().unwrap().push(name.to_string());
+        }
+    }
+}
+
+fn seen() -> Vec<String> {
+    mem::take(&mut SEEN.0.lock().unwrap())
+}
+
+/// A `thread_local!` value is made the first time its thread uses it, and
+/// dropped when that thread exits: after the thread's closure has
+/// returned, before `join` does. A thread that never touches it never
+/// makes one, and has nothing to drop.
+///
+/// A `static` lives for the whole program, and is never dropped; a value
+/// moved into one, or leaked with Box::leak, is never dropped either, even
+/// at exit. Which is why a leaked value may be handed out as a `&'static`:
+/// nothing will ever take it away.
+#[lesson(
+    title = "thread_local! and statics: dropped at thread exit, or never",
+    tags = ["drop-order", "threads"],
+    notes = [
+        "Statics and leaked values are never dropped, on any platform",
+        "The main thread's thread_locals may not be dropped when it exits: not on Unix with pthread-based TLS, for one",
+        "Other threads' are dropped in no particular order, and using one from another's destructor may fail",
+    ],
+)]
+pub fn demo() {
+    let _seen = subscribe(&SEEN);
+
+    thread::spawn(|| {
+        SLOT.with(|s| *s.borrow_mut() = Some(print_on_drop!("tl_thread", 1)));
+        mark("tl_thread_returns");
+    })
+    .join()
+    .unwrap();
+    assert_eq!(seen(), ["tl_thread_returns", "tl_thread"]);
+
+    // Never touched, so never made.
+    thread::spawn(|| mark("tl_untouched")).join().unwrap();
+    assert_eq!(seen(), ["tl_untouched"]);
+
+    FOREVER.get_or_init(|| print_on_drop!("tl_static", 2));
+    let leaked: &'static PrintOnDrop<i32> = Box::leak(Box::new(print_on_drop!("tl_leaked", 3)));
+    thread::spawn(move || assert_eq!(leaked.name(), "tl_leaked")).join().unwrap();
+    // Neither has been dropped, nor will be, even after main returns.
+    assert!(seen().is_empty());
+}
@@ -0,0 +1,269 @@
+// Traces (what the recorder saw) and demo metadata, as JSON (see json.rs),
+// so that they can be saved, diffed and loaded again, by this program or
+// by anything else.
+//
+// ToJson and FromJson play the part of serde's Serialize and Deserialize,
+// and the JSON is what serde's derives would make of the same types, with
+// enums externally tagged. A trace is one object, on one line, so that a
+// file of them diffs a line per trace:
+//
+//     {"demo":"trace","lost":0,"events":[{"Drop":["tr_a",7,"Valid"]},{"Guard":"tr_guard"}]}
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::json::{self, Value};
+use crate::recorder::{self, Event};
+use crate::registry::Demo;
+use crate::scope_guard::{defer, scope};
+use crate::{print_on_drop, State};
+
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+pub trait FromJson: Sized {
+    fn from_json(v: &Value) -> Result<Self, LoadError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LoadError {
+    Json(json::ParseError),
+    /// Valid JSON, but not what was expected: what was expected instead.
+    Format(&'static str),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Json(e) => write!(f, "invalid JSON: {}", e),
+            LoadError::Format(expected) => write!(f, "expected {}", expected),
+        }
+    }
+}
+
+/// Parses `s` as JSON, then as a `T`.
+pub fn load<T: FromJson>(s: &str) -> Result<T, LoadError> {
+    T::from_json(&json::parse(s).map_err(LoadError::Json)?)
+}
+
+/// The events one demo left in the recorder.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    pub demo: String,
+    /// How many events the recorder had to forget; see Overflow.
+    pub lost: usize,
+    pub events: Vec<Event>,
+}
+
+impl Trace {
+    /// Takes everything recorded so far on this thread, as `demo`'s trace.
+    pub fn take(demo: &str) -> Trace {
+        let lost = recorder::lost();
+        Trace { demo: demo.to_string(), lost, events: recorder::take() }
+    }
+
+    /// What was dropped, in order, as `recorder::take_drops` gives it.
+    pub fn drops(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|e| match e {
+                Event::Drop(name, ..) | Event::Guard(name) | Event::Traced(name) => Some(&**name),
+                Event::Enter(_) | Event::Exit(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// A Demo, with what is `&'static` in it owned, so that it can be loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DemoInfo {
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub expected_drops: Option<Vec<String>>,
+    /// Whether the expected drops must come in the order given, rather
+    /// than in any order. Meaningless without them.
+    pub drops_in_order: bool,
+    pub unsound: bool,
+    /// The lesson's explanation, a paragraph at a time, if it has one.
+    pub explanation: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+impl From<&Demo> for DemoInfo {
+    fn from(d: &Demo) -> Self {
+        let strings = |s: &[&str]| s.iter().map(|s| s.to_string()).collect();
+        DemoInfo {
+            name: d.name().to_string(),
+            description: d.description.to_string(),
+            tags: strings(d.tags),
+            expected_drops: d.expected_drops.map(|e| strings(e.drops())),
+            drops_in_order: d.expected_drops.is_none_or(|e| e.in_order()),
+            unsound: d.unsound,
+            explanation: d.lesson.map(|l| l.paragraphs()).unwrap_or_default(),
+            notes: d.lesson.map(|l| strings(l.notes)).unwrap_or_default(),
+        }
+    }
+}
+
+impl ToJson for State {
+    fn to_json(&self) -> Value {
+        Value::from(match self {
+            State::INVALID => "INVALID",
+            State::Valid => "Valid",
+        })
+    }
+}
+
+impl FromJson for State {
+    fn from_json(v: &Value) -> Result<Self, LoadError> {
+        match v.as_str() {
+            Some("INVALID") => Ok(State::INVALID),
+            Some("Valid") => Ok(State::Valid),
+            _ => Err(LoadError::Format("\"INVALID\" or \"Valid\"")),
+        }
+    }
+}
+
+impl ToJson for Event {
+    fn to_json(&self) -> Value {
+        let (variant, v) = match self {
+            Event::Drop(name, id, state) => {
+                ("Drop", Value::Array(vec![Value::from(&**name), Value::from(*id), state.to_json()]))
+            }
+            Event::Guard(name) => ("Guard", Value::from(&**name)),
+            Event::Traced(name) => ("Traced", Value::from(&**name)),
+            Event::Enter(name) => ("Enter", Value::from(&**name)),
+            Event::Exit(name) => ("Exit", Value::from(&**name)),
+        };
+        Value::Object(vec![(variant.to_string(), v)])
+    }
+}
+
+impl FromJson for Event {
+    fn from_json(v: &Value) -> Result<Self, LoadError> {
+        let name = |v: &Value| v.as_str().map(|s| Cow::Owned(s.to_string()));
+        let Value::Object(fields) = v else {
+            return Err(LoadError::Format("an event"));
+        };
+        let e = match &fields[..] {
+            [(variant, v)] => match (variant.as_str(), v) {
+                ("Drop", Value::Array(fields)) => match &fields[..] {
+                    [n, id, state] => name(n)
+                        .zip(id.as_u64())
+                        .map(|(n, id)| State::from_json(state).map(|s| Event::Drop(n, id, s))),
+                    _ => None,
+                },
+                ("Guard", v) => name(v).map(|n| Ok(Event::Guard(n))),
+                ("Traced", v) => name(v).map(|n| Ok(Event::Traced(n))),
+                ("Enter", v) => name(v).map(|n| Ok(Event::Enter(n))),
+                ("Exit", v) => name(v).map(|n| Ok(Event::Exit(n))),
+                _ => None,
+            },
+            _ => None,
+        };
+        e.unwrap_or(Err(LoadError::Format("a Drop, Guard, Traced, Enter or Exit event")))
+    }
+}
+
+impl ToJson for Trace {
+    fn to_json(&self) -> Value {
+        Value::Object(vec![
+            ("demo".to_string(), Value::from(&*self.demo)),
+            ("lost".to_string(), Value::from(self.lost as u64)),
+            ("events".to_string(), Value::Array(self.events.iter().map(Event::to_json).collect())),
+        ])
+    }
+}
+
+impl FromJson for Trace {
+    fn from_json(v: &Value) -> Result<Self, LoadError> {
+        let demo = v.get("demo").and_then(Value::as_str).ok_or(LoadError::Format("a trace's demo"))?;
+        let lost = v.get("lost").and_then(Value::as_u64).ok_or(LoadError::Format("a trace's lost"))?;
+        let events = v.get("events").and_then(Value::as_array).ok_or(LoadError::Format("a trace's events"))?;
+        Ok(Trace {
+            demo: demo.to_string(),
+            lost: lost as usize,
+            events: events.iter().map(Event::from_json).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn strings(s: &[String]) -> Value {
+    Value::Array(s.iter().map(|s| Value::from(&**s)).collect())
+}
+
+fn from_strings(v: Option<&Value>, expected: &'static str) -> Result<Vec<String>, LoadError> {
+    let items = v.and_then(Value::as_array).ok_or(LoadError::Format(expected))?;
+    items.iter().map(|s| s.as_str().map(str::to_string).ok_or(LoadError::Format(expected))).collect()
+}
+
+impl ToJson for DemoInfo {
+    fn to_json(&self) -> Value {
+        Value::Object(vec![
+            ("name".to_string(), Value::from(&*self.name)),
+            ("description".to_string(), Value::from(&*self.description)),
+            ("tags".to_string(), strings(&self.tags)),
+            ("expected_drops".to_string(), self.expected_drops.as_deref().map_or(Value::Null, strings)),
+            ("drops_in_order".to_string(), Value::Bool(self.drops_in_order)),
+            ("unsound".to_string(), Value::Bool(self.unsound)),
+            ("explanation".to_string(), strings(&self.explanation)),
+            ("notes".to_string(), strings(&self.notes)),
+        ])
+    }
+}
+
+impl FromJson for DemoInfo {
+    fn from_json(v: &Value) -> Result<Self, LoadError> {
+        let string = |key, expected| {
+            v.get(key).and_then(Value::as_str).map(str::to_string).ok_or(LoadError::Format(expected))
+        };
+        Ok(DemoInfo {
+            name: string("name", "a demo's name")?,
+            description: string("description", "a demo's description")?,
+            tags: from_strings(v.get("tags"), "a demo's tags")?,
+            expected_drops: match v.get("expected_drops") {
+                Some(Value::Null) => None,
+                drops => Some(from_strings(drops, "a demo's expected drops, or null")?),
+            },
+            drops_in_order: v
+                .get("drops_in_order")
+                .and_then(Value::as_bool)
+                .ok_or(LoadError::Format("a demo's drops_in_order"))?,
+            unsound: v.get("unsound").and_then(Value::as_bool).ok_or(LoadError::Format("a demo's unsound"))?,
+            explanation: from_strings(v.get("explanation"), "a demo's explanation")?,
+            notes: from_strings(v.get("notes"), "a demo's notes")?,
+        })
+    }
+}
+
+crate::registry::demo!("Traces and demo metadata, saved as JSON and loaded again", tags: ["drop-order"]);
+
+pub fn demo() {
+    {
+        scope!("tr_scope");
+        let _a = print_on_drop!("tr_a", 13);
+        defer!("tr_guard", {});
+        // Names may be anything, and must come back just as they were.
+        let _b = print_on_drop!("tr_b", 42);
+        let _c = print_on_drop!("tr_\"quoted\"\\\u{1f980}", 0);
+    }
+    let trace = Trace::take("trace");
+    let saved = trace.to_json().to_string();
+    println!("Trace: {}", saved);
+    assert_eq!(load::<Trace>(&saved), Ok(trace));
+
+    // Every demo's metadata, likewise.
+    for d in crate::registry::all() {
+        let info = DemoInfo::from(d);
+        assert_eq!(load::<DemoInfo>(&info.to_json().to_string()), Ok(info));
+    }
+
+    assert_eq!(
+        load::<Event>(r#"{"Drop":["x",1,"Gone"]}"#),
+        Err(LoadError::Format("\"INVALID\" or \"Valid\""))
+    );
+    assert!(matches!(load::<Event>(r#"{"Guard":"x""#), Err(LoadError::Json(_))));
+    assert_eq!(load::<Event>(r#" {"Guard" : "🦀"} "#), Ok(Event::Guard(Cow::Borrowed("\u{1f980}"))));
+}
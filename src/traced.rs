@@ -0,0 +1,63 @@
+// `#[derive(DropTrace)]` (from the drop_trace crate in this workspace)
+// records a type's drops in the recorder, under the type's name, so that
+// a demo can check drop order for its own types without wrapping each in
+// a PrintOnDrop.
+
+use std::fmt::Debug;
+
+use drop_trace::DropTrace;
+use lesson::lesson;
+
+use crate::{recorder, PrintOnDrop};
+
+#[derive(DropTrace)]
+struct Plain {
+    _n: u32,
+}
+
+#[derive(DropTrace)]
+struct Tuple<'a, T: Debug, const N: usize>(&'a [T; N]);
+
+#[derive(DropTrace)]
+enum Either<L, R = ()>
+where
+    L: Clone,
+{
+    Left(L),
+    #[allow(dead_code)]
+    Right(R),
+}
+
+/// Three types with derived DropTrace impls, one of them generic over a
+/// lifetime, a type and a const, another with a default and a where
+/// clause. Dropped at the end of a block, they are recorded in reverse
+/// order of declaration, under their own names.
+///
+/// The derived Drop has no #[may_dangle], so what a Tuple borrows must
+/// outlive it.
+#[lesson(
+    title = "#[derive(DropTrace)] on the demo's own types",
+    tags = ["drop-order", "macros"],
+    notes = ["`let (_t, v2)` won't compile: `v2` does not live long enough"],
+)]
+pub fn demo() {
+    let v = [PrintOnDrop::new("tr_v", 13)];
+    recorder::take();
+    {
+        let _e = Either::<String>::Left("left".to_string());
+        let t = Tuple(&v);
+        assert_eq!(t.0.len(), 1);
+        let _p = Plain { _n: 1 };
+    }
+    assert_eq!(recorder::take_drops(), ["Plain", "Tuple", "Either"]);
+    if let Either::Left(s) = Either::<&str, u8>::Left("x") {
+        assert_eq!(s, "x");
+    }
+
+    // The derived Drop has no #[may_dangle], so dropck now insists that
+    // what a Tuple borrows outlives it, exactly as for MyBox3:
+    //
+    // let (_t, v2); // won't compile: `v2` does not live long enough
+    // v2 = [PrintOnDrop::new("v2", 13)];
+    // _t = Tuple(&v2);
+}
@@ -0,0 +1,103 @@
+// A database-flavored example: a Transaction<'conn> stages writes against
+// a Connection, and either commits them or, if dropped without
+// committing, rolls them back.
+//
+// The transaction keeps only a raw pointer to its connection (as it might
+// if the connection were really a C client handle). What makes it safe is
+// the PhantomData<&'conn mut Connection>: for as long as a transaction
+// lives, the connection is *exclusively* borrowed, so there can't be a
+// second transaction, a direct query, or a `close`, until it is gone.
+
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+pub struct Connection {
+    rows: Vec<(&'static str, i32)>,
+}
+
+impl Connection {
+    pub fn open() -> Self {
+        Connection { rows: Vec::new() }
+    }
+
+    pub fn begin(&mut self) -> Transaction<'_> {
+        Transaction { conn: NonNull::from(self), pending: Vec::new(), _pd: PhantomData }
+    }
+
+    pub fn count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn close(self) {
+        println!("Connection: closed with {} rows", self.rows.len());
+    }
+}
+
+pub struct Transaction<'conn> {
+    conn: NonNull<Connection>,
+    pending: Vec<(&'static str, i32)>,
+    _pd: PhantomData<&'conn mut Connection>,
+}
+
+impl Transaction<'_> {
+    pub fn insert(&mut self, key: &'static str, value: i32) {
+        self.pending.push((key, value));
+    }
+
+    pub fn commit(mut self) {
+        // Safety: the phantom `&mut` guarantees nobody else is using the
+        // connection, and that it is still open.
+        let conn = unsafe { self.conn.as_mut() };
+        conn.rows.append(&mut self.pending);
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            println!("Transaction: rolled back {} rows", self.pending.len());
+        }
+    }
+}
+
+crate::registry::demo! {
+    "Transaction: commit or roll back on drop",
+    tags: ["drop-order", "lifetimes"],
+}
+
+pub fn demo() {
+    let mut conn = Connection::open();
+
+    let mut tx = conn.begin();
+    tx.insert("alice", 1);
+    tx.insert("bob", 2);
+    tx.commit();
+
+    let mut tx = conn.begin();
+    tx.insert("carol", 3);
+    drop(tx); // rolled back
+
+    assert_eq!(conn.count(), 2);
+    conn.close();
+
+    // Two transactions at once:
+    //
+    // let mut conn = Connection::open();
+    // let t1 = conn.begin();
+    // let t2 = conn.begin(); // won't compile: cannot borrow `conn` as mutable more than once
+    // t1.commit();
+    // t2.commit();
+    //
+    // Closing the connection under a live transaction:
+    //
+    // let mut conn = Connection::open();
+    // let mut tx = conn.begin();
+    // conn.close(); // won't compile: cannot move out of `conn` because it is borrowed
+    // tx.insert("dave", 4);
+    //
+    // It is `begin(&mut self)` that takes the exclusive borrow; the
+    // PhantomData is what keeps it alive for as long as the transaction
+    // is. Without `'conn`, `begin` could hand out a Transaction that
+    // borrows nothing, and both of the above would compile, the second
+    // leaving `tx` pointing at a connection that no longer exists.
+}
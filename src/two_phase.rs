@@ -0,0 +1,89 @@
+// `v.push(v.len())` takes `&mut v` for the call before its argument
+// reads `v`, and compiles anyway: an autoref'd `&mut` receiver is a
+// two-phase borrow, only *reserved* while the arguments are evaluated
+// (when shared uses of `v` are still allowed), and *activated*, as a
+// mutable borrow proper, when the call is made. That comes of the method
+// call syntax, not of anything Vec does, so MyVec gets it too, for every
+// `&mut self` method, and so does a `&mut MyVec` reborrowed for a call.
+//
+// What a reservation allows is reading. A mutable use of `v` in the
+// arguments conflicts with it, and a `&mut v` written out, as in
+// `MyVec::push(&mut v, ..)`, is an ordinary borrow from the start. Nor
+// does indexing get it: `v[v.len() - 1] = x` doesn't compile, for Vec or
+// anything else with an IndexMut (MyVec has none).
+
+use lesson::lesson;
+
+use crate::my_vec::MyVec;
+use crate::{print_on_drop, recorder};
+
+// The same calls, on MyVec and on Vec: each argument reads the vector
+// that is already reserved for the call.
+fn my_vec() -> Vec<usize> {
+    let mut v = MyVec::new();
+    v.push(v.len());
+    v.push(v.len());
+    v.reserve(v.capacity());
+    v.push(v.capacity());
+    v.truncate(v.len() - 1);
+    let r = &mut v;
+    r.push(r.len());
+    v.iter().copied().collect()
+}
+
+fn std_vec() -> Vec<usize> {
+    let mut v = Vec::new();
+    v.push(v.len());
+    v.push(v.len());
+    v.reserve(v.capacity());
+    v.push(v.capacity());
+    v.truncate(v.len() - 1);
+    let r = &mut v;
+    r.push(r.len());
+    v
+}
+
+// What doesn't compile, for MyVec as for Vec:
+//
+// fn rejected() {
+//     let mut v = MyVec::new();
+//     MyVec::push(&mut v, v.len()); // won't compile: E0502, cannot borrow `v` as immutable because it is also borrowed as mutable
+//     v.push({ v.push(0); 1 }); // won't compile: E0499, cannot borrow `v` as mutable more than once at a time
+//     let mut w = vec![0];
+//     w[w.len() - 1] = 1; // won't compile: E0502, cannot borrow `w` as immutable because it is also borrowed as mutable
+// }
+
+/// `v.push(v.len())` compiles, though `v` is borrowed mutably for the
+/// call before `v.len()` is evaluated: the implicit `&mut v` of a method
+/// call is a two-phase borrow, reserved while the arguments are worked
+/// out, and only then activated. It's the method call that makes it so,
+/// so MyVec's `&mut self` methods all allow it, just as Vec's do, and the
+/// arguments see the vector as it was before the call.
+///
+/// Only shared uses fit in a reservation: pushing to `v` while working
+/// out what to push to it is still two mutable borrows. And neither a
+/// written-out `&mut v` nor IndexMut gets a second phase.
+#[lesson(
+    title = "Two-phase borrows: `v.push(v.len())` on MyVec, as on Vec",
+    tags = ["lifetimes", "collections"],
+    notes = [
+        "`MyVec::push(&mut v, v.len())` won't compile: E0502",
+        "A mutable use of `v` in the arguments of `v.push` won't compile: E0499",
+        "`w[w.len() - 1] = 1` won't compile, even for Vec: E0502",
+    ],
+)]
+pub fn demo() {
+    assert_eq!(my_vec(), [0, 1, 2]);
+    assert_eq!(my_vec(), std_vec());
+
+    // The argument is evaluated before the push, so each name has the
+    // length from before its own element.
+    recorder::take();
+    let mut v = MyVec::new();
+    for _ in 0..3 {
+        v.push(print_on_drop!(format!("tp_{}", v.len()), ()));
+    }
+    assert!(recorder::take_drops().is_empty());
+    drop(v);
+    assert_eq!(recorder::take_drops(), ["tp_0", "tp_1", "tp_2"]);
+}
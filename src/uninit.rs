@@ -0,0 +1,159 @@
+// MyBox3::new(t) builds `t` on the stack and then moves it to the heap.
+// For a big `T` that's a big copy, and in a debug build can be enough to
+// overflow the stack before we ever get to the heap. `new_uninit` turns the
+// order around: allocate first, as a MyBox3<MaybeUninit<T>>, then write
+// the value straight into the allocation, then `assume_init` it.
+//
+// MaybeUninit<T> has no drop glue, so a MyBox3<MaybeUninit<T>> owns the
+// memory but no `T`: dropping one before it was initialized frees the
+// allocation and drops nothing. It's `assume_init` that turns "memory for
+// a `T`" into "a `T`", and with it what MyBox3's PhantomData promises
+// dropck.
+//
+// `new_zeroed` is the same, via alloc_zeroed: the memory starts out all
+// zeroes, which for some types is already a valid value (and the
+// allocator may get zeroed pages for free). For which ones is a property
+// of the type, so rather than leave every caller to `assume_init` and
+// hope, `Zeroable` records it, and `zeroed` is safe for `T: Zeroable`.
+
+use std::alloc::{self, Layout};
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ptr::NonNull;
+
+use crate::my_unique::MyUnique;
+use crate::{recorder, MyBox3, PrintOnDrop};
+
+impl<T> MyBox3<T> {
+    pub fn new_uninit() -> MyBox3<MaybeUninit<T>> {
+        let layout = Layout::new::<T>();
+        let p = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            let Some(p) = NonNull::new(unsafe { alloc::alloc(layout) }) else {
+                alloc::handle_alloc_error(layout);
+            };
+            p.cast()
+        };
+        MyBox3 { v: MyUnique::new(p) }
+    }
+
+    pub fn new_zeroed() -> MyBox3<MaybeUninit<T>> {
+        let layout = Layout::new::<T>();
+        let p = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            let Some(p) = NonNull::new(unsafe { alloc::alloc_zeroed(layout) }) else {
+                alloc::handle_alloc_error(layout);
+            };
+            p.cast()
+        };
+        MyBox3 { v: MyUnique::new(p) }
+    }
+
+    pub fn zeroed() -> Self
+    where
+        T: Zeroable,
+    {
+        unsafe { MyBox3::new_zeroed().assume_init() }
+    }
+}
+
+/// # Safety
+///
+/// All-zero bytes must be a valid `Self`.
+pub unsafe trait Zeroable {}
+
+unsafe impl Zeroable for u8 {}
+unsafe impl Zeroable for u32 {}
+unsafe impl Zeroable for u64 {}
+unsafe impl Zeroable for bool {}
+unsafe impl<T: Zeroable, const N: usize> Zeroable for [T; N] {}
+// Zero is the null pointer, which the niche makes `None`.
+unsafe impl<T> Zeroable for Option<NonNull<T>> {}
+// Only with a niche: a MyUnique that is a `*const T` has none (see
+// my_unique.rs), and then zero is a tag that needn't mean `None`.
+#[cfg(not(repr = "const-ptr"))]
+unsafe impl<T> Zeroable for Option<MyBox3<T>> {}
+
+impl<T> MyBox3<MaybeUninit<T>> {
+    /// # Safety
+    ///
+    /// The value must have been fully initialized.
+    pub unsafe fn assume_init(self) -> MyBox3<T> {
+        // Hand the allocation over without freeing it.
+        let this = ManuallyDrop::new(self);
+        MyBox3 { v: MyUnique::new(this.v.as_non_null().cast()) }
+    }
+}
+
+const PIXELS: usize = 1 << 20;
+
+struct Image {
+    width: usize,
+    pixels: [u32; PIXELS],
+    label: PrintOnDrop<usize>,
+}
+
+crate::registry::demo!("new_uninit, new_zeroed and Zeroable", tags: ["alloc", "unsafe"]);
+
+pub fn demo() {
+    // A 4 MiB image, never on the stack. Each field is written through a
+    // raw pointer to it: making a `&mut Image` (or even a `&mut [u32]`)
+    // while the memory is still uninitialized would be UB.
+    let mut b = MyBox3::<Image>::new_uninit();
+    let img = MaybeUninit::as_mut_ptr(&mut *b);
+    unsafe {
+        (&raw mut (*img).width).write(1024);
+        let pixels = (&raw mut (*img).pixels).cast::<u32>();
+        for i in 0..PIXELS {
+            pixels.add(i).write(i as u32);
+        }
+        (&raw mut (*img).label).write(PrintOnDrop::new("uninit_img", PIXELS));
+    }
+    let img = unsafe { b.assume_init() };
+    assert_eq!((img.width, img.pixels[PIXELS - 1], img.label.1), (1024, PIXELS as u32 - 1, PIXELS));
+
+    // Whereas this (in a debug build) builds the whole Image on the stack
+    // first, and may never get as far as allocating:
+    //
+    // let img = MyBox3::new(Image { width: 1024, pixels: [0; PIXELS], label: PrintOnDrop::new("img", 0) });
+
+    // Dropping the MyBox3<Image> drops its label...
+    recorder::take();
+    drop(img);
+    assert_eq!(recorder::take_drops(), ["uninit_img"]);
+
+    // ...but a MyBox3<MaybeUninit<_>> never drops anything, initialized
+    // or not: it owns the memory, not a value.
+    let mut b = MyBox3::<PrintOnDrop<u32>>::new_uninit();
+    b.write(PrintOnDrop::new("uninit_forgotten", 1));
+    drop(b);
+    assert!(recorder::take_drops().is_empty());
+
+    // All zeroes, which is a valid value for these...
+    let counters = MyBox3::<[u64; PIXELS]>::zeroed();
+    assert!(counters.iter().all(|&c| c == 0));
+    #[cfg(not(repr = "const-ptr"))]
+    {
+        let slots = MyBox3::<[Option<MyBox3<u32>>; 16]>::zeroed();
+        assert!(slots.iter().all(Option::is_none));
+    }
+    let flags = MyBox3::<[bool; 8]>::zeroed();
+    assert_eq!(*flags, [false; 8]);
+
+    // ...but not for a NonNull, a reference, or anything holding one;
+    // those have no Zeroable impl:
+    //
+    // let _ = MyBox3::<MyBox3<u32>>::zeroed(); // won't compile: E0277, the trait bound `MyBox3<u32>: Zeroable` is not satisfied
+    // let _ = MyBox3::<&u32>::zeroed(); // won't compile: E0277, the trait bound `&u32: Zeroable` is not satisfied
+    //
+    // new_zeroed itself is fine for any `T`: it's assume_init that would
+    // be UB here. Read as an Option, the same bytes are a `None`.
+    let b = MyBox3::<NonNull<u32>>::new_zeroed();
+    let p = unsafe { MaybeUninit::as_ptr(&*b).cast::<Option<NonNull<u32>>>().read() };
+    assert!(p.is_none());
+
+    // Zero-sized values get no allocation at all.
+    let z = unsafe { MyBox3::<()>::new_uninit().assume_init() };
+    assert_eq!(*z, ());
+}
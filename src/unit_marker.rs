@@ -0,0 +1,137 @@
+// "Can't I just use `()`?" Three boxes, identical but for one field, each
+// with the same eyepatched Drop (`#[may_dangle] T`) that MyBox3 has:
+//
+//   - WithPhantom<T>: a PhantomData<T> (MyBox3's design);
+//   - WithUnit<T>: a `()` in its place;
+//   - Bare<T>: nothing in its place (MyBox2's design).
+//
+// All three are one pointer in size, with the same niche, and cost the
+// same to make and drop: the marker is free either way. But `()` says
+// nothing about `T`, any more than no field does, so only WithPhantom
+// tells dropck that dropping it drops a `T`. The other two take a
+// payload that borrows something dropped before them, exactly as MyBox2
+// does; see the `with_unit` pair in dropck_pairs.rs for what that does
+// when run.
+
+use std::hint::black_box;
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of};
+use std::ptr::NonNull;
+use std::time::{Duration, Instant};
+
+use lesson::lesson;
+
+use crate::{print_on_drop, recorder};
+
+macro_rules! eyepatched_box {
+    ($name:ident $(, $marker:ident: $t:ty = $v:expr)?) => {
+        pub struct $name<T> {
+            ptr: NonNull<T>,
+            $($marker: $t,)?
+        }
+
+        impl<T> $name<T> {
+            pub fn new(t: T) -> Self {
+                $name { ptr: NonNull::from(Box::leak(Box::new(t))), $($marker: $v,)? }
+            }
+        }
+
+        unsafe impl<#[may_dangle] T> Drop for $name<T> {
+            fn drop(&mut self) {
+                unsafe { drop(Box::from_raw(self.ptr.as_ptr())) };
+            }
+        }
+    };
+}
+
+eyepatched_box!(WithPhantom, _owns: PhantomData<T> = PhantomData);
+eyepatched_box!(WithUnit, _owns: () = ());
+eyepatched_box!(Bare);
+
+// Laid out alike, whatever `T` is.
+const _: () = {
+    macro_rules! alike {
+        ($($t:ty),*) => {$(
+            assert!(size_of::<WithPhantom<$t>>() == size_of::<usize>());
+            assert!(size_of::<WithUnit<$t>>() == size_of::<usize>());
+            assert!(size_of::<Bare<$t>>() == size_of::<usize>());
+            assert!(align_of::<WithPhantom<$t>>() == align_of::<WithUnit<$t>>());
+            assert!(align_of::<WithUnit<$t>>() == align_of::<Bare<$t>>());
+            assert!(size_of::<Option<WithPhantom<$t>>>() == size_of::<usize>());
+            assert!(size_of::<Option<WithUnit<$t>>>() == size_of::<usize>());
+            assert!(size_of::<Option<Bare<$t>>>() == size_of::<usize>());
+        )*};
+    }
+    alike!(u8, u128, (), String);
+};
+
+// Not a rigorous benchmark, just enough to see that neither marker costs
+// anything to make or drop.
+fn time<B>(make: impl Fn(u64) -> B, n: u64) -> Duration {
+    let start = Instant::now();
+    for i in 0..n {
+        drop(black_box(make(black_box(i))));
+    }
+    start.elapsed()
+}
+
+// Container first, with a payload borrowing the value: `v` is dropped
+// before the box, whose Drop could see it dangling. Only the PhantomData
+// makes dropck refuse:
+//
+// fn phantom_container_first() {
+//     let (_b, v);
+//     print_on_drop!(v = 13);
+//     _b = WithPhantom::new(print_on_drop!("um_payload", &v)); // won't compile: E0597, `v` does not live long enough
+// }
+//
+// The other two compile, and are unsound. They're never called here.
+#[allow(dead_code)]
+fn unit_container_first() {
+    let (_b, v);
+    print_on_drop!(v = 13);
+    _b = WithUnit::new(print_on_drop!("um_payload", &v));
+}
+
+#[allow(dead_code)]
+fn bare_container_first() {
+    let (_b, v);
+    print_on_drop!(v = 13);
+    _b = Bare::new(print_on_drop!("um_payload", &v));
+}
+
+/// A PhantomData<T>, a `()`, and no field at all are the same to the
+/// machine: each of these boxes is one pointer, with a niche, and they
+/// take the same time to make and drop. To the compiler they differ. With
+/// an eyepatched Drop, dropck only counts `T` as dropped if a field says
+/// the box owns one, and PhantomData<T> is the only one of the three that
+/// mentions `T`. A `()` is a unit value of its own, marking nothing:
+/// WithUnit is accepted with a payload that borrows a value dropped before
+/// it, like Bare (and MyBox2), and reads it dangling when it goes.
+#[lesson(
+    title = "PhantomData<T> versus a `()` field versus nothing: same layout, different dropck",
+    tags = ["dropck", "layout", "bench"],
+    notes = [
+        "WithPhantom, container first, with a borrowing payload won't compile: E0597",
+        "WithUnit and Bare compile in that order, and are unsound (see with_unit_container_first)",
+    ],
+)]
+pub fn demo() {
+    // Value first, all three are fine, and drop alike.
+    recorder::take();
+    {
+        let v = print_on_drop!("um_v", 13);
+        let _p = WithPhantom::new(print_on_drop!("um_phantom", &v));
+        let _u = WithUnit::new(print_on_drop!("um_unit", &v));
+        let _b = Bare::new(print_on_drop!("um_bare", &v));
+    }
+    assert_eq!(recorder::take_drops(), ["um_bare", "um_unit", "um_phantom", "um_v"]);
+
+    let n = 1_000_000;
+    println!(
+        "PhantomData vs (): {:?} for a million WithPhantom<u64>s, {:?} for WithUnit, {:?} for Bare",
+        time(WithPhantom::new, n),
+        time(WithUnit::new, n),
+        time(Bare::new, n)
+    );
+}
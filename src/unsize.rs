@@ -0,0 +1,76 @@
+// PhantomData tells rustc that MyBox3 owns a `T`. What it doesn't do is
+// make MyBox3 a first-class pointer: Box<Square> turns into Box<dyn Shape>
+// by itself, and `self: Box<Self>` works as a method receiver on a
+// `dyn Shape`. For MyBox3 (and the MyUnique in it) both take an unstable
+// trait impl each:
+//
+// - CoerceUnsized: MyBox3<T> -> MyBox3<U> whenever `T: Unsize<U>`,
+//   e.g. `[i32; 3]` to `[i32]`, or `Square` to `dyn Shape`. The pointer
+//   gains a length or vtable; PhantomData<T> becomes PhantomData<U>.
+// - DispatchFromDyn: a method taking `self: MyBox3<Self>` can be called
+//   on a MyBox3<dyn Shape>, by turning it back into a thin pointer for
+//   the concrete type's implementation. (Using MyBox3 as a receiver at
+//   all, even on concrete types, also still needs arbitrary_self_types.)
+//
+// MyBox3's Drop already works for unsized `T`: it takes the layout from
+// the value, rather than from `T`.
+
+use std::fmt::Debug;
+
+use crate::{recorder, MyBox3, PrintOnDrop};
+
+trait Shape {
+    fn area(&self) -> f64;
+
+    // Needs DispatchFromDyn to be callable on a MyBox3<dyn Shape>.
+    fn into_area(self: MyBox3<Self>) -> f64;
+}
+
+struct Square(f64);
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+
+    fn into_area(self: MyBox3<Self>) -> f64 {
+        self.area()
+    }
+}
+
+struct Circle(f64);
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.0 * self.0 * self.0
+    }
+
+    fn into_area(self: MyBox3<Self>) -> f64 {
+        self.area()
+    }
+}
+
+crate::registry::demo!("Unsizing MyBox3: to dyn Trait and slices", tags: ["unsize"]);
+
+pub fn demo() {
+    let shapes: [MyBox3<dyn Shape>; 2] = [MyBox3::new(Square(2.0)), MyBox3::new(Circle(1.0))];
+    let total: f64 = shapes.into_iter().map(|s| s.into_area()).sum();
+    assert_eq!(total, 7.0);
+
+    let xs: MyBox3<[i32]> = MyBox3::new([1, 2, 3]);
+    assert_eq!((xs.len(), xs.iter().sum::<i32>()), (3, 6));
+    println!("unsize: total area {}, slice {:?}", total, &*xs);
+
+    // Dropping through the vtable runs the concrete type's Drop.
+    recorder::take();
+    let d: MyBox3<dyn Debug> = MyBox3::new(PrintOnDrop::new("unsize_1", 1));
+    println!("unsize: {:?}", &*d);
+    drop(d);
+    assert_eq!(recorder::take_drops(), ["unsize_1"]);
+
+    // Without the CoerceUnsized impls, the first line fails; without the
+    // DispatchFromDyn ones, so does any use of `dyn Shape`:
+    //
+    // let s: MyBox3<dyn Shape> = MyBox3::new(Square(2.0)); // won't compile: E0308, mismatched types
+    // s.into_area(); // won't compile: E0038, the trait `Shape` is not dyn compatible
+}
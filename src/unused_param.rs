@@ -0,0 +1,182 @@
+// "parameter `T` is never used" (E0392) is where most people meet
+// PhantomData: a type parameter (or lifetime) that no field mentions is
+// an error, since without a field the compiler has no way to work out
+// the type's variance, auto traits or drop check in it. rustc suggests
+// "a marker such as `PhantomData`", but not which one; each says
+// something different about how the type relates to `T`:
+//
+//   - PhantomData<T>: owns a `T`. Covariant, Send and Sync as `T` is,
+//     and dropck takes dropping it as dropping a `T` (see vec_dropck.rs).
+//   - PhantomData<&'a T>: borrows a `T` for `'a`. Covariant in both, and
+//     Send as a `&T` is; dropping it drops no `T`.
+//   - PhantomData<&'a mut T>: borrows it mutably, and so is invariant in
+//     `T`.
+//   - PhantomData<fn() -> T>: only makes, or names, a `T`. Covariant, and
+//     Send and Sync always.
+//   - PhantomData<fn(T)>: only takes a `T`. Contravariant.
+//   - PhantomData<fn(T) -> T>: both, and so invariant.
+//
+// Each of the fixes below asserts its variance (variance_tests.rs) and
+// whether it is Send (auto_trait_tests.rs), and the demo shows which
+// drops a `T`.
+
+use std::marker::PhantomData;
+use std::mem::{needs_drop, size_of};
+use std::ptr::NonNull;
+
+use lesson::lesson;
+
+use crate::auto_trait_tests::{assert_impl_send, assert_not_impl_send};
+use crate::variance_tests::{assert_contravariant, assert_covariant, assert_invariant};
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// The errors:
+//
+// struct RawHandle<T> { raw: u32 } // won't compile: E0392, type parameter `T` is never used
+// struct Slice<'a, T> { ptr: NonNull<T>, len: usize } // won't compile: E0392, lifetime parameter `'a` is never used
+
+// Owning: a box of one `T`, which it drops.
+struct Owning<T> {
+    ptr: NonNull<T>,
+    _owns: PhantomData<T>,
+}
+
+impl<T> Owning<T> {
+    fn new(t: T) -> Self {
+        Owning { ptr: NonNull::from(Box::leak(Box::new(t))), _owns: PhantomData }
+    }
+}
+
+impl<T> Drop for Owning<T> {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(self.ptr.as_ptr())) };
+    }
+}
+
+// Borrowing: a view of `len` `T`s that someone else owns, for `'a`.
+struct Borrowing<'a, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    _borrows: PhantomData<&'a T>,
+}
+
+impl<'a, T> Borrowing<'a, T> {
+    fn new(s: &'a [T]) -> Self {
+        Borrowing { ptr: NonNull::from(s).cast(), len: s.len(), _borrows: PhantomData }
+    }
+
+    fn get(&self, i: usize) -> Option<&'a T> {
+        (i < self.len).then(|| unsafe { &*self.ptr.as_ptr().add(i) })
+    }
+}
+
+// Borrowing mutably: the same view, which may be written through.
+struct BorrowingMut<'a, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    _borrows: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> BorrowingMut<'a, T> {
+    fn new(s: &'a mut [T]) -> Self {
+        BorrowingMut { ptr: NonNull::from(&mut *s).cast(), len: s.len(), _borrows: PhantomData }
+    }
+
+    fn set(&mut self, i: usize, t: T) {
+        assert!(i < self.len);
+        unsafe { *self.ptr.as_ptr().add(i) = t };
+    }
+}
+
+// fn-based: a typed handle, which names a `T` but has none...
+struct Handle<T> {
+    raw: u32,
+    _names: PhantomData<fn() -> T>,
+}
+
+// ...a sink, that is only ever handed `T`s...
+struct Sink<T> {
+    taken: usize,
+    _takes: PhantomData<fn(T)>,
+}
+
+impl<T> Sink<T> {
+    fn take(&mut self, t: T) {
+        self.taken += 1;
+        drop(t);
+    }
+}
+
+// ...and a transformer, that takes them and hands them back.
+struct Transform<T> {
+    steps: u32,
+    _maps: PhantomData<fn(T) -> T>,
+}
+
+assert_covariant!(for<'a> Owning<&'a str>);
+assert_covariant!(for<'a, T> Borrowing<'a, T>);
+assert_covariant!(for<'a, 'b> Borrowing<'b, &'a str>);
+assert_covariant!(for<'a, T> BorrowingMut<'a, T>);
+assert_invariant!(for<'a, 'b> BorrowingMut<'b, &'a str>);
+assert_covariant!(for<'a> Handle<&'a str>);
+assert_contravariant!(for<'a> Sink<&'a str>);
+assert_invariant!(for<'a> Transform<&'a str>);
+
+// A NonNull is never Send, so the pointer-holding fixes aren't either,
+// whatever their marker; the fn-based ones are, whatever `T` is.
+assert_not_impl_send!(Owning<u32>, Borrowing<'static, u32>, BorrowingMut<'static, u32>);
+assert_impl_send!(Handle<*const u8>, Sink<*const u8>, Transform<*const u8>);
+
+/// A type parameter (or lifetime) that no field uses is an error, E0392:
+/// rustc can't tell what the type's variance, auto traits or drop check
+/// should be for a parameter it knows nothing about. A PhantomData field
+/// fixes it by saying what the type would hold if it held a `T`: owning
+/// one (`PhantomData<T>`), borrowing one (`PhantomData<&'a T>`, or
+/// `&'a mut T`), or only making (`fn() -> T`), taking (`fn(T)`) or both
+/// (`fn(T) -> T`). Each choice comes with its own variance, and only the
+/// owning one tells dropck that dropping the type drops a `T`.
+#[lesson(
+    title = "E0392, a parameter never used, and the PhantomData that fixes it",
+    tags = ["variance", "dropck"],
+    notes = [
+        "A struct with a type parameter no field uses won't compile: E0392",
+        "Likewise a lifetime parameter: E0392",
+    ],
+)]
+pub fn demo() {
+    recorder::take();
+    let values = [print_on_drop!("up_a", 1), print_on_drop!("up_b", 2)];
+    {
+        let owning = Owning::new(print_on_drop!("up_owned", 3));
+        let view = Borrowing::new(&values);
+        assert_eq!(view.get(1).unwrap().name(), "up_b");
+        assert!(view.get(2).is_none());
+        let handle: Handle<PrintOnDrop<i32>> = Handle { raw: 7, _names: PhantomData };
+        assert_eq!(handle.raw, 7);
+        drop(owning);
+    }
+    assert_eq!(recorder::take_drops(), ["up_owned"]);
+
+    let mut numbers = [1, 2, 3];
+    let mut view = BorrowingMut::new(&mut numbers);
+    view.set(0, 10);
+    assert_eq!(numbers, [10, 2, 3]);
+
+    let mut sink = Sink { taken: 0, _takes: PhantomData };
+    for p in values {
+        sink.take(p);
+    }
+    assert_eq!(sink.taken, 2);
+    assert_eq!(recorder::take_drops(), ["up_a", "up_b"]);
+
+    // Only the owning fix has drop glue for `T`; each is as big as its
+    // fields without the marker.
+    assert!(needs_drop::<Owning<PrintOnDrop<i32>>>());
+    assert!(!needs_drop::<Borrowing<'static, PrintOnDrop<i32>>>());
+    assert!(!needs_drop::<Handle<PrintOnDrop<i32>>>());
+    assert!(!needs_drop::<Sink<PrintOnDrop<i32>>>());
+    assert_eq!(size_of::<Handle<PrintOnDrop<i32>>>(), size_of::<u32>());
+    assert_eq!(size_of::<Transform<String>>(), size_of::<u32>());
+    let transform: Transform<String> = Transform { steps: 1, _maps: PhantomData };
+    assert_eq!(transform.steps, 1);
+}
@@ -0,0 +1,120 @@
+// UnwindSafe and RefUnwindSafe are auto traits too, so PhantomData
+// affects them just as it does Send and Sync, and it's just as easy to
+// forget.
+//
+// They exist as a lint: `catch_unwind` refuses a closure that could
+// observe a value left half-updated by a panic, unless we say
+// AssertUnwindSafe. `&mut T` is !UnwindSafe (a panic mid-update leaves
+// the `T` behind), and so is `&T` when `T` has interior mutability
+// (!RefUnwindSafe). Raw pointers, on the other hand, are UnwindSafe. So
+// a type that mutates through a raw pointer gets no such protection
+// unless its PhantomData says what it really does:
+//
+// - LooseView<'a> claims PhantomData<&'a Vec<i32>>, so it is UnwindSafe, and
+//   catch_unwind lets a panic in `push_pair` go unnoticed.
+// - PairWriter<'a> says PhantomData<&'a mut Vec<i32>>; it isn't, and
+//   catch_unwind makes the caller own up to it.
+
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe, RefUnwindSafe, UnwindSafe};
+use std::ptr::NonNull;
+
+use crate::auto_trait_tests::assert_impl;
+use crate::once_box::OnceBox;
+use crate::MyBox3;
+
+// Both write pairs to a Vec, which should therefore always have an even
+// length. Each writes its first element, then calls `check`, which may
+// panic, before writing the second.
+pub struct LooseView<'a> {
+    vec: NonNull<Vec<i32>>,
+    _pd: PhantomData<&'a Vec<i32>>, // wrong: we write through it
+}
+
+pub struct PairWriter<'a> {
+    vec: NonNull<Vec<i32>>,
+    _pd: PhantomData<&'a mut Vec<i32>>,
+}
+
+fn push_pair(vec: NonNull<Vec<i32>>, a: i32, b: i32, check: impl FnOnce()) {
+    // Safety: both views are made from a `&mut` that they keep borrowed.
+    let vec = unsafe { &mut *vec.as_ptr() };
+    vec.push(a);
+    check();
+    vec.push(b);
+}
+
+impl<'a> LooseView<'a> {
+    pub fn new(vec: &'a mut Vec<i32>) -> Self {
+        LooseView { vec: NonNull::from(vec), _pd: PhantomData }
+    }
+
+    pub fn push_pair(&self, a: i32, b: i32, check: impl FnOnce()) {
+        push_pair(self.vec, a, b, check)
+    }
+}
+
+impl<'a> PairWriter<'a> {
+    pub fn new(vec: &'a mut Vec<i32>) -> Self {
+        PairWriter { vec: NonNull::from(vec), _pd: PhantomData }
+    }
+
+    pub fn push_pair(&self, a: i32, b: i32, check: impl FnOnce()) {
+        push_pair(self.vec, a, b, check)
+    }
+}
+
+pub fn quietly<R>(f: impl FnOnce() -> R) -> R {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let r = f();
+    panic::set_hook(hook);
+    r
+}
+
+crate::registry::demo!("UnwindSafe and RefUnwindSafe through PhantomData", tags: ["auto-traits"]);
+
+pub fn demo() {
+    // The MyBox family: a NonNull<T> and a PhantomData<T>, so both traits
+    // follow `T`'s.
+    assert_impl!(MyBox3<i32>, LooseView<'static> : UnwindSafe);
+    assert_impl!(MyBox3<i32> : RefUnwindSafe);
+    // assert_impl!(MyBox3<&mut i32> : UnwindSafe); // won't compile: E0277, the type `&mut i32` may not be safely transferred across an unwind boundary
+    // assert_impl!(PairWriter<'static> : UnwindSafe); // won't compile: E0277, the type `&'static mut Vec<i32>` may not be safely transferred across an unwind boundary
+
+    // Types with interior mutability are !RefUnwindSafe: OnceBox through
+    // its Cell. MyCellBox isn't even UnwindSafe, as its NonNull
+    // <UnsafeCell<T>> counts as a shared reference to the cell...
+    assert_impl!(OnceBox<i32> : UnwindSafe);
+    // assert_impl!(OnceBox<i32> : RefUnwindSafe); // won't compile: E0277, the type `UnsafeCell<Option<NonNull<i32>>>` may contain interior mutability
+    // assert_impl!(crate::my_cell_box::MyCellBox<i32> : UnwindSafe); // won't compile: E0277, the type `UnsafeCell<i32>` may contain interior mutability
+
+    // ...which is for good reason: sharing one with a closure that might
+    // panic means having to check what state a panic left it in. Here, a
+    // panicking initializer leaves the OnceBox empty, ready to try again.
+    let once = OnceBox::new();
+    // let r = panic::catch_unwind(|| once.get_or_init(|| 1)); // won't compile: E0277, the type `UnsafeCell<Option<NonNull<i32>>>` may contain interior mutability
+    let r = quietly(|| panic::catch_unwind(AssertUnwindSafe(|| *once.get_or_init(|| panic!("init failed")))));
+    assert!(r.is_err() && once.get().is_none());
+    assert_eq!(*once.get_or_init(|| 1), 1);
+
+    // The wrongly-declared view breaks its invariant and nobody is told:
+    let mut v = Vec::new();
+    let view = LooseView::new(&mut v);
+    let r = quietly(|| panic::catch_unwind(move || view.push_pair(1, 2, || panic!("check failed"))));
+    assert!(r.is_err());
+    assert_eq!(v.len(), 1, "odd length, and catch_unwind didn't object");
+
+    // The correctly-declared one can't even be captured without
+    // AssertUnwindSafe, which is where we have to think about what a
+    // panic leaves behind (here: drop the unpaired element).
+    let mut v = Vec::new();
+    let w = PairWriter::new(&mut v);
+    // let r = panic::catch_unwind(move || w.push_pair(1, 2, || panic!("check failed"))); // won't compile: E0277, the type `&mut Vec<i32>` may not be safely transferred across an unwind boundary
+    let r = quietly(|| panic::catch_unwind(AssertUnwindSafe(move || w.push_pair(1, 2, || panic!("check failed")))));
+    if r.is_err() && v.len() % 2 == 1 {
+        v.pop();
+    }
+    assert!(v.is_empty());
+    println!("unwind_safe: LooseView left an odd length; PairWriter's caller had to clean up");
+}
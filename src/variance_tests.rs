@@ -0,0 +1,191 @@
+// Variance is part of a type's API: users rely on being able to pass a
+// MyBox3<&'static str> where a MyBox3<&'a str> is expected, and in the
+// other direction, soundness relies on *not* being able to shorten the
+// payload of a MyCellBox. But nothing in a type definition spells its
+// variance out; it falls out of the field (usually PhantomData) types,
+// and a harmless-looking change to one of them silently changes it.
+//
+// So the macros below spell it out, for a type written in terms of one
+// lifetime (and, optionally, other parameters it is generic over):
+//
+//     assert_covariant!(for<'a> MyBox3<&'a str>);
+//     assert_covariant!(for<'a, T> MyIter<'a, T>);
+//
+// expand to a coercion (in the first lifetime) that only compiles while
+// the type has the variance we promise, and changing that becomes a
+// build error. Invariance can't be checked that way, since it is the
+// *absence* of both coercions: an `assert_invariant!` expands to them
+// only under `--cfg variance_fail`, where each one must fail to build,
+//
+//     RUSTFLAGS="--cfg variance_fail" cargo build
+//
+// with two "lifetime may not live long enough" errors per assertion. See
+// also shorten_payload_mut in my_iter.rs and the cross-brand case in
+// branded.rs.
+
+macro_rules! assert_covariant {
+    (for<$lt:lifetime $(, $g:tt)*> $ty:ty) => {
+        const _: () = {
+            type Alias<$lt $(, $g)*> = $ty;
+            #[allow(dead_code)]
+            fn shorten<'short, 'long: 'short $(, $g)*>(x: Alias<'long $(, $g)*>) -> Alias<'short $(, $g)*> {
+                x
+            }
+        };
+    };
+}
+pub(crate) use assert_covariant;
+
+macro_rules! assert_contravariant {
+    (for<$lt:lifetime $(, $g:tt)*> $ty:ty) => {
+        const _: () = {
+            type Alias<$lt $(, $g)*> = $ty;
+            #[allow(dead_code)]
+            fn lengthen<'short, 'long: 'short $(, $g)*>(x: Alias<'short $(, $g)*>) -> Alias<'long $(, $g)*> {
+                x
+            }
+        };
+    };
+}
+pub(crate) use assert_contravariant;
+
+macro_rules! assert_invariant {
+    (for<$lt:lifetime $(, $g:tt)*> $ty:ty) => {
+        const _: () = {
+            #[allow(dead_code)]
+            type Alias<$lt $(, $g)*> = $ty;
+            #[cfg(variance_fail)]
+            fn shorten<'short, 'long: 'short $(, $g)*>(x: Alias<'long $(, $g)*>) -> Alias<'short $(, $g)*> {
+                x
+            }
+            #[cfg(variance_fail)]
+            fn lengthen<'short, 'long: 'short $(, $g)*>(x: Alias<'short $(, $g)*>) -> Alias<'long $(, $g)*> {
+                x
+            }
+        };
+    };
+}
+pub(crate) use assert_invariant;
+
+// Asserts each row's variance, with the macro it names, and keeps the
+// rows for `report`.
+macro_rules! variances {
+    ($($ty:ident in $param:tt: $assert:ident($($checked:tt)*), $why:literal;)*) => {
+        $($assert!($($checked)*);)*
+        const REPORT: &[(&str, &str, &str, &str)] = &[$((stringify!($ty), stringify!($param), variance!($assert), $why)),*];
+    };
+}
+
+macro_rules! variance {
+    (assert_covariant) => { "covariant" };
+    (assert_contravariant) => { "contravariant" };
+    (assert_invariant) => { "invariant" };
+}
+
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::branded::BrandedIndex;
+use crate::hrtb::Callback;
+use crate::map_ref::MapRef;
+use crate::my_arc::{MyArc, MyWeak};
+use crate::my_cell_box::MyCellBox;
+use crate::my_iter::{MyIter, MyIterMut};
+use crate::my_linked_list::MyLinkedList;
+use crate::my_vec::MyVec;
+use crate::once_box::OnceBox;
+use crate::scoped_threads::Scope;
+use crate::slab::Slab;
+use crate::{print_on_drop, MyBox1, MyBox2, MyBox3, MyUnique};
+
+// The crate's generic types, one parameter at a time, each asserted as
+// it is listed; `cargo run -- variance-report` prints the list, so what
+// it says is what was checked. The lifetime the assertion varies stands
+// for the parameter: in `MyBox3<&'a str>`, it's `T`.
+variances! {
+    MyBox1 in T: assert_covariant(for<'a> MyBox1<&'a str>), "holds a Box<T>, which is covariant";
+    MyBox2 in T: assert_covariant(for<'a> MyBox2<&'a str>), "holds a NonNull<T>, covariant like a *const T";
+    MyBox3 in T: assert_covariant(for<'a> MyBox3<&'a str>), "holds a MyUnique<T>, and so is covariant like Box";
+    MyUnique in T: assert_covariant(for<'a> MyUnique<&'a str>), "a NonNull<T> and a PhantomData<T>, both covariant";
+    MyVec in T: assert_covariant(for<'a> MyVec<&'a str>), "its buffer is a MyUnique<T>, not a *mut T";
+    MyIter in 'a: assert_covariant(for<'a, T> MyIter<'a, T>), "PhantomData<&'a T>: a shared borrow, like slice::Iter";
+    MyIter in T: assert_covariant(for<'a, 'b> MyIter<'b, &'a str>), "PhantomData<&'a T>: only read through";
+    MyIterMut in 'a: assert_covariant(for<'a, T> MyIterMut<'a, T>), "PhantomData<&'a mut T>: a borrow, like slice::IterMut";
+    MyIterMut in T: assert_invariant(for<'a, 'b> MyIterMut<'b, &'a str>), "PhantomData<&'a mut T>: written through, so invariant";
+    MyCellBox in T: assert_invariant(for<'a> MyCellBox<&'a str>), "PhantomData<UnsafeCell<T>>: set through a shared reference";
+    OnceBox in T: assert_invariant(for<'a> OnceBox<&'a str>), "a Cell<Option<NonNull<T>>>, set through a shared reference";
+    MyArc in T: assert_covariant(for<'a> MyArc<&'a str>), "PhantomData<ArcInner<T>>: shared, and never mutated";
+    MyWeak in T: assert_covariant(for<'a> MyWeak<&'a str>), "a NonNull<ArcInner<T>>, and no more";
+    MyLinkedList in T: assert_covariant(for<'a> MyLinkedList<&'a str>), "NonNull links and PhantomData<Box<Node<T>>>, like LinkedList";
+    Slab in T: assert_covariant(for<'a> Slab<&'a str>), "a NonNull to its entries, which it owns";
+    MapRef in 'a: assert_covariant(for<'a> MapRef<'a, (), (), ()>), "PhantomData<&'a T>: borrows the vector, like MyIter";
+    MapRef in T: assert_covariant(for<'a> MapRef<'static, &'a str, (), ()>), "a NonNull<MyVec<T>> and PhantomData<&'a T>";
+    MapRef in U: assert_covariant(for<'a> MapRef<'static, (), &'a str, ()>), "PhantomData<fn() -> U>: only ever returned";
+    Callback in T: assert_contravariant(for<'a> Callback<&'a str, ()>), "PhantomData<fn(&T)>: only ever taken";
+    BrandedIndex in 'brand: assert_invariant(for<'a> BrandedIndex<'a>), "a brand, never to be turned into another one";
+    Scope in 'scope: assert_invariant(for<'a> Scope<'a, 'static>), "PhantomData<&'scope mut &'scope ()>, as std's Scope";
+    Scope in 'env: assert_invariant(for<'a, 'b> Scope<'b, 'a>), "PhantomData<&'env mut &'env ()>, as std's Scope";
+}
+
+// The markers themselves: PhantomData<T> and PhantomData<&T> are
+// covariant; PhantomData<fn(T)> is contravariant, so it *lengthens*;
+// `*mut T`, Cell<T> and fn(T) -> T are invariant.
+assert_covariant!(for<'a> PhantomData<&'a str>);
+assert_covariant!(for<'a> PhantomData<&'a &'static str>);
+assert_contravariant!(for<'a> PhantomData<fn(&'a str)>);
+assert_invariant!(for<'a> PhantomData<*mut &'a str>);
+assert_invariant!(for<'a> PhantomData<Cell<&'a str>>);
+assert_invariant!(for<'a> PhantomData<fn(&'a str) -> &'a str>);
+
+// And what must not compile:
+//
+// assert_covariant!(for<'a> PhantomData<fn(&'a str)>); // won't compile: lifetime may not live long enough
+// assert_contravariant!(for<'a> PhantomData<&'a str>); // won't compile: lifetime may not live long enough
+
+// Trait objects: `dyn Trait + 'a` is covariant in `'a`, like a reference,
+// so a Box of one is too, and so is anything covariant that holds such a
+// Box...
+assert_covariant!(for<'a> Box<dyn Debug + 'a>);
+assert_covariant!(for<'a> MyBox3<dyn Debug + 'a>);
+assert_covariant!(for<'a> MyVec<Box<dyn Debug + 'a>>);
+// ...but not what's invariant, the crate's own invariant types included.
+assert_invariant!(for<'a> MyCellBox<Box<dyn Debug + 'a>>);
+assert_invariant!(for<'a, 'b> &'b mut Box<dyn Debug + 'a>);
+assert_invariant!(for<'a> (Box<dyn Debug + 'a>, BrandedIndex<'a>));
+// One catch: `&mut (dyn Debug + 'long)` is invariant, and still turns into
+// a `&mut (dyn Debug + 'short)`, by unsizing coercion rather than by
+// subtyping. That's sound (nothing can be written through a `&mut dyn`
+// that would need the longer lifetime), and only happens at the pointer
+// holding the trait object itself: one layer further in, as in the `&mut
+// Box<dyn ..>` above, it doesn't. So this passes, though the type isn't
+// covariant, and an assert_invariant would fail under variance_fail:
+assert_covariant!(for<'a, 'b> &'b mut (dyn Debug + 'a));
+
+/// Prints the variance of each of the crate's generic types in each of
+/// its parameters, with why it is what it is.
+pub fn report() {
+    for (ty, param, variance, why) in REPORT {
+        println!("{:<13} {:<7} {:<14} {}", ty, param, variance, why);
+    }
+}
+
+crate::registry::demo!("Variance assertions for everything here", tags: ["variance"]);
+
+pub fn demo() {
+    // Nothing much to run: it's enough that the assertions above compile.
+    // But covariance is why this does: boxes of `'static` trait objects,
+    // in a vector of shorter-lived ones, along with one that borrows.
+    let statics: MyVec<Box<dyn Debug>> = MyVec::new();
+    let v = print_on_drop!("var_v", 13);
+    let mut shorter: MyVec<Box<dyn Debug + '_>> = statics;
+    shorter.push(Box::new(print_on_drop!(borrowing &v)));
+    assert_eq!(shorter.len(), 1);
+
+    // Through a `&mut`, the vector keeps its `'static`, and won't take it:
+    //
+    // let mut statics: MyVec<Box<dyn Debug>> = MyVec::new();
+    // let r = &mut statics;
+    // let v = print_on_drop!("var_v", 13);
+    // r.push(Box::new(print_on_drop!(borrowing &v))); // won't compile: E0597, `v` does not live long enough
+}
@@ -0,0 +1,112 @@
+// The order Vec drops its elements in, for each way of getting rid of
+// them, and MyVec held to the same: each operation is run on both, and
+// whatever MyVec drops differently from Vec is a MyVec bug. One list of
+// expected drops serves as the documentation of Vec's behavior and the
+// conformance test for MyVec's.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use lesson::lesson;
+
+use crate::my_vec::MyVec;
+use crate::temporaries::mark;
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+#[derive(Clone, Debug)]
+enum Op {
+    Drop,
+    Truncate(usize),
+    Clear,
+    // Drains the range, taking (and dropping) this many of its elements
+    // before dropping the Drain.
+    Drain(Range<usize>, usize),
+}
+
+// What both vectors can do, as far as this demo is concerned.
+trait Elements: Sized {
+    fn filled(n: usize) -> Self;
+    fn apply(&mut self, op: Op);
+}
+
+type Element = PrintOnDrop<usize>;
+
+fn element(i: usize) -> Element {
+    print_on_drop!(format!("vo_{}", i), i)
+}
+
+impl Elements for Vec<Element> {
+    fn filled(n: usize) -> Self {
+        (0..n).map(element).collect()
+    }
+
+    fn apply(&mut self, op: Op) {
+        match op {
+            Op::Drop => {}
+            Op::Truncate(len) => self.truncate(len),
+            Op::Clear => self.clear(),
+            Op::Drain(range, take) => self.drain(range).take(take).for_each(drop),
+        }
+    }
+}
+
+impl Elements for MyVec<Element> {
+    fn filled(n: usize) -> Self {
+        let mut v = MyVec::new();
+        (0..n).for_each(|i| v.push(element(i)));
+        v
+    }
+
+    fn apply(&mut self, op: Op) {
+        match op {
+            Op::Drop => {}
+            Op::Truncate(len) => self.truncate(len),
+            Op::Clear => self.clear(),
+            Op::Drain(range, take) => {
+                let mut drain = self.drain(range);
+                (0..take).for_each(|_| drop(drain.next()));
+            }
+        }
+    }
+}
+
+// Everything dropped by `op`, then by dropping the vector after it.
+fn drops<V: Elements>(op: Op) -> Vec<Cow<'static, str>> {
+    let mut v = V::filled(5);
+    recorder::take();
+    v.apply(op);
+    mark("vo_then");
+    drop(v);
+    recorder::take_drops()
+}
+
+fn conforms(op: Op, expected: &[&str]) {
+    let std = drops::<Vec<Element>>(op.clone());
+    assert_eq!(std, expected, "Vec, {:?}", op);
+    let mine = drops::<MyVec<Element>>(op.clone());
+    assert_eq!(mine, std, "MyVec bug: {:?} drops differently from Vec", op);
+}
+
+/// Vec drops its elements front to back, whichever way they go: all of
+/// them when it is dropped or cleared, the ones past `len` when it is
+/// truncated. A Drain drops what it hands out as the caller drops it, and
+/// whatever it didn't hand out when it is itself dropped, again front to
+/// back; the elements after the drained range are moved down, and go with
+/// the vector.
+///
+/// MyVec is run through the same operations, and has to drop exactly what
+/// Vec does, in the same order: where it doesn't, the demo fails, and the
+/// bug is MyVec's.
+#[lesson(
+    title = "Vec and MyVec: drop order of truncate, clear, drain and drop",
+    tags = ["drop-order", "collections"],
+    notes = ["Front-to-back element order is what Vec does, but it isn't a documented guarantee"],
+)]
+pub fn demo() {
+    conforms(Op::Drop, &["vo_then", "vo_0", "vo_1", "vo_2", "vo_3", "vo_4"]);
+    conforms(Op::Truncate(2), &["vo_2", "vo_3", "vo_4", "vo_then", "vo_0", "vo_1"]);
+    conforms(Op::Truncate(7), &["vo_then", "vo_0", "vo_1", "vo_2", "vo_3", "vo_4"]);
+    conforms(Op::Clear, &["vo_0", "vo_1", "vo_2", "vo_3", "vo_4", "vo_then"]);
+    conforms(Op::Drain(1..4, 1), &["vo_1", "vo_2", "vo_3", "vo_then", "vo_0", "vo_4"]);
+    conforms(Op::Drain(0..5, 0), &["vo_0", "vo_1", "vo_2", "vo_3", "vo_4", "vo_then"]);
+}
@@ -0,0 +1,99 @@
+// std's Vec and MyVec, side by side under dropck: each case is written
+// once per container, with the same declarations in the same order, and
+// what compiles for one must compile for the other. vec_drop_order.rs
+// holds MyVec to Vec's behavior at run time; this holds it to Vec's
+// design at compile time, which comes down to the same two things in
+// both: an eyepatched Drop (`#[may_dangle] T`), and a PhantomData<T> (in
+// Vec's Unique, in MyVec's MyUnique) saying that a `T` is dropped anyway.
+
+use lesson::lesson;
+
+use crate::my_vec::MyVec;
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// Value first: the vector is dropped first, and its elements with it,
+// while what they borrow is still there. Always fine.
+fn std_value_first() {
+    let v;
+    let mut vec = Vec::new();
+    print_on_drop!(v = 13);
+    vec.push(print_on_drop!("vd_elem", &v));
+}
+
+fn my_value_first() {
+    let v;
+    let mut vec = MyVec::new();
+    print_on_drop!(v = 13);
+    vec.push(print_on_drop!("vd_elem", &v));
+}
+
+// Container first, with elements that have drop glue of their own: `v` is
+// gone by the time they're dropped, and their Drop could look at it. The
+// PhantomData<T> is what makes both vectors' `T` count, and both refuse:
+//
+// fn std_container_first() {
+//     let mut vec;
+//     let v;
+//     print_on_drop!(v = 13);
+//     vec = Vec::new(); // (Or `MyVec::new()`; the error is the same.)
+//     vec.push(print_on_drop!("vd_elem", &v)); // won't compile: E0597, `v` does not live long enough
+// }
+
+// Container first, with plain references: no drop glue, nothing that
+// could touch `v` when they go, and the eyepatch lets both vectors say
+// so.
+// Line for line as MyVec's, which has no `vec![]`.
+#[allow(clippy::vec_init_then_push)]
+fn std_refs_container_first() {
+    let mut vec;
+    let v;
+    print_on_drop!(v = 13);
+    vec = Vec::new();
+    vec.push(&v);
+}
+
+fn my_refs_container_first() {
+    let mut vec;
+    let v;
+    print_on_drop!(v = 13);
+    vec = MyVec::new();
+    vec.push(&v);
+}
+
+/// Vec and MyVec are accepted and rejected alike by dropck, case for case.
+/// Value first, the vector goes first, and a PrintOnDrop that borrows the
+/// value is fine in it. Container first, the value goes first, and the
+/// borrowing PrintOnDrop would be dropped, Drop impl and all, after what
+/// it borrows: both vectors are refused, because both say (through a
+/// PhantomData<T>) that they drop `T`s.
+///
+/// With plain `&v` elements, container first compiles for both: a
+/// reference has no drop glue, and both Drop impls are eyepatched
+/// (`#[may_dangle] T`), promising not to look at a `T` apart from
+/// dropping it.
+#[lesson(
+    title = "Vec and MyVec under dropck: accepted and rejected alike",
+    tags = ["dropck", "collections"],
+    notes = ["Container first with a borrowing PrintOnDrop won't compile, for Vec or MyVec: E0597"],
+)]
+pub fn demo() {
+    recorder::take();
+    std_value_first();
+    let std = recorder::take_drops();
+    my_value_first();
+    assert_eq!(recorder::take_drops(), std);
+    assert_eq!(std, ["vd_elem", "v"]);
+
+    std_refs_container_first();
+    let std = recorder::take_drops();
+    my_refs_container_first();
+    assert_eq!(recorder::take_drops(), std);
+    assert_eq!(std, ["v"]);
+
+    // Neither is any stricter with owned values, of course.
+    let (mut a, mut b) = (Vec::new(), MyVec::new());
+    a.push(print_on_drop!("vd_owned", 0));
+    b.push(print_on_drop!("vd_owned", 0));
+    drop::<(Vec<PrintOnDrop<i32>>, MyVec<PrintOnDrop<i32>>)>((a, b));
+    assert_eq!(recorder::take_drops(), ["vd_owned", "vd_owned"]);
+}
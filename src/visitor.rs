@@ -0,0 +1,119 @@
+// A fold over a small expression tree. A visitor says what to produce for
+// each kind of node, given what was produced for its children.
+//
+// `Eval<N>` evaluates into any numeric type `N`. It holds no `N`, since
+// `N` only ever appears in its methods' signatures (as `Visitor::Output`),
+// but the parameter still has to be used by some field, or the struct is
+// rejected with E0392. PhantomData<N> is that field.
+
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Neg};
+
+pub enum Expr {
+    Num(i32),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+}
+
+pub trait Visitor {
+    type Output;
+    fn num(&mut self, n: i32) -> Self::Output;
+    fn add(&mut self, a: Self::Output, b: Self::Output) -> Self::Output;
+    fn mul(&mut self, a: Self::Output, b: Self::Output) -> Self::Output;
+    fn neg(&mut self, a: Self::Output) -> Self::Output;
+}
+
+impl Expr {
+    pub fn fold<V: Visitor>(&self, v: &mut V) -> V::Output {
+        match self {
+            Expr::Num(n) => v.num(*n),
+            Expr::Add(a, b) => {
+                let (a, b) = (a.fold(v), b.fold(v));
+                v.add(a, b)
+            }
+            Expr::Mul(a, b) => {
+                let (a, b) = (a.fold(v), b.fold(v));
+                v.mul(a, b)
+            }
+            Expr::Neg(a) => {
+                let a = a.fold(v);
+                v.neg(a)
+            }
+        }
+    }
+}
+
+pub struct Eval<N> {
+    _out: PhantomData<N>,
+}
+
+impl<N> Eval<N> {
+    pub fn new() -> Self {
+        Eval { _out: PhantomData }
+    }
+}
+
+impl<N> Visitor for Eval<N>
+where
+    N: From<i32> + Add<Output = N> + Mul<Output = N> + Neg<Output = N>,
+{
+    type Output = N;
+    fn num(&mut self, n: i32) -> N {
+        N::from(n)
+    }
+    fn add(&mut self, a: N, b: N) -> N {
+        a + b
+    }
+    fn mul(&mut self, a: N, b: N) -> N {
+        a * b
+    }
+    fn neg(&mut self, a: N) -> N {
+        -a
+    }
+}
+
+/// Renders an expression fully parenthesized.
+pub struct Printer;
+
+impl Visitor for Printer {
+    type Output = String;
+    fn num(&mut self, n: i32) -> String {
+        n.to_string()
+    }
+    fn add(&mut self, a: String, b: String) -> String {
+        format!("({} + {})", a, b)
+    }
+    fn mul(&mut self, a: String, b: String) -> String {
+        format!("({} * {})", a, b)
+    }
+    fn neg(&mut self, a: String) -> String {
+        format!("-{}", a)
+    }
+}
+
+crate::registry::demo! {
+    "A fold over an expression tree, with a phantom result type",
+    tags: ["typestate"],
+}
+
+pub fn demo() {
+    use Expr::*;
+    // -(2 * 3) + 100000 * 100000
+    let e = Add(
+        Box::new(Neg(Box::new(Mul(Box::new(Num(2)), Box::new(Num(3)))))),
+        Box::new(Mul(Box::new(Num(100_000)), Box::new(Num(100_000)))),
+    );
+    let text = e.fold(&mut Printer);
+    // Too big for i32, fine in i64 or f64: the output type is chosen by
+    // the caller, at compile time.
+    let as_i64: i64 = e.fold(&mut Eval::new());
+    let as_f64: f64 = e.fold(&mut Eval::new());
+    println!("Visitor: {} = {} = {:e}", text, as_i64, as_f64);
+    assert_eq!(as_i64, 9_999_999_994);
+    assert_eq!(as_f64, 9_999_999_994.0);
+
+    // Without the PhantomData:
+    //
+    // struct BadEval<N> {} // won't compile: E0392, parameter `N` is never used
+}
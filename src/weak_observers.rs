@@ -0,0 +1,130 @@
+// The observer pattern with shared ownership: a Subject in an `Rc`, and
+// Watchers that each keep a `Weak` to it, so that watching doesn't keep
+// it alive (and, were the subject to own its watchers, doesn't make a
+// cycle of the kind rc_cycle.rs leaks). The subject keeps `Weak`s to its
+// watchers in turn, and forgets those that have gone the next time it
+// notifies.
+//
+// When the subject is dropped is no longer a matter of scope: it goes
+// when the last `Rc` does, and an upgraded `Weak` is an `Rc` like any
+// other. Its Drop tells the watchers still there, which, already, can't
+// upgrade: by the time a Drop runs, the strong count is zero. Every step
+// is recorded, as a drop of a PrintOnDrop named for it.
+
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use lesson::lesson;
+
+use crate::{print_on_drop, recorder, PrintOnDrop};
+
+// Records that `who` did `what`.
+fn note(who: &str, what: &str) {
+    drop(print_on_drop!(format!("{}_{}", who, what), ()));
+}
+
+struct Subject {
+    value: PrintOnDrop<Cell<u32>>,
+    watchers: RefCell<Vec<Weak<Watcher>>>,
+}
+
+impl Subject {
+    fn new(name: &'static str) -> Rc<Subject> {
+        Rc::new(Subject { value: print_on_drop!(name, Cell::new(0)), watchers: RefCell::new(Vec::new()) })
+    }
+
+    fn set(&self, v: u32) {
+        self.value.1.set(v);
+        self.notify(Watcher::changed);
+    }
+
+    // Tells every watcher still there, forgetting the rest.
+    fn notify(&self, f: fn(&Watcher)) {
+        self.watchers.borrow_mut().retain(|w| match w.upgrade() {
+            Some(w) => {
+                f(&w);
+                true
+            }
+            None => {
+                note(self.value.name(), "pruned");
+                false
+            }
+        });
+    }
+}
+
+impl Drop for Subject {
+    fn drop(&mut self) {
+        self.notify(Watcher::subject_dropped);
+    }
+}
+
+struct Watcher {
+    name: PrintOnDrop<()>,
+    subject: Weak<Subject>,
+}
+
+impl Watcher {
+    fn new(name: &'static str, subject: &Rc<Subject>) -> Rc<Watcher> {
+        let w = Rc::new(Watcher { name: print_on_drop!(name, ()), subject: Rc::downgrade(subject) });
+        subject.watchers.borrow_mut().push(Rc::downgrade(&w));
+        w
+    }
+
+    fn changed(&self) {
+        let subject = self.subject.upgrade().expect("notified by a subject that is gone");
+        note(self.name.name(), &format!("saw{}", subject.value.1.get()));
+    }
+
+    fn subject_dropped(&self) {
+        assert!(self.subject.upgrade().is_none());
+        note(self.name.name(), "told");
+    }
+
+    fn poll(&self) -> Option<u32> {
+        let value = self.subject.upgrade().map(|s| s.value.1.get());
+        if value.is_none() {
+            note(self.name.name(), "gone");
+        }
+        value
+    }
+}
+
+/// Watchers hold `Weak`s to their subject, and the subject `Weak`s to its
+/// watchers, so neither keeps the other alive: a watcher dropped is simply
+/// forgotten by the subject at its next notification, and the subject is
+/// dropped when its last `Rc` is, wherever in the program that is. A
+/// watcher's upgraded `Weak` counts, so long as it is held.
+///
+/// The subject's Drop tells its watchers it is going, but they can't
+/// upgrade to look at it: the strong count is zero by then, though its
+/// fields (the PrintOnDrop among them) are only dropped after. Afterwards
+/// every upgrade fails, and the `Weak`s keep only the allocation, not the
+/// value, alive.
+#[lesson(
+    title = "Observers with Weak references: upgrades, pruning, and drop notifications",
+    tags = ["drop-order", "collections"],
+)]
+pub fn demo() {
+    recorder::take();
+    let subject = Subject::new("wo_subject");
+    let a = Watcher::new("wo_a", &subject);
+    let b = Watcher::new("wo_b", &subject);
+    subject.set(1);
+    drop(b);
+    subject.set(2);
+    assert_eq!(recorder::take_drops(), ["wo_a_saw1", "wo_b_saw1", "wo_b", "wo_a_saw2", "wo_subject_pruned"]);
+
+    // Upgraded, a Weak keeps the subject alive past its own `Rc`.
+    let held = a.subject.upgrade().unwrap();
+    drop(subject);
+    assert_eq!(a.poll(), Some(2));
+    assert!(recorder::take_drops().is_empty());
+    drop(held);
+    assert_eq!(recorder::take_drops(), ["wo_a_told", "wo_subject"]);
+
+    assert_eq!(a.poll(), None);
+    assert_eq!((Weak::strong_count(&a.subject), Weak::weak_count(&a.subject)), (0, 0));
+    drop(a);
+    assert_eq!(recorder::take_drops(), ["wo_a_gone", "wo_a"]);
+}
@@ -0,0 +1,120 @@
+// The claim underlying everything else here: PhantomData<T> is free. It
+// is a zero-sized type with alignment 1, so adding one to a struct changes
+// what the type checker (variance, dropck, auto traits) thinks of it and
+// nothing about what the machine sees.
+//
+// Each of the crate's phantom-bearing types is compared with a "twin"
+// holding the same fields minus the PhantomData: same size, same
+// alignment, and (checked through Option) same niche. The element type is
+// varied too, in case a marker like PhantomData<u128> somehow dragged in
+// `T`'s alignment. Then a MyIter, whose `&'a T` exists only as a
+// PhantomData, is timed against a bare pointer loop over the same buffer.
+// (Compare those timings from a --release build: in a debug build none of
+// MyIter's small methods get inlined, and that, not the marker, is what
+// makes it slower there.)
+
+use std::cell::{Cell, UnsafeCell};
+use std::hint::black_box;
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of};
+use std::ptr::NonNull;
+use std::sync::atomic::AtomicPtr;
+use std::time::{Duration, Instant};
+
+use crate::encoder::{Encoder, Json};
+use crate::endian::{BigEndian, U32};
+use crate::fd_state::{Fd, Open};
+use crate::my_cell_box::MyCellBox;
+use crate::my_iter::{MyIter, MyIterMut};
+use crate::my_linked_list::MyLinkedList;
+use crate::my_unique::{MyUnique, Raw};
+use crate::my_vec::MyVec;
+use crate::once_box::{OnceBox, SyncOnceBox};
+use crate::slab::Slab;
+use crate::tagged_box::TaggedBox;
+use crate::transaction::{Connection, Transaction};
+#[cfg(not(repr = "const-ptr"))]
+use crate::MyBox2;
+use crate::MyBox3;
+
+// Asserts that `$with` and `$without` are laid out alike.
+macro_rules! twins {
+    ($with:ty, $without:ty) => {
+        assert_eq!(size_of::<$with>(), size_of::<$without>(), "size of {}", stringify!($with));
+        assert_eq!(align_of::<$with>(), align_of::<$without>(), "align of {}", stringify!($with));
+        assert_eq!(size_of::<Option<$with>>(), size_of::<Option<$without>>(), "niche in {}", stringify!($with));
+    };
+}
+
+fn check_twins<T: 'static>() {
+    // The marker itself, whatever it marks.
+    twins!(PhantomData<T>, ());
+    twins!((u8, PhantomData<T>), u8);
+
+    // Raw<T> is MyUnique's pointer, in whichever representation.
+    twins!(MyUnique<T>, Raw<T>);
+    twins!(MyBox3<T>, Raw<T>);
+    // MyBox2 is MyBox3 before the PhantomData went in (and with it, before
+    // a choice of representation).
+    #[cfg(not(repr = "const-ptr"))]
+    twins!(MyBox3<T>, MyBox2<T>);
+    twins!(MyCellBox<T>, NonNull<UnsafeCell<T>>);
+    twins!(TaggedBox<T>, NonNull<u8>);
+    twins!(OnceBox<T>, Cell<Option<NonNull<T>>>);
+    twins!(SyncOnceBox<T>, AtomicPtr<T>);
+
+    twins!(MyVec<T>, (Raw<T>, usize, usize));
+    twins!(MyIter<'static, T>, (NonNull<T>, *const T));
+    twins!(MyIterMut<'static, T>, (NonNull<T>, *mut T));
+    twins!(Slab<T>, (NonNull<T>, usize, usize, usize, usize));
+    twins!(MyLinkedList<T>, (Option<NonNull<T>>, Option<NonNull<T>>, usize));
+}
+
+fn time_my_iter(buf: &MyVec<u64>) -> Duration {
+    let start = Instant::now();
+    let sum: u64 = black_box(buf).iter().sum();
+    let elapsed = start.elapsed();
+    assert_eq!(black_box(sum), (buf.len() as u64) * (buf.len() as u64 - 1) / 2);
+    elapsed
+}
+
+// The same loop MyIter runs, without the PhantomData (or the lifetime).
+fn time_raw_loop(buf: &MyVec<u64>) -> Duration {
+    let start = Instant::now();
+    let mut p = black_box(buf).as_ptr();
+    let end = unsafe { p.add(buf.len()) };
+    let mut sum = 0;
+    while p != end {
+        unsafe {
+            sum += *p;
+            p = p.add(1);
+        }
+    }
+    let elapsed = start.elapsed();
+    assert_eq!(black_box(sum), (buf.len() as u64) * (buf.len() as u64 - 1) / 2);
+    elapsed
+}
+
+crate::registry::demo!("PhantomData is free: layout and codegen", tags: ["layout", "bench"]);
+
+pub fn demo() {
+    check_twins::<u8>();
+    check_twins::<u64>();
+    check_twins::<u128>();
+    check_twins::<()>();
+    check_twins::<String>();
+
+    // And the pure markers: typestate, byte order, format.
+    twins!(Fd<Open>, i32);
+    twins!(U32<BigEndian>, [u8; 4]);
+    twins!(Encoder<Json>, (Vec<u8>, usize));
+    twins!(Transaction<'static>, (NonNull<Connection>, Vec<(&'static str, i32)>));
+
+    let mut buf = MyVec::new();
+    for i in 0..1_000_000u64 {
+        buf.push(i);
+    }
+    println!("PhantomData is free: MyIter sums a million u64s in {:?} (raw loop: {:?})",
+             time_my_iter(&buf),
+             time_raw_loop(&buf));
+}